@@ -0,0 +1,153 @@
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+
+use crate::config;
+use crate::db;
+use crate::db::encryption::{self, FileState};
+use crate::db::schema;
+
+/// `claude-memory backup [--to <file>]`
+pub fn backup(to: Option<&Path>) -> anyhow::Result<()> {
+    let project_dir = config::detect_project_dir()?;
+    let db_path = config::db_path(&project_dir)?;
+    anyhow::ensure!(db_path.exists(), "No memory database found at {}", db_path.display());
+
+    let dest = match to {
+        Some(path) => path.to_path_buf(),
+        None => timestamped_path(&project_dir, "memory")?,
+    };
+    anyhow::ensure!(!dest.exists(), "Refusing to overwrite {}", dest.display());
+
+    let conn = db::open_readonly(&db_path)?;
+    db::backup_to(&conn, &dest)?;
+
+    let size = std::fs::metadata(&dest)?.len();
+    println!("Backed up {} to {} ({} bytes)", db_path.display(), dest.display(), size);
+    Ok(())
+}
+
+/// `claude-memory restore <file>`: replace the project's database with a
+/// backup. The current database is snapshotted first.
+pub fn restore(file: &Path) -> anyhow::Result<()> {
+    let project_dir = config::detect_project_dir()?;
+    let db_path = config::db_path(&project_dir)?;
+
+    check_backup_file(file)?;
+
+    // Pages are copied as they are, so both sides must be encrypted alike
+    let current = encryption::file_state(&db_path);
+    let restored = encryption::file_state(file);
+    if current != FileState::New && current != restored {
+        let (kind, command) = match restored {
+            FileState::Encrypted => ("encrypted", "encrypt"),
+            _ => ("not encrypted", "decrypt"),
+        };
+        anyhow::bail!(
+            "{} is {} but {} is not; run `claude-memory {}` first",
+            file.display(),
+            kind,
+            db_path.display(),
+            command
+        );
+    }
+
+    if db_path.exists() {
+        let snapshot = timestamped_path(&project_dir, "memory-pre-restore")?;
+        match db::open_readonly(&db_path).and_then(|conn| db::backup_to(&conn, &snapshot)) {
+            Ok(()) => println!("Saved the current database to {}", snapshot.display()),
+            // The database being restored is often the broken one
+            Err(e) => eprintln!("Warning: could not snapshot the current database: {}", e),
+        }
+    } else if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Restore through SQLite rather than copying the file, so open connections
+    // (e.g. a running MCP server) see the new contents instead of a stale file
+    let source = db::open_readonly(file)?;
+    let mut conn = Connection::open(&db_path)?;
+    encryption::match_encryption(&conn, file)?;
+    conn.busy_timeout(db::BUSY_TIMEOUT)?;
+    db::copy_pages(&source, &mut conn)?;
+    drop(conn);
+
+    // Bring backups from older versions up to the current schema
+    let conn = db::open(&db_path)?;
+    let (sessions, _, _) = db::sessions::session_stats(&conn)?;
+    let notes = db::notes::note_count(&conn)?;
+    println!(
+        "Restored {} from {} ({} sessions, {} notes)",
+        db_path.display(),
+        file.display(),
+        sessions,
+        notes
+    );
+    Ok(())
+}
+
+/// Make sure `file` is an intact memory database this version can read.
+fn check_backup_file(file: &Path) -> anyhow::Result<()> {
+    anyhow::ensure!(file.is_file(), "Not a file: {}", file.display());
+
+    let conn = db::open_readonly(file)
+        .map_err(|e| anyhow::anyhow!("Cannot open {}: {}", file.display(), e))?;
+    let check: String = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|e| anyhow::anyhow!("{} is not a SQLite database: {}", file.display(), e))?;
+    anyhow::ensure!(check == "ok", "{} is damaged: {}", file.display(), check);
+
+    let has_sessions: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'sessions'",
+        [],
+        |row| row.get(0),
+    )?;
+    anyhow::ensure!(has_sessions, "{} is not a claude-memory database", file.display());
+
+    let version = schema::get_schema_version(&conn)?;
+    anyhow::ensure!(
+        version <= schema::CURRENT_VERSION,
+        "{} uses schema v{}, newer than this claude-memory (v{}); upgrade first",
+        file.display(),
+        version,
+        schema::CURRENT_VERSION
+    );
+    Ok(())
+}
+
+/// `<project>/.claude/memory-backups/<prefix>-<timestamp>.db`
+fn timestamped_path(project_dir: &Path, prefix: &str) -> anyhow::Result<PathBuf> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    Ok(config::backup_dir(project_dir)?.join(format!("{}-{}.db", prefix, stamp)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_includes_uncheckpointed_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = db::open(&dir.path().join("memory.db")).unwrap();
+        db::notes::insert_note(&conn, "still in the WAL", &[], None).unwrap();
+
+        let dest = dir.path().join("backups").join("copy.db");
+        db::backup_to(&db::open_readonly(&dir.path().join("memory.db")).unwrap(), &dest).unwrap();
+
+        check_backup_file(&dest).unwrap();
+        let copy = db::open(&dest).unwrap();
+        assert_eq!(db::notes::note_count(&copy).unwrap(), 1);
+    }
+
+    #[test]
+    fn rejects_non_memory_databases() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = dir.path().join("notes.txt");
+        std::fs::write(&text, "not a database at all, just some text").unwrap();
+        assert!(check_backup_file(&text).is_err());
+
+        let other = dir.path().join("other.db");
+        Connection::open(&other).unwrap().execute_batch("CREATE TABLE t (x);").unwrap();
+        assert!(check_backup_file(&other).unwrap_err().to_string().contains("not a claude-memory"));
+    }
+}
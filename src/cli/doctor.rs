@@ -0,0 +1,182 @@
+use std::path::Path;
+
+use rusqlite::Connection;
+
+use crate::config;
+use crate::db;
+use crate::db::encryption::{self, Key};
+use crate::db::schema;
+
+/// Full-text indexes checked (and rebuilt by `--fix`).
+const FTS_TABLES: [&str; 2] = ["sessions_fts", "notes_fts"];
+
+/// `claude-memory doctor [--fix]`
+pub fn run(fix: bool) -> anyhow::Result<()> {
+    let project_dir = config::detect_project_dir()?;
+    let db_path = config::db_path(&project_dir)?;
+
+    println!("claude-memory doctor");
+    println!("====================");
+    println!("Database:     {}", db_path.display());
+
+    if !db_path.exists() {
+        println!("\nNo memory database found. Run `claude-memory install` first.");
+        return Ok(());
+    }
+
+    let key = encryption::configured_key()?;
+    let (problems, damaged) = inspect(&db_path, key.as_ref(), fix)?;
+
+    if problems == 0 {
+        println!("\nNo problems found.");
+        return Ok(());
+    }
+
+    println!();
+    if !fix {
+        println!("Run `claude-memory doctor --fix` to repair what can be repaired.");
+    }
+    if damaged {
+        println!(
+            "If the database stays damaged, restore a backup: `claude-memory restore <file>` (see {}).",
+            config::backup_dir(&project_dir)?.display()
+        );
+    }
+    anyhow::bail!("{} problem(s) found", problems)
+}
+
+/// Check (and with `fix`, repair) the database, printing a line per check.
+/// Returns the number of problems left and whether the integrity check failed.
+fn inspect(db_path: &Path, key: Option<&Key>, fix: bool) -> anyhow::Result<(usize, bool)> {
+    // Not migrated on open, so an outdated database can still be inspected
+    let conn = db::open_unmigrated_with(db_path, key)?;
+
+    let mut problems = 0;
+
+    let integrity = integrity_check(&conn)?;
+    match &integrity {
+        Ok(()) => println!("Integrity:    ok"),
+        Err(errors) if fix => {
+            // Damaged indexes are the one kind of corruption REINDEX can repair
+            conn.execute_batch("REINDEX;")?;
+            match integrity_check(&conn)? {
+                Ok(()) => println!("Integrity:    fixed (reindexed)"),
+                Err(_) => {
+                    problems += 1;
+                    println!("Integrity:    FAILED\n{}", indent(errors));
+                }
+            }
+        }
+        Err(errors) => {
+            problems += 1;
+            println!("Integrity:    FAILED\n{}", indent(errors));
+        }
+    }
+
+    let version = schema::get_schema_version(&conn)?;
+    if version == schema::CURRENT_VERSION {
+        println!("Schema:       v{} (current)", version);
+    } else if version > schema::CURRENT_VERSION {
+        problems += 1;
+        println!(
+            "Schema:       v{} is newer than this claude-memory (v{}); upgrade claude-memory",
+            version,
+            schema::CURRENT_VERSION
+        );
+    } else if fix {
+        schema::ensure_schema(&conn)?;
+        println!("Schema:       migrated v{} -> v{}", version, schema::CURRENT_VERSION);
+    } else {
+        problems += 1;
+        println!(
+            "Schema:       v{} is outdated (current: v{}); migrated on next open or with --fix",
+            version,
+            schema::CURRENT_VERSION
+        );
+    }
+
+    for table in FTS_TABLES {
+        let label = format!("{}:", table);
+        match fts_check(&conn, table) {
+            Ok(()) => println!("{:<13} ok", label),
+            Err(e) if fix => {
+                conn.execute(&format!("INSERT INTO {table}({table}) VALUES('rebuild')"), [])?;
+                match fts_check(&conn, table) {
+                    Ok(()) => println!("{:<13} fixed (rebuilt)", label),
+                    Err(_) => {
+                        problems += 1;
+                        println!("{:<13} FAILED: {}", label, e);
+                    }
+                }
+            }
+            Err(e) => {
+                problems += 1;
+                println!("{:<13} FAILED: {}", label, e);
+            }
+        }
+    }
+
+    Ok((problems, integrity.is_err()))
+}
+
+/// `PRAGMA integrity_check`, returning the reported errors if it isn't clean.
+fn integrity_check(conn: &Connection) -> anyhow::Result<Result<(), Vec<String>>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(if rows == ["ok"] { Ok(()) } else { Err(rows) })
+}
+
+/// FTS5 `integrity-check`. `rank = 1` also compares the index against its
+/// content table, which catches rows written while triggers were missing.
+fn fts_check(conn: &Connection, table: &str) -> Result<(), String> {
+    conn.execute(&format!("INSERT INTO {table}({table}, rank) VALUES('integrity-check', 1)"), [])
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn indent(lines: &[String]) -> String {
+    lines.iter().map(|l| format!("  {}", l)).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_and_rebuilds_stale_fts_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = db::open(&dir.path().join("memory.db")).unwrap();
+        assert_eq!(fts_check(&conn, "sessions_fts"), Ok(()));
+
+        // A row written without its trigger leaves the index out of sync
+        conn.execute_batch(
+            "DROP TRIGGER sessions_ai;
+             INSERT INTO sessions (id, project_dir, started_at, user_prompts)
+             VALUES ('s1', '/p', '2026-01-01', '[\"unindexed\"]');",
+        ).unwrap();
+        assert!(fts_check(&conn, "sessions_fts").is_err());
+
+        db::rebuild_fts(&conn).unwrap();
+        assert_eq!(fts_check(&conn, "sessions_fts"), Ok(()));
+        assert_eq!(integrity_check(&conn).unwrap(), Ok(()));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn inspects_an_encrypted_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memory.db");
+        let key = Key::for_tests("correct horse");
+        {
+            let conn = db::open_unmigrated_with(&path, Some(&key)).unwrap();
+            schema::ensure_schema(&conn).unwrap();
+            db::notes::insert_note(&conn, "encrypted note", &[], None).unwrap();
+        }
+        assert_eq!(encryption::file_state(&path), encryption::FileState::Encrypted);
+
+        assert_eq!(inspect(&path, Some(&key), false).unwrap(), (0, false));
+        assert!(inspect(&path, None, false).is_err());
+    }
+}
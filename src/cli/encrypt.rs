@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use crate::config;
+use crate::db;
+use crate::db::encryption::{self, FileState, Key, KeySource};
+
+/// `claude-memory encrypt`: convert the project's database to an encrypted
+/// one, keyed with the configured key.
+pub fn encrypt() -> anyhow::Result<()> {
+    encryption::ensure_supported()?;
+    let project_dir = config::detect_project_dir()?;
+    let db_path = config::db_path(&project_dir)?;
+    match encryption::file_state(&db_path) {
+        FileState::New => anyhow::bail!("No memory database found at {}", db_path.display()),
+        FileState::Encrypted => anyhow::bail!("{} is already encrypted", db_path.display()),
+        FileState::Plaintext => {}
+    }
+
+    let key = encryption::configured_key()?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "No key configured: set {} or write a key to {}",
+            encryption::KEY_ENV,
+            encryption::key_file_path().map_or_else(String::new, |p| p.display().to_string())
+        )
+    })?;
+    if let KeySource::File(path) = &key.source {
+        let path = path.canonicalize()?;
+        anyhow::ensure!(
+            !path.starts_with(project_dir.canonicalize()?),
+            "The key file {} is inside the project; keep it outside the repository",
+            path.display()
+        );
+        warn_if_readable_by_others(&path);
+    }
+
+    convert(&db_path, Some(&key))?;
+    println!("Encrypted {}.", db_path.display());
+    println!(
+        "Backups in {} are still plaintext; delete them or take a new backup.",
+        config::backup_dir(&project_dir)?.display()
+    );
+    Ok(())
+}
+
+/// `claude-memory decrypt`: convert the project's database back to plaintext.
+pub fn decrypt() -> anyhow::Result<()> {
+    encryption::ensure_supported()?;
+    let project_dir = config::detect_project_dir()?;
+    let db_path = config::db_path(&project_dir)?;
+    match encryption::file_state(&db_path) {
+        FileState::New => anyhow::bail!("No memory database found at {}", db_path.display()),
+        FileState::Plaintext => anyhow::bail!("{} is not encrypted", db_path.display()),
+        FileState::Encrypted => {}
+    }
+
+    convert(&db_path, None)?;
+    println!("Decrypted {}.", db_path.display());
+    Ok(())
+}
+
+/// Rewrite the database into a new file with the given encryption and move it
+/// into place. A running MCP server notices the new file and reopens it.
+fn convert(db_path: &Path, key: Option<&Key>) -> anyhow::Result<()> {
+    let conn = db::open(db_path)?;
+    // Fold the WAL into the file so the export sees everything
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+
+    let converted = db_path.with_extension("db.converting");
+    let _ = std::fs::remove_file(&converted);
+    if let Err(e) = encryption::export(&conn, &converted, key) {
+        let _ = std::fs::remove_file(&converted);
+        return Err(e);
+    }
+    drop(conn);
+
+    std::fs::rename(&converted, db_path)?;
+    // The old WAL and shared-memory files belong to the replaced file
+    for suffix in ["-wal", "-shm"] {
+        let mut path = db_path.as_os_str().to_owned();
+        path.push(suffix);
+        let _ = std::fs::remove_file(path);
+    }
+
+    // Make sure the result opens with the current key setup
+    db::open(db_path)?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn warn_if_readable_by_others(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Ok(meta) = std::fs::metadata(path) {
+        if meta.permissions().mode() & 0o077 != 0 {
+            eprintln!("Warning: {} is readable by other users; run `chmod 600` on it", path.display());
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_if_readable_by_others(_path: &Path) {}
@@ -0,0 +1,391 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::db;
+use crate::db::notes::NoteRow;
+use crate::db::sessions::SessionRow;
+use crate::mcp::budget::DETAIL_LEVELS;
+use crate::mcp::tools;
+
+use super::sessions::parse_since;
+
+/// Directory written by the Markdown and CSV formats when `--out` isn't given.
+const DEFAULT_OUT_DIR: &str = "memory-export";
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum Format {
+    /// One JSON object per line, sessions then notes
+    Jsonl,
+    /// One file per session plus a notes index
+    Markdown,
+    /// sessions.csv and notes.csv
+    Csv,
+}
+
+/// One line of a JSON Lines export. JSON columns are decoded so other tools
+/// don't have to parse JSON inside JSON.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Record {
+    Session(Box<SessionRecord>),
+    Note(NoteRecord),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub id: String,
+    pub project_dir: String,
+    pub git_branch: Option<String>,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub duration_seconds: Option<i64>,
+    pub model: Option<String>,
+    pub user_prompts: Vec<String>,
+    pub files_modified: Vec<String>,
+    pub files_read: Vec<String>,
+    pub commands_run: Vec<String>,
+    pub git_commits: Vec<String>,
+    pub tools_used: BTreeMap<String, u32>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub summary: Option<String>,
+    pub transcript_path: Option<String>,
+    pub ingested_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteRecord {
+    pub id: String,
+    pub session_id: Option<String>,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub created_at: String,
+    pub pinned: bool,
+}
+
+impl From<&SessionRow> for SessionRecord {
+    fn from(s: &SessionRow) -> Self {
+        let list = |json: &str| serde_json::from_str(json).unwrap_or_default();
+        SessionRecord {
+            id: s.id.clone(),
+            project_dir: s.project_dir.clone(),
+            git_branch: s.git_branch.clone(),
+            started_at: s.started_at.clone(),
+            ended_at: s.ended_at.clone(),
+            duration_seconds: s.duration_seconds,
+            model: s.model.clone(),
+            user_prompts: list(&s.user_prompts),
+            files_modified: list(&s.files_modified),
+            files_read: list(&s.files_read),
+            commands_run: list(&s.commands_run),
+            git_commits: list(&s.git_commits),
+            tools_used: serde_json::from_str(&s.tools_used).unwrap_or_default(),
+            input_tokens: s.input_tokens,
+            output_tokens: s.output_tokens,
+            summary: s.summary.clone(),
+            transcript_path: s.transcript_path.clone(),
+            ingested_at: s.ingested_at.clone(),
+            scope: s.scope.clone(),
+        }
+    }
+}
+
+impl From<&NoteRow> for NoteRecord {
+    fn from(n: &NoteRow) -> Self {
+        NoteRecord {
+            id: n.id.clone(),
+            session_id: n.session_id.clone(),
+            content: n.content.clone(),
+            tags: serde_json::from_str(&n.tags).unwrap_or_default(),
+            created_at: n.created_at.clone(),
+            pinned: n.pinned,
+        }
+    }
+}
+
+impl SessionRecord {
+    /// Back to a database row, re-encoding the JSON columns.
+    pub fn into_row(self) -> anyhow::Result<SessionRow> {
+        Ok(SessionRow {
+            user_prompts: serde_json::to_string(&self.user_prompts)?,
+            files_modified: serde_json::to_string(&self.files_modified)?,
+            files_read: serde_json::to_string(&self.files_read)?,
+            commands_run: serde_json::to_string(&self.commands_run)?,
+            git_commits: serde_json::to_string(&self.git_commits)?,
+            tools_used: serde_json::to_string(&self.tools_used)?,
+            id: self.id,
+            project_dir: self.project_dir,
+            git_branch: self.git_branch,
+            started_at: self.started_at,
+            ended_at: self.ended_at,
+            duration_seconds: self.duration_seconds,
+            model: self.model,
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            summary: self.summary,
+            transcript_path: self.transcript_path,
+            ingested_at: self.ingested_at,
+            scope: self.scope,
+        })
+    }
+}
+
+impl NoteRecord {
+    /// Back to a database row, re-encoding the tags.
+    pub fn into_row(self) -> anyhow::Result<NoteRow> {
+        Ok(NoteRow {
+            tags: serde_json::to_string(&self.tags)?,
+            id: self.id,
+            session_id: self.session_id,
+            content: self.content,
+            created_at: self.created_at,
+            pinned: self.pinned,
+        })
+    }
+}
+
+/// `claude-memory export --format <fmt> [--since] [--out]`
+pub fn run(format: Format, since: Option<&str>, out: Option<&Path>) -> anyhow::Result<()> {
+    let conn = match super::open_project_db()? {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+
+    let since = since.map(parse_since).transpose()?;
+    let sessions = db::sessions::sessions_since(&conn, since.as_deref())?;
+    let notes = db::notes::notes_since(&conn, since.as_deref())?;
+
+    let written = match format {
+        Format::Jsonl => match out {
+            Some(path) => {
+                let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+                write_jsonl(&mut file, &sessions, &notes)?;
+                file.flush()?;
+                path.to_path_buf()
+            }
+            None => {
+                write_jsonl(&mut std::io::stdout().lock(), &sessions, &notes)?;
+                return Ok(());
+            }
+        },
+        Format::Markdown => {
+            let dir = out_dir(out);
+            write_markdown(&dir, &sessions, &notes)?;
+            dir
+        }
+        Format::Csv => {
+            let dir = out_dir(out);
+            write_csv(&dir, &sessions, &notes)?;
+            dir
+        }
+    };
+
+    // Progress goes to stderr so it never mixes with exported data
+    eprintln!(
+        "Exported {} session(s) and {} note(s) to {}",
+        sessions.len(),
+        notes.len(),
+        written.display()
+    );
+    Ok(())
+}
+
+fn out_dir(out: Option<&Path>) -> PathBuf {
+    out.map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_OUT_DIR))
+}
+
+pub fn write_jsonl(
+    out: &mut impl Write,
+    sessions: &[SessionRow],
+    notes: &[NoteRow],
+) -> anyhow::Result<()> {
+    for session in sessions {
+        serde_json::to_writer(&mut *out, &Record::Session(Box::new(session.into())))?;
+        writeln!(out)?;
+    }
+    for note in notes {
+        serde_json::to_writer(&mut *out, &Record::Note(note.into()))?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Write `sessions/<date>-<id>.md` for each session and a `notes.md` index.
+fn write_markdown(dir: &Path, sessions: &[SessionRow], notes: &[NoteRow]) -> anyhow::Result<()> {
+    let sessions_dir = dir.join("sessions");
+    std::fs::create_dir_all(&sessions_dir)?;
+
+    for session in sessions {
+        let date = session.started_at.get(..10).unwrap_or("unknown");
+        let path = sessions_dir.join(format!("{}-{}.md", date, safe_file_name(&session.id)));
+        let mut content = tools::format_session_detail(session, &DETAIL_LEVELS[0]);
+        if let Some(transcript) = &session.transcript_path {
+            content.push_str(&format!("\n**Transcript:** `{}`\n", transcript));
+        }
+        std::fs::write(path, content)?;
+    }
+
+    let mut index = format!("# Notes ({})\n\n", notes.len());
+    for note in notes {
+        index.push_str(&tools::format_note(note, &DETAIL_LEVELS[0]));
+        index.push_str(&format!("_id: `{}`", note.id));
+        if let Some(session_id) = &note.session_id {
+            index.push_str(&format!(" · session: `{}`", session_id));
+        }
+        index.push_str("_\n\n");
+    }
+    std::fs::write(dir.join("notes.md"), index)?;
+    Ok(())
+}
+
+/// Write `sessions.csv` and `notes.csv`. List columns hold one item per line.
+fn write_csv(dir: &Path, sessions: &[SessionRow], notes: &[NoteRow]) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    let mut out = String::new();
+    push_csv_row(
+        &mut out,
+        &[
+            "id", "project_dir", "git_branch", "started_at", "ended_at", "duration_seconds",
+            "model", "user_prompts", "files_modified", "files_read", "commands_run",
+            "git_commits", "tools_used", "input_tokens", "output_tokens", "summary",
+            "transcript_path", "ingested_at", "scope",
+        ],
+    );
+    for session in sessions {
+        let r = SessionRecord::from(session);
+        let tools_used: Vec<String> = r.tools_used.iter().map(|(k, v)| format!("{}:{}", k, v)).collect();
+        push_csv_row(
+            &mut out,
+            &[
+                &r.id,
+                &r.project_dir,
+                r.git_branch.as_deref().unwrap_or(""),
+                &r.started_at,
+                r.ended_at.as_deref().unwrap_or(""),
+                &r.duration_seconds.map(|d| d.to_string()).unwrap_or_default(),
+                r.model.as_deref().unwrap_or(""),
+                &r.user_prompts.join("\n"),
+                &r.files_modified.join("\n"),
+                &r.files_read.join("\n"),
+                &r.commands_run.join("\n"),
+                &r.git_commits.join("\n"),
+                &tools_used.join("\n"),
+                &r.input_tokens.to_string(),
+                &r.output_tokens.to_string(),
+                r.summary.as_deref().unwrap_or(""),
+                r.transcript_path.as_deref().unwrap_or(""),
+                &r.ingested_at,
+                r.scope.as_deref().unwrap_or(""),
+            ],
+        );
+    }
+    std::fs::write(dir.join("sessions.csv"), out)?;
+
+    let mut out = String::new();
+    push_csv_row(&mut out, &["id", "session_id", "content", "tags", "created_at", "pinned"]);
+    for note in notes {
+        let r = NoteRecord::from(note);
+        push_csv_row(
+            &mut out,
+            &[
+                &r.id,
+                r.session_id.as_deref().unwrap_or(""),
+                &r.content,
+                &r.tags.join("\n"),
+                &r.created_at,
+                if r.pinned { "true" } else { "false" },
+            ],
+        );
+    }
+    std::fs::write(dir.join("notes.csv"), out)?;
+    Ok(())
+}
+
+/// Append one RFC 4180 row: fields with commas, quotes or newlines are quoted.
+fn push_csv_row(out: &mut String, fields: &[&str]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+/// Keep IDs usable as file names on every platform.
+fn safe_file_name(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_quotes_only_when_needed() {
+        let mut out = String::new();
+        push_csv_row(&mut out, &["plain", "a,b", "say \"hi\"", "two\nlines"]);
+        assert_eq!(out, "plain,\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\"\r\n");
+    }
+
+    #[test]
+    fn csv_has_the_session_scope() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = db::open(&dir.path().join("memory.db")).unwrap();
+        conn.execute(
+            "INSERT INTO sessions (id, project_dir, started_at, scope) VALUES ('s1', '/p', '2026-01-01', 'api')",
+            [],
+        ).unwrap();
+
+        let sessions = db::sessions::sessions_since(&conn, None).unwrap();
+        write_csv(dir.path(), &sessions, &[]).unwrap();
+        let csv = std::fs::read_to_string(dir.path().join("sessions.csv")).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].ends_with(",ingested_at,scope"));
+        assert!(lines[1].ends_with(",api"));
+    }
+
+    #[test]
+    fn jsonl_decodes_json_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = db::open(&dir.path().join("memory.db")).unwrap();
+        conn.execute(
+            "INSERT INTO sessions (id, project_dir, started_at, user_prompts, tools_used)
+             VALUES ('s1', '/p', '2026-01-01', '[\"fix it\"]', '{\"Bash\":2}')",
+            [],
+        ).unwrap();
+        db::notes::insert_note(&conn, "remember", &["x".to_string()], None).unwrap();
+
+        let sessions = db::sessions::sessions_since(&conn, None).unwrap();
+        let notes = db::notes::notes_since(&conn, None).unwrap();
+        let mut out = Vec::new();
+        write_jsonl(&mut out, &sessions, &notes).unwrap();
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["type"], "session");
+        assert_eq!(lines[0]["user_prompts"][0], "fix it");
+        assert_eq!(lines[0]["tools_used"]["Bash"], 2);
+        assert_eq!(lines[1]["type"], "note");
+        assert_eq!(lines[1]["tags"][0], "x");
+    }
+}
@@ -0,0 +1,97 @@
+use std::io::{BufRead, IsTerminal, Write};
+
+use rusqlite::Connection;
+
+use super::sessions::parse_since;
+use crate::config;
+use crate::cross_project;
+use crate::db::forget::{self, Scope, Selector};
+use crate::db::{self, LookupError};
+
+/// Options of `claude-memory forget`.
+pub struct ForgetArgs {
+    pub ids: Vec<String>,
+    pub query: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub only: Scope,
+    pub with_notes: bool,
+    pub dry_run: bool,
+    pub yes: bool,
+}
+
+/// `claude-memory forget [IDS...] [--query Q] [--since D] [--until D]`
+pub fn run(args: ForgetArgs) -> anyhow::Result<()> {
+    let project_dir = config::detect_project_dir()?;
+    let conn = match super::open_project_db()? {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+
+    let selector = selector(&conn, &args)?;
+    let selection = forget::select(&conn, &selector)?;
+    if selection.is_empty() {
+        println!("Nothing matches; nothing to forget.");
+        return Ok(());
+    }
+
+    let verb = if args.dry_run { "Would forget" } else { "Forgetting" };
+    print!("{}:\n{}", verb, selection.describe());
+    if args.dry_run {
+        return Ok(());
+    }
+    if !args.yes && !confirm()? {
+        println!("Nothing was deleted.");
+        return Ok(());
+    }
+
+    println!("{}", forget::forget(&conn, &selection)?);
+    let elsewhere = cross_project::forget(&selection.sessions, &project_dir);
+    if elsewhere > 0 {
+        println!("Removed their notice from {} other project(s).", elsewhere);
+    }
+    println!("Backups taken earlier still contain them.");
+    Ok(())
+}
+
+/// Build the selector, sorting positional IDs into sessions and notes.
+fn selector(conn: &Connection, args: &ForgetArgs) -> anyhow::Result<Selector> {
+    let mut selector = Selector {
+        query: args.query.clone(),
+        date_from: args.since.as_deref().map(parse_since).transpose()?,
+        date_to: args.until.as_deref().map(parse_until).transpose()?,
+        scope: args.only,
+        with_notes: args.with_notes,
+        ..Default::default()
+    };
+    for id in &args.ids {
+        match db::sessions::resolve_session_id(conn, id) {
+            Ok(full) => selector.session_ids.push(full),
+            Err(e) if matches!(e.downcast_ref::<LookupError>(), Some(LookupError::NotFound { .. })) => {
+                selector.note_ids.push(db::notes::resolve_note_id(conn, id)?)
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(selector)
+}
+
+/// Same forms as `--since`; an age means "older than".
+fn parse_until(until: &str) -> anyhow::Result<String> {
+    parse_since(until).map_err(|_| {
+        anyhow::anyhow!("Invalid --until: {} (expected YYYY-MM-DD or an age like 3d, 2w, 12h)", until)
+    })
+}
+
+/// Ask on the terminal. Refuses when there is nobody to ask.
+fn confirm() -> anyhow::Result<bool> {
+    anyhow::ensure!(
+        std::io::stdin().is_terminal(),
+        "Refusing to delete without confirmation; pass --yes to run non-interactively"
+    );
+    print!("Delete permanently? [y/N] ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
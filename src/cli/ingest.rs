@@ -5,7 +5,16 @@ use serde::Deserialize;
 
 use crate::config;
 use crate::db;
+use crate::cross_project;
+use crate::identity;
+use crate::registry;
+use crate::scopes::Scopes;
+use crate::transcript::exclude::PathFilter;
 use crate::transcript::parser;
+use crate::transcript::redact::Redactor;
+
+/// Set (to anything but `0`) to keep the current session out of memory.
+const DISABLE_ENV: &str = "CLAUDE_MEMORY_DISABLE";
 
 /// Hook input from Claude Code's SessionEnd event.
 #[derive(Debug, Deserialize)]
@@ -46,18 +55,28 @@ pub fn run() -> anyhow::Result<()> {
         None => config::detect_project_dir()?,
     };
 
-    let db_path = config::db_path(&project_dir);
-    let conn = db::open(&db_path)?;
+    // Settings decide what must be kept out, so a broken file stops here
+    // rather than storing what it would have excluded
+    let settings = config::load_settings(&project_dir).map_err(not_stored)?;
+
+    let db_path = config::db_path(&project_dir).map_err(not_stored)?;
+    let mut conn = db::open(&db_path)?;
 
-    // Check idempotency
+    // Check idempotency (a private session stays private when resumed)
     if let Some(ref sid) = hook.session_id {
-        if db::sessions::session_exists(&conn, sid)? {
+        if db::sessions::session_exists(&conn, sid)? || db::sessions::session_skipped(&conn, sid)? {
             return Ok(()); // Already ingested
         }
     }
 
+    if std::env::var(DISABLE_ENV).is_ok_and(|v| !v.is_empty() && v != "0") {
+        let sid = hook.session_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        return skip(&conn, &sid, "env");
+    }
+
     // Parse transcript
-    let mut meta = parser::parse_transcript(&transcript_path)?;
+    let mut meta = parser::parse_transcript(&transcript_path, &settings.parser)?;
+    meta.transcript_path = Some(transcript_path.to_string_lossy().to_string());
 
     // Use hook session_id if transcript didn't have one
     if meta.session_id.is_empty() {
@@ -78,15 +97,65 @@ pub fn run() -> anyhow::Result<()> {
         return Ok(());
     }
 
+    let marker = settings.privacy.marker.to_lowercase();
+    if !marker.is_empty() && meta.user_prompts[0].to_lowercase().contains(&marker) {
+        return skip(&conn, &meta.session_id, "marker");
+    }
+
+    PathFilter::new(&settings.privacy.exclude_paths, &project_dir)
+        .map_err(not_stored)?
+        .filter_metadata(&mut meta);
+
+    // Scrub secrets before anything reaches the database or its search index
+    let redactor = Redactor::new(&settings.redaction).map_err(not_stored)?;
+    let redacted = redactor.redact_metadata(&mut meta);
+
+    // Scopes only sort sessions, so a broken [scopes] setting leaves them unscoped
+    match Scopes::load(&project_dir, &settings.scopes) {
+        Ok(scopes) => {
+            meta.scope = scopes.session_scope(&project_dir, &meta.project_dir, &meta.files_modified, &meta.files_read)
+        }
+        Err(e) => eprintln!("claude-memory: {}; storing the session without a scope", e),
+    }
+
     // Store in database
     db::sessions::insert_session(&conn, &meta)?;
+    let identity = identity::project_identity(&project_dir);
+    db::project::record_project(&conn, &identity, &project_dir.to_string_lossy())?;
+    if let Err(e) = registry::touch(&project_dir, &db_path, &identity) {
+        eprintln!("claude-memory: cannot update the project registry: {}", e);
+    }
+
+    let elsewhere = cross_project::record(&meta, &project_dir);
 
     eprintln!(
-        "claude-memory: ingested session {} ({} prompts, {} files modified)",
+        "claude-memory: ingested session {} ({} prompts, {} files modified{}{})",
         &meta.session_id[..8.min(meta.session_id.len())],
         meta.user_prompts.len(),
-        meta.files_modified.len()
+        meta.files_modified.len(),
+        if redacted > 0 { format!(", {} secret(s) redacted", redacted) } else { String::new() },
+        if elsewhere > 0 { format!(", noted in {} other project(s)", elsewhere) } else { String::new() }
     );
 
+    // Retention is best-effort here: the session is already stored
+    if let Err(e) = super::prune::after_ingest(&mut conn, &settings.retention) {
+        eprintln!("claude-memory: prune after ingest failed: {}", e);
+    }
+
     Ok(())
 }
+
+/// Record a tombstone instead of the session, so `status` shows that ingestion ran.
+fn skip(conn: &rusqlite::Connection, session_id: &str, reason: &str) -> anyhow::Result<()> {
+    db::sessions::record_skipped(conn, session_id, reason)?;
+    eprintln!(
+        "claude-memory: session {} not stored ({})",
+        &session_id[..8.min(session_id.len())],
+        reason
+    );
+    Ok(())
+}
+
+fn not_stored(e: anyhow::Error) -> anyhow::Error {
+    anyhow::anyhow!("{}; session not stored", e)
+}
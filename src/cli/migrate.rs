@@ -0,0 +1,42 @@
+use crate::config;
+use crate::db;
+use crate::db::schema::{self, CURRENT_VERSION, MIGRATIONS};
+
+/// `claude-memory migrate [--status] [--to <version>]`
+pub fn run(status: bool, to: Option<i64>) -> anyhow::Result<()> {
+    let project_dir = config::detect_project_dir()?;
+    let db_path = config::db_path(&project_dir)?;
+    anyhow::ensure!(db_path.exists(), "No memory database found at {}", db_path.display());
+
+    let conn = db::open_unmigrated(&db_path)?;
+    let version = schema::get_schema_version(&conn)?;
+
+    if status {
+        println!("Database: {}", db_path.display());
+        println!("Schema:   v{} (latest: v{})", version, CURRENT_VERSION);
+        if version > CURRENT_VERSION {
+            println!("\nThis database was written by a newer claude-memory.");
+            return Ok(());
+        }
+        println!();
+        for m in &MIGRATIONS {
+            println!(
+                "  {:<8} v{}  {}{}",
+                if m.version <= version { "applied" } else { "pending" },
+                m.version,
+                m.description,
+                if m.destructive { " (snapshot taken first)" } else { "" }
+            );
+        }
+        return Ok(());
+    }
+
+    let target = to.unwrap_or(CURRENT_VERSION);
+    let reached = schema::migrate(&conn, target)?;
+    if reached == version {
+        println!("Schema is already at v{}.", version);
+    } else {
+        println!("Migrated {} from v{} to v{}.", db_path.display(), version, reached);
+    }
+    Ok(())
+}
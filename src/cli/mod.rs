@@ -1,4 +1,36 @@
+pub mod backup;
+pub mod doctor;
+pub mod encrypt;
+pub mod export;
+pub mod forget;
+pub mod import;
 pub mod ingest;
 pub mod install;
+pub mod migrate;
+pub mod notes;
+pub mod projects;
+pub mod prune;
+pub mod redact;
+pub mod relink;
+pub mod relocate;
+pub mod scopes;
 pub mod search;
+pub mod sessions;
+pub mod settings;
 pub mod status;
+
+use crate::config;
+use crate::db;
+
+/// Open the current project's database, or explain how to create it.
+pub(crate) fn open_project_db() -> anyhow::Result<Option<rusqlite::Connection>> {
+    let project_dir = config::detect_project_dir()?;
+    let db_path = config::db_path(&project_dir)?;
+
+    if !db_path.exists() {
+        println!("No memory database found. Run `claude-memory install` first.");
+        return Ok(None);
+    }
+
+    Ok(Some(db::open(&db_path)?))
+}
@@ -0,0 +1,249 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use crate::config;
+use crate::db;
+use crate::db::notes::NoteRow;
+
+use crate::text::one_line;
+
+/// Prefix of the tags line in the file opened by `notes edit`.
+const TAGS_HEADER: &str = "tags:";
+
+/// The project's database, or with `global` the user's global memory.
+fn open_notes_db(global: bool) -> anyhow::Result<Option<rusqlite::Connection>> {
+    if !global {
+        return super::open_project_db();
+    }
+    let conn = db::open_global(&config::global_db_path()?)?;
+    if conn.is_none() {
+        println!("No global notes yet. Add one with `claude-memory notes add --global`.");
+    }
+    Ok(conn)
+}
+
+/// `claude-memory notes add "<text>" [--tag x]... [--global]`
+pub fn add(text: &str, tags: &[String], global: bool) -> anyhow::Result<()> {
+    let conn = if global {
+        Some(db::open(&config::global_db_path()?)?)
+    } else {
+        super::open_project_db()?
+    };
+    let Some(conn) = conn else { return Ok(()) };
+
+    let text = text.trim();
+    anyhow::ensure!(!text.is_empty(), "Note text is empty");
+
+    let id = db::notes::insert_note(&conn, text, tags, None)?;
+    let what = if global { "Global note" } else { "Note" };
+    println!("{} saved{} (id: {})", what, tag_display(tags), &id[..8]);
+    Ok(())
+}
+
+/// `claude-memory notes list [--tag x] [--query q] [--global]`: the project's
+/// notes together with the global ones, or only global ones.
+pub fn list(tag: Option<&str>, query: Option<&str>, limit: usize, global: bool) -> anyhow::Result<()> {
+    let project = if global {
+        None
+    } else {
+        match super::open_project_db()? {
+            Some(c) => Some(c),
+            None => return Ok(()),
+        }
+    };
+    let results = find_notes(project.as_ref(), &config::global_db_path()?, tag, query, limit)?;
+    if results.is_empty() {
+        println!("No notes found.");
+        return Ok(());
+    }
+
+    for (is_global, note) in &results {
+        let tags: Vec<String> = serde_json::from_str(&note.tags).unwrap_or_default();
+        println!(
+            "{}  {}  {}{}{}",
+            note.id.get(..8).unwrap_or(&note.id),
+            note.created_at.get(..10).unwrap_or(&note.created_at),
+            one_line(&note.content, 70),
+            tag_display(&tags),
+            if *is_global && !global { " (global)" } else { "" }
+        );
+    }
+
+    println!("\n{} note(s).", results.len());
+    Ok(())
+}
+
+/// Notes of the project, if given, together with the global ones.
+fn find_notes(
+    project: Option<&rusqlite::Connection>,
+    global_db: &Path,
+    tag: Option<&str>,
+    query: Option<&str>,
+    limit: usize,
+) -> anyhow::Result<Vec<(bool, NoteRow)>> {
+    let project = match project {
+        Some(conn) => db::notes::search_notes(conn, query, tag, limit)?,
+        None => Vec::new(),
+    };
+    let global = match db::open_global(global_db)? {
+        Some(conn) => db::notes::search_notes(&conn, query, tag, limit)?,
+        None => Vec::new(),
+    };
+    Ok(db::notes::merge_scopes(project, global, query.is_some(), limit))
+}
+
+/// `claude-memory notes edit <id> [--global]`: open the note in `$VISUAL` / `$EDITOR`.
+pub fn edit(id: &str, global: bool) -> anyhow::Result<()> {
+    let conn = match open_notes_db(global)? {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+
+    let note = find_note(&conn, id)?;
+    let tags: Vec<String> = serde_json::from_str(&note.tags).unwrap_or_default();
+    let original = edit_buffer(&note.content, &tags);
+
+    // Private (0600) and randomly named; removed when dropped, whatever happens
+    let mut file = tempfile::Builder::new()
+        .prefix("claude-memory-note-")
+        .suffix(".md")
+        .tempfile()?;
+    file.write_all(original.as_bytes())?;
+    file.flush()?;
+    let status = run_editor(file.path());
+    let edited = std::fs::read_to_string(file.path());
+    drop(file);
+
+    anyhow::ensure!(status?.success(), "Editor exited with an error; note not changed");
+    let edited = edited?;
+
+    if edited == original {
+        println!("No changes.");
+        return Ok(());
+    }
+
+    let (content, tags) = parse_edit_buffer(&edited);
+    anyhow::ensure!(!content.is_empty(), "Note is empty; not saved (use `notes rm` to delete it)");
+
+    db::notes::update_note(&conn, &note.id, &content, &tags)?;
+    println!("Note updated{} (id: {})", tag_display(&tags), note.id.get(..8).unwrap_or(&note.id));
+    Ok(())
+}
+
+/// `claude-memory notes rm <id> [--global]`
+pub fn rm(id: &str, global: bool) -> anyhow::Result<()> {
+    let conn = match open_notes_db(global)? {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+
+    let note = remove(&conn, id)?;
+    println!("Deleted note {}: {}", note.id.get(..8).unwrap_or(&note.id), one_line(&note.content, 60));
+    Ok(())
+}
+
+/// Delete the note with this ID or prefix, returning it.
+fn remove(conn: &rusqlite::Connection, id: &str) -> anyhow::Result<NoteRow> {
+    let note = find_note(conn, id)?;
+    db::notes::delete_note(conn, &note.id)?;
+    Ok(note)
+}
+
+fn find_note(conn: &rusqlite::Connection, id: &str) -> anyhow::Result<NoteRow> {
+    let full_id = db::notes::resolve_note_id(conn, id)?;
+    db::notes::get_note(conn, &full_id)?
+        .ok_or_else(|| anyhow::anyhow!("Note not found: {}", full_id))
+}
+
+fn tag_display(tags: &[String]) -> String {
+    if tags.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", tags.join(", "))
+    }
+}
+
+/// The text opened in the editor: a tags line, a blank line, then the content.
+fn edit_buffer(content: &str, tags: &[String]) -> String {
+    format!("{} {}\n\n{}\n", TAGS_HEADER, tags.join(", "), content)
+}
+
+/// Split an edited buffer back into content and tags. The tags line is
+/// optional, so deleting it keeps the whole file as content with no tags.
+fn parse_edit_buffer(buffer: &str) -> (String, Vec<String>) {
+    let (tags, body) = match buffer.strip_prefix(TAGS_HEADER) {
+        Some(rest) => {
+            let (line, body) = rest.split_once('\n').unwrap_or((rest, ""));
+            let tags = line
+                .split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect();
+            (tags, body)
+        }
+        None => (Vec::new(), buffer),
+    };
+
+    (body.trim().to_string(), tags)
+}
+
+fn run_editor(path: &Path) -> anyhow::Result<std::process::ExitStatus> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("$EDITOR is empty"))?;
+
+    Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to start editor `{}`: {}", editor, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_buffer_round_trips() {
+        let tags = vec!["decision".to_string(), "db".to_string()];
+        let buffer = edit_buffer("Use SQLite.\n\nNo server needed.", &tags);
+        assert_eq!(
+            parse_edit_buffer(&buffer),
+            ("Use SQLite.\n\nNo server needed.".to_string(), tags)
+        );
+    }
+
+    #[test]
+    fn global_notes_are_listed_and_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = db::open(&dir.path().join("memory.db")).unwrap();
+        let global_db = dir.path().join("memory-global.db");
+        assert!(find_notes(Some(&project), &global_db, None, None, 10).unwrap().is_empty());
+
+        db::notes::insert_note(&project, "run migrations before deploy", &[], None).unwrap();
+        let global = db::open(&global_db).unwrap();
+        let id = db::notes::insert_note(&global, "prefer small commits", &["habit".to_string()], None).unwrap();
+
+        let all = find_notes(Some(&project), &global_db, None, None, 10).unwrap();
+        assert_eq!(all.iter().filter(|(is_global, _)| *is_global).count(), 1);
+        assert_eq!(all.len(), 2);
+        let only_global = find_notes(None, &global_db, Some("habit"), None, 10).unwrap();
+        assert_eq!(only_global.len(), 1);
+        assert_eq!(only_global[0].1.content, "prefer small commits");
+
+        assert_eq!(remove(&global, &id[..8]).unwrap().id, id);
+        assert!(find_notes(None, &global_db, None, None, 10).unwrap().is_empty());
+        assert!(remove(&global, &id[..8]).is_err());
+    }
+
+    #[test]
+    fn edit_buffer_without_tags_line() {
+        assert_eq!(parse_edit_buffer("just text\n"), ("just text".to_string(), Vec::new()));
+        assert_eq!(parse_edit_buffer("tags: , \n\nbody"), ("body".to_string(), Vec::new()));
+    }
+}
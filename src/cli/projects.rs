@@ -0,0 +1,103 @@
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::registry::{Registry, RegistryEntry};
+
+/// `claude-memory projects list`: every registered project.
+pub fn list() -> anyhow::Result<()> {
+    let projects = config::discover_project_dbs();
+    let registry = Registry::load()?.unwrap_or_default();
+    let dead: Vec<(&PathBuf, &RegistryEntry)> =
+        registry.projects.iter().filter(|(_, e)| !e.db_path.is_file()).collect();
+
+    if projects.is_empty() && dead.is_empty() {
+        println!("No projects with memory databases found. Try `claude-memory projects rescan`.");
+        return Ok(());
+    }
+    for project in &projects {
+        let seen = registry
+            .projects
+            .get(&project.project_dir)
+            .map_or("-", |e| &e.last_seen[..10.min(e.last_seen.len())]);
+        println!("{}  {}", seen, project.project_dir.display());
+    }
+    for (dir, _) in &dead {
+        println!("{:10}  {}  (database missing)", "-", dir.display());
+    }
+    println!();
+    println!("{} project(s).", projects.len());
+    if !dead.is_empty() {
+        println!("{} without a database; `claude-memory projects rescan` drops them.", dead.len());
+    }
+    Ok(())
+}
+
+/// `claude-memory projects add <path>`
+pub fn add(path: &Path) -> anyhow::Result<()> {
+    anyhow::ensure!(path.is_dir(), "Not a directory: {}", path.display());
+    let project_dir = config::find_project_root(&path.canonicalize()?);
+    let db_path = config::db_path(&project_dir)?;
+    anyhow::ensure!(db_path.is_file(), "No memory database found at {}", db_path.display());
+
+    let mut registry = Registry::load()?.unwrap_or_default();
+    let verb = if registry.insert(&project_dir, &db_path) { "Added" } else { "Refreshed" };
+    registry.save()?;
+    println!("{} {}", verb, project_dir.display());
+    Ok(())
+}
+
+/// `claude-memory projects remove <path|name>`: forget about a project. Its
+/// database stays where it is.
+pub fn remove(spec: &str) -> anyhow::Result<()> {
+    let mut registry = Registry::load()?.unwrap_or_default();
+    let path = Path::new(spec);
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let matches: Vec<PathBuf> = registry
+        .projects
+        .keys()
+        .filter(|dir| **dir == path || dir.file_name().is_some_and(|n| n == spec))
+        .cloned()
+        .collect();
+
+    match matches.as_slice() {
+        [] => anyhow::bail!("No registered project matches {}", spec),
+        [dir] => {
+            registry.projects.remove(dir);
+            registry.save()?;
+            println!("Removed {} (its database was kept)", dir.display());
+            Ok(())
+        }
+        _ => {
+            let list: Vec<String> = matches.iter().map(|d| format!("  {}", d.display())).collect();
+            anyhow::bail!("{} matches several projects; give the path:\n{}", spec, list.join("\n"))
+        }
+    }
+}
+
+/// `claude-memory projects rescan`: search the scan roots for databases,
+/// register new ones and drop projects whose database is gone.
+pub fn rescan() -> anyhow::Result<()> {
+    let mut registry = Registry::load()?.unwrap_or_default();
+    let found = config::scan_for_projects();
+    let mut added = 0;
+    for project in &found {
+        if !registry.projects.contains_key(&project.project_dir) {
+            registry.insert(&project.project_dir, &project.db_path);
+            println!("Added    {}", project.project_dir.display());
+            added += 1;
+        }
+    }
+    let pruned = registry.prune();
+    for dir in &pruned {
+        println!("Dropped  {} (database missing)", dir.display());
+    }
+    registry.save()?;
+    println!(
+        "Found {} project(s): {} new, {} dropped, {} registered.",
+        found.len(),
+        added,
+        pruned.len(),
+        registry.projects.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,293 @@
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+
+use crate::config::{self, RetentionSettings};
+use crate::db;
+
+/// Deleted sessions listed individually; the rest are only counted.
+const LIST_LIMIT: usize = 20;
+
+/// A session `prune` would delete, and the limit it exceeds.
+#[derive(Debug)]
+pub struct Deletion {
+    pub id: String,
+    pub started_at: String,
+    pub reason: String,
+}
+
+/// What applying the retention settings would change.
+#[derive(Debug, Default)]
+pub struct Plan {
+    pub delete: Vec<Deletion>,
+    /// Sessions to reduce to summary-only.
+    pub summarize: Vec<String>,
+    /// Sessions over a limit but kept for their notes or commits.
+    pub protected: usize,
+}
+
+impl Plan {
+    pub fn is_empty(&self) -> bool {
+        self.delete.is_empty() && self.summarize.is_empty()
+    }
+}
+
+/// `claude-memory prune [--dry-run]`
+pub fn run(dry_run: bool) -> anyhow::Result<()> {
+    let project_dir = config::detect_project_dir()?;
+    let retention = config::load_settings(&project_dir)?.retention;
+    if !retention.is_enabled() {
+        println!(
+            "No retention limits configured. Set them under [retention] in {}.",
+            config::settings_path(&project_dir).display()
+        );
+        return Ok(());
+    }
+
+    let mut conn = match super::open_project_db()? {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+
+    let plan = plan(&conn, &retention, Utc::now())?;
+    if plan.is_empty() {
+        println!("Nothing to prune.");
+    } else {
+        print_plan(&plan, dry_run);
+    }
+    if plan.protected > 0 {
+        println!("Kept {} session(s) over a limit because they have notes or commits.", plan.protected);
+    }
+    if dry_run || plan.is_empty() {
+        return Ok(());
+    }
+
+    let db_path = config::db_path(&project_dir)?;
+    let before = std::fs::metadata(&db_path)?.len();
+    apply(&mut conn, &plan)?;
+    if retention.vacuum {
+        db::optimize_and_vacuum(&conn)?;
+        let after = std::fs::metadata(&db_path)?.len();
+        println!("Optimized search indexes and vacuumed: {} -> {} bytes.", before, after);
+    }
+    Ok(())
+}
+
+/// Prune after an ingest, if configured. Never vacuums, to keep the hook fast.
+pub fn after_ingest(conn: &mut Connection, retention: &RetentionSettings) -> anyhow::Result<()> {
+    if !retention.prune_after_ingest || !retention.is_enabled() {
+        return Ok(());
+    }
+    let plan = plan(conn, retention, Utc::now())?;
+    if !plan.is_empty() {
+        apply(conn, &plan)?;
+        eprintln!(
+            "claude-memory: pruned {} session(s), summarized {}",
+            plan.delete.len(),
+            plan.summarize.len()
+        );
+    }
+    Ok(())
+}
+
+/// Work out which sessions the retention settings delete or summarize,
+/// without changing anything.
+pub fn plan(conn: &Connection, retention: &RetentionSettings, now: DateTime<Utc>) -> anyhow::Result<Plan> {
+    let outlines = db::sessions::session_outlines(conn)?;
+    let cutoff = |days: u64| (now - Duration::days(days as i64)).format("%Y-%m-%dT%H:%M:%S").to_string();
+    let is_protected = |o: &db::sessions::SessionOutline| {
+        (retention.keep_with_notes && o.has_notes) || (retention.keep_with_commits && o.has_commits)
+    };
+
+    // Reasons by position in the newest-first list
+    let mut reasons: Vec<Option<String>> = vec![None; outlines.len()];
+    let age_cutoff = (retention.max_age_days > 0).then(|| cutoff(retention.max_age_days));
+    for (i, outline) in outlines.iter().enumerate() {
+        if age_cutoff.as_ref().is_some_and(|c| outline.started_at < *c) {
+            reasons[i] = Some(format!("older than {} days", retention.max_age_days));
+        } else if retention.max_sessions > 0 && i >= retention.max_sessions {
+            reasons[i] = Some(format!("beyond the newest {}", retention.max_sessions));
+        }
+    }
+
+    let mut protected = 0;
+    for (i, outline) in outlines.iter().enumerate() {
+        if reasons[i].is_some() && is_protected(outline) {
+            reasons[i] = None;
+            protected += 1;
+        }
+    }
+
+    // Size is estimated per session, so delete the oldest until the average
+    // session size times the deletions covers the excess
+    if retention.max_db_mb > 0 && !outlines.is_empty() {
+        let used = db::used_bytes(conn)?;
+        let limit = retention.max_db_mb * 1024 * 1024;
+        if used > limit {
+            let per_session = (used / outlines.len() as u64).max(1);
+            let mut freed = reasons.iter().filter(|r| r.is_some()).count() as u64 * per_session;
+            for (i, outline) in outlines.iter().enumerate().rev() {
+                if used.saturating_sub(freed) <= limit {
+                    break;
+                }
+                if reasons[i].is_some() {
+                    continue;
+                }
+                if is_protected(outline) {
+                    protected += 1;
+                    continue;
+                }
+                reasons[i] = Some(format!("over {} MB", retention.max_db_mb));
+                freed += per_session;
+            }
+        }
+    }
+
+    let summarize_cutoff = (retention.summarize_after_days > 0).then(|| cutoff(retention.summarize_after_days));
+    let mut plan = Plan { protected, ..Plan::default() };
+    for (outline, reason) in outlines.into_iter().zip(reasons).rev() {
+        match reason {
+            Some(reason) => plan.delete.push(Deletion {
+                id: outline.id,
+                started_at: outline.started_at,
+                reason,
+            }),
+            None if outline.has_details
+                && summarize_cutoff.as_ref().is_some_and(|c| outline.started_at < *c) =>
+            {
+                plan.summarize.push(outline.id)
+            }
+            None => {}
+        }
+    }
+    Ok(plan)
+}
+
+/// Carry out a plan in one transaction. Notes of deleted sessions are kept,
+/// unlinked from the session.
+pub fn apply(conn: &mut Connection, plan: &Plan) -> anyhow::Result<()> {
+    let tx = conn.transaction()?;
+    for deletion in &plan.delete {
+        db::sessions::delete_session(&tx, &deletion.id)?;
+    }
+    for id in &plan.summarize {
+        db::sessions::summarize_session(&tx, id)?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+fn print_plan(plan: &Plan, dry_run: bool) {
+    let (delete, summarize) = if dry_run {
+        ("Would delete", "Would summarize")
+    } else {
+        ("Deleted", "Summarized")
+    };
+
+    if !plan.delete.is_empty() {
+        println!("{} {} session(s):", delete, plan.delete.len());
+        for d in plan.delete.iter().take(LIST_LIMIT) {
+            println!(
+                "  {}  {}  {}",
+                &d.id[..8.min(d.id.len())],
+                &d.started_at[..10.min(d.started_at.len())],
+                d.reason
+            );
+        }
+        if plan.delete.len() > LIST_LIMIT {
+            println!("  ... and {} more", plan.delete.len() - LIST_LIMIT);
+        }
+    }
+    if !plan.summarize.is_empty() {
+        println!(
+            "{} {} session(s) to summary-only (files read and commands run dropped).",
+            summarize,
+            plan.summarize.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded_db(dir: &std::path::Path) -> Connection {
+        let conn = db::open(&dir.join("memory.db")).unwrap();
+        for (id, started, commits) in [
+            ("s1", "2026-01-01T10:00:00Z", "[]"),
+            ("s2", "2026-02-01T10:00:00Z", "[\"abc123 fix\"]"),
+            ("s3", "2026-03-01T10:00:00Z", "[]"),
+            ("s4", "2026-04-01T10:00:00Z", "[]"),
+        ] {
+            conn.execute(
+                "INSERT INTO sessions (id, project_dir, started_at, user_prompts, files_read, commands_run, git_commits)
+                 VALUES (?1, '/p', ?2, '[\"prompt\"]', '[\"src/main.rs\"]', '[\"cargo test\"]', ?3)",
+                [id, started, commits],
+            ).unwrap();
+        }
+        db::notes::insert_note(&conn, "about s3", &[], Some("s3")).unwrap();
+        conn
+    }
+
+    fn now() -> DateTime<Utc> {
+        "2026-04-15T00:00:00Z".parse().unwrap()
+    }
+
+    fn deleted(plan: &Plan) -> Vec<&str> {
+        plan.delete.iter().map(|d| d.id.as_str()).collect()
+    }
+
+    #[test]
+    fn age_and_count_limits_spare_protected_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = seeded_db(dir.path());
+
+        let by_age = RetentionSettings { max_age_days: 60, ..Default::default() };
+        assert_eq!(deleted(&plan(&conn, &by_age, now()).unwrap()), ["s1", "s2"]);
+
+        let by_count = RetentionSettings { max_sessions: 1, keep_with_commits: true, ..Default::default() };
+        let counted = plan(&conn, &by_count, now()).unwrap();
+        assert_eq!(deleted(&counted), ["s1"]);
+        assert_eq!(counted.protected, 2);
+    }
+
+    #[test]
+    fn apply_deletes_unlinks_notes_and_summarizes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = seeded_db(dir.path());
+
+        let retention = RetentionSettings {
+            max_sessions: 1,
+            keep_with_notes: false,
+            summarize_after_days: 10,
+            ..Default::default()
+        };
+        let plan = plan(&conn, &retention, now()).unwrap();
+        assert_eq!(deleted(&plan), ["s1", "s2", "s3"]);
+        assert_eq!(plan.summarize, ["s4"]);
+
+        apply(&mut conn, &plan).unwrap();
+        assert!(db::sessions::get_session(&conn, "s3").unwrap().is_none());
+        let s4 = db::sessions::get_session(&conn, "s4").unwrap().unwrap();
+        assert_eq!((s4.files_read.as_str(), s4.commands_run.as_str()), ("[]", "[]"));
+        assert_eq!(s4.user_prompts, "[\"prompt\"]");
+
+        let notes = db::notes::search_notes(&conn, Some("s3"), None, 5).unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].session_id, None);
+        assert_eq!(db::sessions::search_sessions(&conn, "prompt", 10).unwrap().0.len(), 1);
+        db::optimize_and_vacuum(&conn).unwrap();
+    }
+
+    #[test]
+    fn size_limit_deletes_oldest_unprotected_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = seeded_db(dir.path());
+        // Pad each session to ~400 KB so four of them exceed 1 MB
+        conn.execute("UPDATE sessions SET summary = printf('%.*c', 400000, 'x')", []).unwrap();
+        assert!(db::used_bytes(&conn).unwrap() > 1024 * 1024);
+
+        let retention = RetentionSettings { max_db_mb: 1, ..Default::default() };
+        let plan = plan(&conn, &retention, now()).unwrap();
+        assert_eq!(deleted(&plan), ["s1", "s2"]);
+    }
+}
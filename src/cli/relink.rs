@@ -0,0 +1,54 @@
+use std::path::Path;
+
+use crate::config::{self, StorageMode};
+use crate::db;
+use crate::identity;
+use crate::registry::Registry;
+use crate::storage;
+
+/// `claude-memory relink <old-path>`: after a project moved from `old-path`
+/// to the current directory, rewrite the paths stored in its memory.
+pub fn run(old: &Path, force: bool) -> anyhow::Result<()> {
+    let project_dir = config::detect_project_dir()?;
+    let new = project_dir.to_string_lossy().trim_end_matches('/').to_string();
+    let old = old.to_string_lossy().trim_end_matches('/').to_string();
+    anyhow::ensure!(old != new, "{} is the current project already", old);
+
+    let mode = config::load_settings(&project_dir)?.storage.mode;
+    if mode == StorageMode::Xdg && storage::relink(Path::new(&old), &project_dir)? {
+        println!("Linked the database of {} to {}", old, new);
+    }
+
+    let db_path = config::db_path(&project_dir)?;
+    anyhow::ensure!(db_path.exists(), "No memory database found at {}", db_path.display());
+    let conn = db::open(&db_path)?;
+
+    let identity = identity::project_identity(&project_dir);
+    let info = db::project::project_info(&conn)?;
+    if let Some(recorded) = info.identity.as_deref().filter(|r| *r != identity) {
+        anyhow::ensure!(
+            force,
+            "This memory belongs to {} but the project here is {}; pass --force to relink anyway",
+            recorded,
+            identity
+        );
+    }
+
+    let changed = db::project::relink(&conn, &old, &new)?;
+    if changed == 0 && info.path.as_deref() != Some(old.as_str()) {
+        println!("Nothing is stored under {}; nothing to rewrite.", old);
+        return Ok(());
+    }
+    db::project::record_project(&conn, &identity, &new)?;
+    let registered = Registry::load().and_then(|registry| {
+        let mut registry = registry.unwrap_or_default();
+        registry.projects.remove(Path::new(&old));
+        registry.insert(&project_dir, &db_path);
+        registry.save()
+    });
+    if let Err(e) = registered {
+        eprintln!("Warning: cannot update the project registry: {}", e);
+    }
+    println!("Rewrote paths from {} to {} in {} session(s).", old, new, changed);
+    Ok(())
+}
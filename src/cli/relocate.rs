@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use crate::config::{self, StorageMode};
+use crate::db;
+use crate::identity;
+use crate::registry;
+
+/// `claude-memory relocate [--to xdg|project]`: move the project's database
+/// and its backups to the other storage location, and switch `storage.mode`
+/// in the project's memory.toml to match.
+///
+/// The old database stays write-locked until the settings point at the new
+/// one and the old file is deleted, so a running MCP server either writes
+/// before the copy or finds its file gone: reads are repeated on the new
+/// database, writes fail and ask to be retried.
+pub fn run(to: StorageMode) -> anyhow::Result<()> {
+    let project_dir = config::detect_project_dir()?;
+    let mode = config::load_settings(&project_dir)?.storage.mode;
+    let from = config::db_path_for(&project_dir, mode)?;
+    let dest = config::db_path_for(&project_dir, to)?;
+
+    anyhow::ensure!(from != dest, "The database is already stored in {} mode at {}", to, dest.display());
+    anyhow::ensure!(from.exists(), "No memory database found at {}", from.display());
+    anyhow::ensure!(!dest.exists(), "Refusing to overwrite {}", dest.display());
+
+    let lock = db::open(&from)?;
+    lock.execute_batch("BEGIN IMMEDIATE")?;
+    copy_database(&from, &dest)?;
+    let moved = move_backups(
+        &config::backup_dir_for(&project_dir, mode),
+        &config::backup_dir_for(&project_dir, to),
+    )?;
+
+    let settings = config::settings_path(&project_dir);
+    super::settings::set_in_file(&settings, "storage.mode", &to.to_string())?;
+    remove_database(&from);
+    drop(lock);
+
+    if let Err(e) = registry::touch(&project_dir, &dest, &identity::project_identity(&project_dir)) {
+        eprintln!("Warning: cannot update the project registry: {}", e);
+    }
+
+    if mode == StorageMode::Xdg {
+        // Only succeeds once the project's data directory is empty
+        let _ = from.parent().map(std::fs::remove_dir);
+    }
+
+    println!("Moved {} to {}", from.display(), dest.display());
+    if moved > 0 {
+        println!("Moved {} backup(s) to {}", moved, config::backup_dir_for(&project_dir, to).display());
+    }
+    println!("Set storage.mode = \"{}\" in {}", to, settings.display());
+    Ok(())
+}
+
+/// Copy with the backup API, so a live WAL is included, then check the copy
+/// before anything is deleted.
+fn copy_database(from: &Path, dest: &Path) -> anyhow::Result<()> {
+    let conn = db::open_readonly(from)?;
+    if let Err(e) = db::backup_to(&conn, dest) {
+        remove_database(dest);
+        return Err(e);
+    }
+
+    let check: String = db::open_readonly(dest)?.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+    if check != "ok" {
+        remove_database(dest);
+        anyhow::bail!("The copy at {} failed its integrity check: {}", dest.display(), check);
+    }
+    Ok(())
+}
+
+/// Move every backup from one directory to the other. Returns how many moved.
+fn move_backups(from: &Path, to: &Path) -> anyhow::Result<usize> {
+    let entries = match std::fs::read_dir(from) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(0),
+    };
+    std::fs::create_dir_all(to)?;
+
+    let mut moved = 0;
+    for entry in entries.flatten() {
+        let target = to.join(entry.file_name());
+        if target.exists() {
+            eprintln!("Warning: {} already exists; leaving {} in place", target.display(), entry.path().display());
+            continue;
+        }
+        // The data directory is often on another filesystem than the project
+        if std::fs::rename(entry.path(), &target).is_err() {
+            std::fs::copy(entry.path(), &target)?;
+            std::fs::remove_file(entry.path())?;
+        }
+        moved += 1;
+    }
+    let _ = std::fs::remove_dir(from);
+    Ok(moved)
+}
+
+fn remove_database(path: &Path) {
+    let _ = std::fs::remove_file(path);
+    for suffix in ["-wal", "-shm"] {
+        let mut side = path.as_os_str().to_owned();
+        side.push(suffix);
+        let _ = std::fs::remove_file(side);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::config;
+use crate::db;
+use crate::scopes::Scopes;
+
+/// `claude-memory scopes [--reassign]`: show the monorepo scopes and how many
+/// sessions each has; `--reassign` recomputes every session's scope, e.g.
+/// after changing `[scopes]`.
+pub fn run(reassign: bool) -> anyhow::Result<()> {
+    let project_dir = config::detect_project_dir()?;
+    let settings = config::load_settings(&project_dir)?;
+    let scopes = Scopes::load(&project_dir, &settings.scopes)?;
+    let conn = match super::open_project_db()? {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+
+    if scopes.is_empty() {
+        println!("No scopes: set [scopes] roots, or detect = true to use Cargo/npm workspaces.");
+    } else {
+        println!("Scope roots: {}", scopes.patterns().join(", "));
+    }
+
+    if reassign {
+        let mut changed = 0;
+        let tx = conn.unchecked_transaction()?;
+        for session in db::sessions::sessions_since(&tx, None)? {
+            let list = |json: &str| serde_json::from_str::<Vec<String>>(json).unwrap_or_default();
+            let scope = scopes.session_scope(
+                &project_dir,
+                &session.project_dir,
+                &list(&session.files_modified),
+                &list(&session.files_read),
+            );
+            changed += db::sessions::set_session_scope(&tx, &session.id, scope.as_deref())? as usize;
+        }
+        tx.commit()?;
+        println!("Reassigned {} session(s).", changed);
+    }
+
+    println!();
+    for (scope, count) in db::sessions::scope_counts(&conn)? {
+        println!("{:>6}  {}", count, scope.as_deref().unwrap_or("(repository-wide)"));
+    }
+    Ok(())
+}
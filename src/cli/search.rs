@@ -1,9 +1,14 @@
 use crate::config;
 use crate::db;
+use crate::text::truncate;
 
-pub fn run(query: &str, limit: usize) -> anyhow::Result<()> {
+pub fn run(query: &str, limit: Option<usize>) -> anyhow::Result<()> {
     let project_dir = config::detect_project_dir()?;
-    let db_path = config::db_path(&project_dir);
+    let limit = match limit {
+        Some(l) => l,
+        None => config::load_settings(&project_dir)?.search.recall_limit,
+    };
+    let db_path = config::db_path(&project_dir)?;
 
     if !db_path.exists() {
         println!("No memory database found. Run `claude-memory install` first.");
@@ -12,9 +17,11 @@ pub fn run(query: &str, limit: usize) -> anyhow::Result<()> {
 
     let conn = db::open(&db_path)?;
     let (results, is_fallback) = db::sessions::search_sessions(&conn, query, limit)?;
+    let elsewhere = db::crossrefs::search_cross_references(&conn, query, limit)?;
 
     if results.is_empty() {
         println!("No sessions found matching: {}", query);
+        print_cross_references(&elsewhere);
         return Ok(());
     }
 
@@ -44,12 +51,7 @@ pub fn run(query: &str, limit: usize) -> anyhow::Result<()> {
         // Show first user prompt (truncated)
         if let Ok(prompts) = serde_json::from_str::<Vec<String>>(&session.user_prompts) {
             if let Some(first) = prompts.first() {
-                let display = if first.len() > 120 {
-                    format!("{}...", &first[..120])
-                } else {
-                    first.clone()
-                };
-                println!("  First prompt: {}", display);
+                println!("  First prompt: {}", truncate(first, 120));
             }
         }
 
@@ -65,11 +67,31 @@ pub fn run(query: &str, limit: usize) -> anyhow::Result<()> {
 
         println!();
     }
+    print_cross_references(&elsewhere);
 
     Ok(())
 }
 
-fn format_duration(seconds: i64) -> String {
+/// Sessions of other projects that edited matching files of this one.
+fn print_cross_references(elsewhere: &[db::crossrefs::CrossReference]) {
+    if elsewhere.is_empty() {
+        return;
+    }
+    println!("\nEdited from other projects:\n");
+    for xref in elsewhere {
+        let date = &xref.started_at[..10.min(xref.started_at.len())];
+        println!("--- {} | from {} ---", date, xref.source_project);
+        println!("  ID: {}", xref.session_id);
+        if let Some(prompt) = &xref.prompt {
+            println!("  First prompt: {}", truncate(prompt, 120));
+        }
+        let files: Vec<String> = serde_json::from_str(&xref.files).unwrap_or_default();
+        println!("  Files: {}", files.join(", "));
+        println!();
+    }
+}
+
+pub fn format_duration(seconds: i64) -> String {
     if seconds < 60 {
         format!("{}s", seconds)
     } else if seconds < 3600 {
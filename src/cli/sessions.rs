@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use crate::config;
+use crate::db;
+use crate::db::sessions::SessionRow;
+use crate::text::one_line;
+use crate::transcript::render;
+
+use super::search::format_duration;
+
+/// `claude-memory sessions list`
+pub fn list(since: Option<&str>, branch: Option<&str>, limit: usize) -> anyhow::Result<()> {
+    let conn = match super::open_project_db()? {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+
+    let date_from = since.map(parse_since).transpose()?;
+    let results = db::sessions::list_sessions(&conn, limit, date_from.as_deref(), None, branch, None)?;
+
+    if results.is_empty() {
+        println!("No sessions found.");
+        return Ok(());
+    }
+
+    for session in &results {
+        let started = session.started_at.get(..16).unwrap_or(&session.started_at).replace('T', " ");
+        let duration = session
+            .duration_seconds
+            .map(format_duration)
+            .unwrap_or_else(|| "?".to_string());
+        let branch = session.git_branch.as_deref().unwrap_or("?");
+        let first_prompt = serde_json::from_str::<Vec<String>>(&session.user_prompts)
+            .ok()
+            .and_then(|p| p.into_iter().next())
+            .map(|p| one_line(&p, 60))
+            .unwrap_or_default();
+
+        println!(
+            "{}  {}  {:>7}  {:<20}  {}",
+            &session.id[..8.min(session.id.len())],
+            started,
+            duration,
+            branch,
+            first_prompt
+        );
+    }
+
+    println!("\n{} session(s). Use `claude-memory sessions show <id>` for details.", results.len());
+    Ok(())
+}
+
+/// `claude-memory sessions show <id>`
+pub fn show(id: &str) -> anyhow::Result<()> {
+    let conn = match super::open_project_db()? {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+
+    let session = find_session(&conn, id)?;
+    print!("{}", format_session(&session));
+    Ok(())
+}
+
+/// `claude-memory sessions transcript <id>`
+pub fn transcript(id: &str, raw: bool, no_pager: bool) -> anyhow::Result<()> {
+    let conn = match super::open_project_db()? {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+
+    let session = find_session(&conn, id)?;
+    let path = match config::locate_transcript(&session.id, session.transcript_path.as_deref()) {
+        Some(p) => p,
+        None => {
+            println!(
+                "Transcript for session {} is no longer on disk{}.",
+                session.id,
+                session
+                    .transcript_path
+                    .as_deref()
+                    .map(|p| format!(" (was {})", p))
+                    .unwrap_or_default()
+            );
+            return Ok(());
+        }
+    };
+
+    let content = if raw {
+        std::fs::read(&path)?
+    } else {
+        let mut buf = Vec::new();
+        render::render_transcript(&path, &mut buf)?;
+        buf
+    };
+
+    if no_pager {
+        std::io::stdout().write_all(&content)?;
+        Ok(())
+    } else {
+        page(&content)
+    }
+}
+
+fn find_session(conn: &rusqlite::Connection, id: &str) -> anyhow::Result<SessionRow> {
+    let full_id = db::sessions::resolve_session_id(conn, id)?;
+    db::sessions::get_session(conn, &full_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", full_id))
+}
+
+/// Accept an ISO date (`2026-02-01`) or a relative age (`3d`, `2w`, `12h`).
+pub(crate) fn parse_since(since: &str) -> anyhow::Result<String> {
+    let relative = since
+        .strip_suffix('h')
+        .map(|n| (n, 1))
+        .or_else(|| since.strip_suffix('d').map(|n| (n, 24)))
+        .or_else(|| since.strip_suffix('w').map(|n| (n, 24 * 7)));
+
+    if let Some((n, hours_per_unit)) = relative {
+        if let Ok(n) = n.parse::<i64>() {
+            let from = chrono::Utc::now() - chrono::Duration::hours(n * hours_per_unit);
+            return Ok(from.format("%Y-%m-%dT%H:%M:%S").to_string());
+        }
+    }
+
+    anyhow::ensure!(
+        chrono::NaiveDate::parse_from_str(since.get(..10).unwrap_or(since), "%Y-%m-%d").is_ok(),
+        "Invalid --since: {} (expected YYYY-MM-DD or an age like 3d, 2w, 12h)",
+        since
+    );
+    Ok(since.to_string())
+}
+
+fn format_session(session: &SessionRow) -> String {
+    let mut out = format!("Session {}\n", session.id);
+    out.push_str(&format!("Started:   {}\n", session.started_at));
+    if let Some(ended) = &session.ended_at {
+        out.push_str(&format!("Ended:     {}\n", ended));
+    }
+    if let Some(d) = session.duration_seconds {
+        out.push_str(&format!("Duration:  {}\n", format_duration(d)));
+    }
+    out.push_str(&format!("Branch:    {}\n", session.git_branch.as_deref().unwrap_or("?")));
+    if let Some(model) = &session.model {
+        out.push_str(&format!("Model:     {}\n", model));
+    }
+    out.push_str(&format!("Project:   {}\n", session.project_dir));
+    out.push_str(&format!(
+        "Tokens:    {} input / {} output\n",
+        session.input_tokens, session.output_tokens
+    ));
+
+    let list = |out: &mut String, title: &str, json: &str| {
+        let items: Vec<String> = serde_json::from_str(json).unwrap_or_default();
+        if !items.is_empty() {
+            out.push_str(&format!("\n{} ({}):\n", title, items.len()));
+            for item in items {
+                out.push_str(&format!("  - {}\n", item.replace('\n', "\n    ")));
+            }
+        }
+    };
+
+    list(&mut out, "Prompts", &session.user_prompts);
+    list(&mut out, "Files modified", &session.files_modified);
+    list(&mut out, "Files read", &session.files_read);
+    list(&mut out, "Commands", &session.commands_run);
+    list(&mut out, "Commits", &session.git_commits);
+
+    if let Ok(tools) = serde_json::from_str::<HashMap<String, u32>>(&session.tools_used) {
+        if !tools.is_empty() {
+            let mut sorted: Vec<_> = tools.into_iter().collect();
+            sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            let display: Vec<String> = sorted.iter().map(|(k, v)| format!("{}:{}", k, v)).collect();
+            out.push_str(&format!("\nTool usage: {}\n", display.join(", ")));
+        }
+    }
+
+    out
+}
+
+/// Show `content` through `$PAGER` (default `less -R`) when stdout is a terminal.
+fn page(content: &[u8]) -> anyhow::Result<()> {
+    let mut stdout = std::io::stdout();
+    if !stdout.is_terminal() {
+        stdout.write_all(content)?;
+        return Ok(());
+    }
+
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less -R".to_string());
+    let mut parts = pager.split_whitespace();
+    let spawned = parts.next().and_then(|program| {
+        Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .spawn()
+            .ok()
+    });
+
+    match spawned {
+        Some(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                // The pager may exit early (e.g. `q` in less); that's not an error
+                let _ = stdin.write_all(content);
+            }
+            child.wait()?;
+        }
+        None => stdout.write_all(content)?,
+    }
+    Ok(())
+}
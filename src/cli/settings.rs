@@ -0,0 +1,256 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::config::{self, Settings, SettingsLayers, Source};
+
+/// `claude-memory config show`: every effective setting and where it comes from.
+pub fn show() -> anyhow::Result<()> {
+    let project_dir = config::detect_project_dir()?;
+    let layers = SettingsLayers::load(Some(&project_dir))?;
+    let settings = layers.settings()?;
+
+    let user_path = config::user_settings_path();
+    let project_path = config::settings_path(&project_dir);
+    println!("User file:    {}", describe_file(user_path.as_deref()));
+    println!("Project file: {}", describe_file(Some(&project_path)));
+
+    let mut current_section = String::new();
+    for (section, key, value) in entries(&settings)? {
+        if section != current_section {
+            println!("\n[{}]", section);
+            current_section = section.clone();
+        }
+        println!("{:<22} = {:<24} # {}", key, value, layers.source(&section, &key));
+    }
+
+    let known = entries(&Settings::default())?;
+    for (name, layer) in [("user", &layers.user), ("project", &layers.project)] {
+        for key in unknown_keys(layer.as_ref(), &known) {
+            eprintln!("Warning: unknown setting {} in the {} file is ignored", key, name);
+        }
+    }
+    Ok(())
+}
+
+/// `claude-memory config get <section.key>`
+pub fn get(key: &str) -> anyhow::Result<()> {
+    let project_dir = config::detect_project_dir()?;
+    let layers = SettingsLayers::load(Some(&project_dir))?;
+    let (section, name) = split_key(key)?;
+
+    let value = entries(&layers.settings()?)?
+        .into_iter()
+        .find(|(s, k, _)| *s == section && *k == name)
+        .map(|(_, _, v)| v)
+        .ok_or_else(|| unknown_setting(key))?;
+    println!("{}  ({})", value, layers.source(section, name));
+    Ok(())
+}
+
+/// `claude-memory config set <section.key> <value> [--user]`
+pub fn set(key: &str, value: &str, user: bool) -> anyhow::Result<()> {
+    let project_dir = config::detect_project_dir()?;
+    let path = if user {
+        config::user_settings_path().ok_or_else(|| anyhow::anyhow!("HOME is not set"))?
+    } else {
+        config::settings_path(&project_dir)
+    };
+
+    let written = set_in_file(&path, key, value)?;
+    println!("Set {} = {} in {}", key, written, path.display());
+
+    // A user setting the project overrides has no effect here
+    let (section, name) = split_key(key)?;
+    let layers = SettingsLayers::load(Some(&project_dir))?;
+    if user && layers.source(section, name) == Source::Project {
+        println!(
+            "Note: {} also sets {}, which takes precedence in this project.",
+            config::settings_path(&project_dir).display(),
+            key
+        );
+    }
+    Ok(())
+}
+
+/// Write `key = value` into a settings file, keeping its comments and layout.
+/// The value is parsed as TOML (so `5` and `true` keep their types), falling
+/// back to a string. Returns the value as written.
+pub(crate) fn set_in_file(path: &Path, key: &str, value: &str) -> anyhow::Result<String> {
+    let (section, name) = split_key(key)?;
+    let default = entries(&Settings::default())?
+        .into_iter()
+        .find(|(s, k, _)| s == section && k == name)
+        .map(|(_, _, v)| v)
+        .ok_or_else(|| unknown_setting(key))?;
+
+    let parsed = match value.parse::<toml_edit::Value>() {
+        // Paths and other strings are never reinterpreted as numbers
+        Ok(v) if !default.is_str() || v.is_str() => v,
+        _ => toml_edit::Value::from(value),
+    };
+
+    let content = if path.is_file() {
+        std::fs::read_to_string(path)?
+    } else {
+        String::new()
+    };
+    let mut doc: toml_edit::DocumentMut = content
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))?;
+    let table = doc
+        .entry(section)
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .ok_or_else(|| anyhow::anyhow!("[{}] in {} is not a table", section, path.display()))?;
+    table.insert(name, toml_edit::value(parsed.clone()));
+
+    let updated = doc.to_string();
+    let check: toml::Table = toml::from_str(&updated)?;
+    if config::user_settings_path().as_deref() != Some(path) {
+        config::check_project_layer(&check)?;
+    }
+    Settings::deserialize(check).map_err(|e| anyhow::anyhow!("Invalid value for {}: {}", key, e))?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, updated)?;
+    Ok(parsed.decorated("", "").to_string())
+}
+
+/// `(section, key, value)` for every setting, sorted by section and key.
+fn entries(settings: &Settings) -> anyhow::Result<Vec<(String, String, toml::Value)>> {
+    let table = toml::Table::try_from(settings)?;
+    let mut out = Vec::new();
+    for (section, values) in table {
+        if let toml::Value::Table(values) = values {
+            for (key, value) in values {
+                out.push((section.clone(), key, value));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Keys in a settings file that no setting reads.
+fn unknown_keys(layer: Option<&toml::Table>, known: &[(String, String, toml::Value)]) -> Vec<String> {
+    let is_known = |section: &str, key: Option<&str>| {
+        known
+            .iter()
+            .any(|(s, k, _)| s == section && key.is_none_or(|key| k == key))
+    };
+    let mut unknown = Vec::new();
+    for (section, values) in layer.into_iter().flatten() {
+        match values.as_table() {
+            Some(values) if is_known(section, None) => unknown.extend(
+                values
+                    .keys()
+                    .filter(|key| !is_known(section, Some(key)))
+                    .map(|key| format!("{}.{}", section, key)),
+            ),
+            _ => unknown.push(section.clone()),
+        }
+    }
+    unknown
+}
+
+fn split_key(key: &str) -> anyhow::Result<(&str, &str)> {
+    key.split_once('.')
+        .ok_or_else(|| anyhow::anyhow!("Settings are named <section>.<key>, e.g. search.recall_limit"))
+}
+
+fn unknown_setting(key: &str) -> anyhow::Error {
+    anyhow::anyhow!("Unknown setting: {} (see `claude-memory config show`)", key)
+}
+
+fn describe_file(path: Option<&Path>) -> String {
+    match path.map(PathBuf::from) {
+        Some(p) if p.is_file() => p.display().to_string(),
+        Some(p) => format!("{} (not present)", p.display()),
+        None => "(HOME is not set)".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_keeps_comments_and_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memory.toml");
+        std::fs::write(&path, "# my settings\n[output]\nmax_tokens = 1000 # tight\n").unwrap();
+
+        assert_eq!(set_in_file(&path, "search.recall_limit", "8").unwrap(), "8");
+        assert_eq!(set_in_file(&path, "output.verbosity", "brief").unwrap(), "\"brief\"");
+        assert_eq!(set_in_file(&path, "storage.db_path", "2024.db").unwrap(), "\"2024.db\"");
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# my settings\n[output]\nmax_tokens = 1000 # tight\n"));
+        let settings = Settings::deserialize(toml::from_str::<toml::Table>(&content).unwrap()).unwrap();
+        assert_eq!(settings.search.recall_limit, 8);
+        assert_eq!(settings.storage.db_path, "2024.db");
+    }
+
+    #[test]
+    fn set_rejects_unknown_keys_and_bad_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memory.toml");
+
+        assert!(set_in_file(&path, "search.nope", "1").unwrap_err().to_string().contains("Unknown setting"));
+        assert!(set_in_file(&path, "search.recall_limit", "many").is_err());
+        assert!(set_in_file(&path, "output.verbosity", "loud").is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn project_keys_override_user_keys() {
+        let layers = SettingsLayers {
+            user: Some(toml::from_str("[search]\nrecall_limit = 7\nnotes_limit = 4\n").unwrap()),
+            project: Some(toml::from_str("[search]\nrecall_limit = 3\n").unwrap()),
+        };
+        let settings = layers.settings().unwrap();
+        assert_eq!((settings.search.recall_limit, settings.search.notes_limit), (3, 4));
+        assert_eq!(layers.source("search", "recall_limit"), Source::Project);
+        assert_eq!(layers.source("search", "notes_limit"), Source::User);
+        assert_eq!(layers.source("search", "list_limit"), Source::Default);
+    }
+
+    #[test]
+    fn project_file_keeps_the_database_in_the_project() {
+        let layers = |project: &str| SettingsLayers {
+            user: Some(toml::from_str("[storage]\ndb_path = \"/data/memory.db\"\n").unwrap()),
+            project: Some(toml::from_str(project).unwrap()),
+        };
+        assert!(layers("[storage]\ndb_path = \"/tmp/stolen.db\"\n").settings().is_err());
+        assert!(layers("[storage]\ndb_path = \"../other/memory.db\"\n").settings().is_err());
+        let settings = layers("[storage]\ndb_path = \"./.claude/mine.db\"\nmode = \"xdg\"\n").settings().unwrap();
+        assert_eq!(settings.storage.db_path, "./.claude/mine.db");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memory.toml");
+        assert!(set_in_file(&path, "storage.db_path", "/tmp/stolen.db").is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn project_file_only_adds_redaction_patterns() {
+        let layers = |project: &str| SettingsLayers {
+            user: Some(toml::from_str("[redaction]\npatterns = [\"ACME-\\\\d+\"]\n").unwrap()),
+            project: Some(toml::from_str(project).unwrap()),
+        };
+        assert!(layers("[redaction]\nbuiltin = false\n").settings().is_err());
+        assert!(layers("[redaction]\nhigh_entropy = false\n").settings().is_err());
+        let settings = layers("[redaction]\npatterns = [\"pin \\\\d{4}\"]\n").settings().unwrap();
+        assert_eq!(settings.redaction.patterns, [r"ACME-\d+", r"pin \d{4}"]);
+        assert!(settings.redaction.builtin);
+    }
+
+    #[test]
+    fn reports_unknown_keys() {
+        let known = entries(&Settings::default()).unwrap();
+        let layer: toml::Table = toml::from_str("[search]\nrecall_limit = 3\nrecal_limit = 4\n[serch]\nx = 1\n").unwrap();
+        assert_eq!(unknown_keys(Some(&layer), &known), ["search.recal_limit", "serch"]);
+    }
+}
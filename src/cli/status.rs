@@ -1,9 +1,11 @@
+use std::path::Path;
+
 use crate::config;
 use crate::db;
 
 pub fn run() -> anyhow::Result<()> {
     let project_dir = config::detect_project_dir()?;
-    let db_path = config::db_path(&project_dir);
+    let db_path = config::db_path(&project_dir)?;
 
     if !db_path.exists() {
         println!("No memory database found at {}", db_path.display());
@@ -15,6 +17,8 @@ pub fn run() -> anyhow::Result<()> {
 
     let (session_count, total_input, total_output) = db::sessions::session_stats(&conn)?;
     let note_count = db::notes::note_count(&conn)?;
+    let skipped = db::sessions::skipped_counts(&conn)?;
+    let cross_references = db::crossrefs::cross_reference_count(&conn)?;
 
     // Get DB file size
     let file_size = std::fs::metadata(&db_path)?.len();
@@ -34,8 +38,24 @@ pub fn run() -> anyhow::Result<()> {
     println!("Project:    {}", project_dir.display());
     println!("Database:   {}", db_path.display());
     println!("DB size:    {}", format_bytes(file_size));
+    let encrypted = db::encryption::file_state(&db_path) == db::encryption::FileState::Encrypted;
+    println!("Encrypted:  {}", if encrypted { "yes" } else { "no" });
+    let info = db::project::project_info(&conn)?;
+    println!("Identity:   {}", info.identity.as_deref().unwrap_or("(not recorded yet)"));
+    // Another clone still at the recorded path shares this memory legitimately
+    if let Some(recorded) = info.path.filter(|p| Path::new(p) != project_dir && !Path::new(p).exists()) {
+        println!("Moved:      paths are stored under {}; run `claude-memory relink {}`", recorded, recorded);
+    }
     println!();
     println!("Sessions:   {}", session_count);
+    if !skipped.is_empty() {
+        let total: i64 = skipped.iter().map(|(_, n)| n).sum();
+        let reasons: Vec<String> = skipped.iter().map(|(r, n)| format!("{} by {}", n, r)).collect();
+        println!("Skipped:    {} private ({})", total, reasons.join(", "));
+    }
+    if cross_references > 0 {
+        println!("Edited by:  {} session(s) of other projects", cross_references);
+    }
     println!("Notes:      {}", note_count);
     println!(
         "Tokens:     {} input / {} output",
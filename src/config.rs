@@ -1,43 +1,518 @@
 use std::path::{Path, PathBuf};
 
+use serde::{Deserialize, Serialize};
+
+use crate::identity;
+use crate::registry::Registry;
+use crate::storage;
+
+/// Settings, read from `~/.claude/memory.toml` (user) and
+/// `<project>/.claude/memory.toml` (project). Project keys override user keys.
+///
+/// Every key is optional; missing keys keep their defaults.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub parser: ParserSettings,
+    pub search: SearchSettings,
+    pub output: OutputSettings,
+    pub storage: StorageSettings,
+    pub redaction: RedactionSettings,
+    pub privacy: PrivacySettings,
+    pub retention: RetentionSettings,
+    pub scopes: ScopeSettings,
+}
+
+/// How much of a transcript is kept at ingest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParserSettings {
+    /// User prompts longer than this many characters are truncated.
+    pub max_prompt_len: usize,
+    /// Distinct shell commands kept per session.
+    pub max_commands: usize,
+    /// Shell commands longer than this many characters are truncated.
+    pub max_command_len: usize,
+}
+
+impl Default for ParserSettings {
+    fn default() -> Self {
+        Self {
+            max_prompt_len: 2000,
+            max_commands: 50,
+            max_command_len: 200,
+        }
+    }
+}
+
+/// Result counts for searches that don't set `limit`, and the caps on it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchSettings {
+    pub recall_limit: usize,
+    pub max_recall_limit: usize,
+    pub list_limit: usize,
+    pub max_list_limit: usize,
+    pub notes_limit: usize,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        Self {
+            recall_limit: 5,
+            max_recall_limit: 20,
+            list_limit: 10,
+            max_list_limit: 50,
+            notes_limit: 10,
+        }
+    }
+}
+
+/// Secrets scrubbed from sessions before they are stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionSettings {
+    /// Run the built-in detectors (key shapes, JWTs, private keys, `password=`, ...).
+    pub builtin: bool,
+    /// Also redact long random-looking strings.
+    pub high_entropy: bool,
+    /// Extra regexes. A match is redacted whole, or just its `secret` group if it has one.
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            builtin: true,
+            high_entropy: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// What is kept out of memory altogether.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacySettings {
+    /// A session whose first prompt contains this is not stored (empty = off).
+    pub marker: String,
+    /// Gitignore-style globs for paths never recorded as read or modified;
+    /// commands mentioning them are dropped too.
+    pub exclude_paths: Vec<String>,
+}
+
+impl Default for PrivacySettings {
+    fn default() -> Self {
+        Self {
+            marker: "#nomemory".to_string(),
+            exclude_paths: Vec::new(),
+        }
+    }
+}
+
+/// Sub-projects of a monorepo, so sessions can be told apart by package.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScopeSettings {
+    /// Globs of sub-project directories relative to the project root, e.g.
+    /// `packages/*`.
+    pub roots: Vec<String>,
+    /// Also use the workspace members of Cargo.toml and package.json.
+    pub detect: bool,
+}
+
+/// Where databases live.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageSettings {
+    /// Keep the database in the project, or in the user's data directory.
+    pub mode: StorageMode,
+    /// Database file, relative to the project root unless absolute. Only used
+    /// in `project` mode.
+    pub db_path: String,
+    /// Directory levels under each scan root searched for projects' databases.
+    pub discovery_depth: usize,
+    /// Directories searched by `claude-memory projects rescan` (`~/` expands
+    /// to the home directory). `$HOME` when empty.
+    pub scan_roots: Vec<String>,
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        Self {
+            mode: StorageMode::default(),
+            db_path: DEFAULT_DB_PATH.to_string(),
+            discovery_depth: 2,
+            scan_roots: Vec::new(),
+        }
+    }
+}
+
+/// Where a project's database is kept.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageMode {
+    /// In the project, at `storage.db_path`
+    #[default]
+    Project,
+    /// Outside the repository, in `$XDG_DATA_HOME/claude-memory/<project-id>/`
+    Xdg,
+}
+
+impl std::fmt::Display for StorageMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            StorageMode::Project => "project",
+            StorageMode::Xdg => "xdg",
+        })
+    }
+}
+
+/// `storage.db_path` when not configured.
+const DEFAULT_DB_PATH: &str = ".claude/memory.db";
+
+/// Requested amount of detail for a tool result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    Brief,
+    Normal,
+    Full,
+}
+
+impl Verbosity {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "brief" => Some(Self::Brief),
+            "normal" => Some(Self::Normal),
+            "full" => Some(Self::Full),
+            _ => None,
+        }
+    }
+}
+
+/// Defaults for how much MCP tools print.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputSettings {
+    /// Detail level when a tool call doesn't ask for one.
+    pub verbosity: Verbosity,
+    /// Estimated token budget per tool result when a call doesn't set `max_tokens` (0 = unlimited).
+    pub max_tokens: usize,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        Self {
+            verbosity: Verbosity::Normal,
+            max_tokens: 4000,
+        }
+    }
+}
+
+/// What `claude-memory prune` removes or slims down. Limits of 0 are off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionSettings {
+    /// Delete sessions that started more than this many days ago.
+    pub max_age_days: u64,
+    /// Keep at most this many sessions, deleting the oldest.
+    pub max_sessions: usize,
+    /// Delete the oldest sessions until the data fits in this many megabytes.
+    pub max_db_mb: u64,
+    /// Never delete sessions that notes refer to.
+    pub keep_with_notes: bool,
+    /// Never delete sessions that made git commits.
+    pub keep_with_commits: bool,
+    /// Drop `files_read` and `commands_run` from sessions older than this many days.
+    pub summarize_after_days: u64,
+    /// Also prune after every ingest (without `VACUUM`).
+    pub prune_after_ingest: bool,
+    /// Optimize the search indexes and `VACUUM` after `claude-memory prune`.
+    pub vacuum: bool,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            max_age_days: 0,
+            max_sessions: 0,
+            max_db_mb: 0,
+            keep_with_notes: true,
+            keep_with_commits: false,
+            summarize_after_days: 0,
+            prune_after_ingest: false,
+            vacuum: true,
+        }
+    }
+}
+
+impl RetentionSettings {
+    /// Whether any limit is set, i.e. whether pruning can change anything.
+    pub fn is_enabled(&self) -> bool {
+        self.max_age_days > 0
+            || self.max_sessions > 0
+            || self.max_db_mb > 0
+            || self.summarize_after_days > 0
+    }
+}
+
+/// Path of the project settings file: `<project>/.claude/memory.toml`
+pub fn settings_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(".claude").join("memory.toml")
+}
+
+/// Path of the user settings file: `~/.claude/memory.toml`
+pub fn user_settings_path() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".claude").join("memory.toml"))
+}
+
+/// Path of the user's global memory: `~/.claude/memory-global.db`, for notes
+/// that apply in every project.
+pub fn global_db_path() -> anyhow::Result<PathBuf> {
+    let home = std::env::var("HOME").map_err(|_| anyhow::anyhow!("HOME is not set; the global memory lives there"))?;
+    Ok(PathBuf::from(home).join(".claude").join("memory-global.db"))
+}
+
+/// Where an effective setting comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    User,
+    Project,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Default => "default",
+            Self::User => "user",
+            Self::Project => "project",
+        })
+    }
+}
+
+/// The settings files that exist, each already parsed and checked.
+#[derive(Debug, Default)]
+pub struct SettingsLayers {
+    pub user: Option<toml::Table>,
+    pub project: Option<toml::Table>,
+}
+
+impl SettingsLayers {
+    /// Read the user file and, given a project, the project file.
+    pub fn load(project_dir: Option<&Path>) -> anyhow::Result<Self> {
+        let user_path = user_settings_path();
+        let project_path = project_dir.map(settings_path);
+        // With the project at $HOME both are the same file; treat it as the project's
+        let user = match user_path.filter(|p| Some(p) != project_path.as_ref()) {
+            Some(path) => read_table(&path)?,
+            None => None,
+        };
+        let project = match project_path {
+            Some(path) => read_table(&path)?,
+            None => None,
+        };
+        Ok(Self { user, project })
+    }
+
+    /// Effective settings: defaults, overridden by user keys, overridden by
+    /// project keys, within what [`check_project_layer`] lets a project set.
+    pub fn settings(&self) -> anyhow::Result<Settings> {
+        if let Some(project) = &self.project {
+            check_project_layer(project)?;
+        }
+        let mut merged = toml::Table::new();
+        for layer in [&self.user, &self.project].into_iter().flatten() {
+            merge_table(&mut merged, layer);
+        }
+        // Project redaction patterns add to the user's instead of replacing them
+        let patterns = |layer: &Option<toml::Table>| {
+            layer
+                .as_ref()
+                .and_then(|t| t.get("redaction"))
+                .and_then(|r| r.get("patterns"))
+                .and_then(|p| p.as_array())
+                .cloned()
+        };
+        if let (Some(mut all), Some(project)) = (patterns(&self.user), patterns(&self.project)) {
+            all.extend(project);
+            if let Some(toml::Value::Table(redaction)) = merged.get_mut("redaction") {
+                redaction.insert("patterns".to_string(), toml::Value::Array(all));
+            }
+        }
+        Ok(Settings::deserialize(merged)?)
+    }
+
+    /// The layer that sets `section.key`.
+    pub fn source(&self, section: &str, key: &str) -> Source {
+        let sets = |layer: &Option<toml::Table>| {
+            layer
+                .as_ref()
+                .and_then(|t| t.get(section))
+                .and_then(|s| s.as_table())
+                .is_some_and(|s| s.contains_key(key))
+        };
+        if sets(&self.project) {
+            Source::Project
+        } else if sets(&self.user) {
+            Source::User
+        } else {
+            Source::Default
+        }
+    }
+}
+
+/// Parse a settings file, or `None` if it doesn't exist. Values of the wrong
+/// type are reported against the file they are in.
+fn read_table(path: &Path) -> anyhow::Result<Option<toml::Table>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)?;
+    let invalid = |e: toml::de::Error| anyhow::anyhow!("Invalid {}: {}", path.display(), e);
+    let table: toml::Table = toml::from_str(&content).map_err(invalid)?;
+    Settings::deserialize(table.clone()).map_err(invalid)?;
+    Ok(Some(table))
+}
+
+/// A project's memory.toml comes with the repository, so it may not point
+/// the database outside the project: `storage.db_path` must be a relative
+/// path without `..`. Other locations are for the user file. Nor may it
+/// weaken redaction: it can add `redaction.patterns`, nothing else.
+pub fn check_project_layer(project: &toml::Table) -> anyhow::Result<()> {
+    if let Some(redaction) = project.get("redaction").and_then(|r| r.as_table()) {
+        if let Some(key) = redaction.keys().find(|k| *k != "patterns") {
+            anyhow::bail!(
+                "redaction.{} can't be set in the project's memory.toml, only patterns added; \
+                 set it in ~/.claude/memory.toml",
+                key
+            );
+        }
+    }
+    let db_path = project
+        .get("storage")
+        .and_then(|s| s.get("db_path"))
+        .and_then(|p| p.as_str());
+    if let Some(db_path) = db_path {
+        let inside = Path::new(db_path)
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_) | std::path::Component::CurDir));
+        anyhow::ensure!(
+            inside,
+            "storage.db_path = {:?} in the project's memory.toml leaves the project; \
+             set other locations in ~/.claude/memory.toml",
+            db_path
+        );
+    }
+    Ok(())
+}
+
+/// Merge `overlay` into `base`, section by section.
+fn merge_table(base: &mut toml::Table, overlay: &toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(section)) => {
+                merge_table(existing, section)
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Load the effective settings for a project, falling back to defaults for
+/// anything neither file sets. A file that fails to load is an error, not
+/// the defaults: ingest stops on it rather than store what it excludes.
+pub fn load_settings(project_dir: &Path) -> anyhow::Result<Settings> {
+    SettingsLayers::load(Some(project_dir))?.settings()
+}
+
+/// Load user settings only, for things that aren't tied to one project.
+pub fn load_user_settings() -> anyhow::Result<Settings> {
+    SettingsLayers::load(None)?.settings()
+}
+
 /// A discovered project with a memory database.
 pub struct DiscoveredProject {
     pub project_dir: PathBuf,
     pub db_path: PathBuf,
 }
 
-/// Scan for all projects with memory databases.
+/// Find all projects with memory databases.
 ///
-/// Searches at two depths under `$HOME`:
-///   `$HOME/*/.claude/memory.db`   (depth 1)
-///   `$HOME/*/*/.claude/memory.db` (depth 2)
+/// Reads the project registry (`~/.claude/memory-projects.json`), which
+/// ingest keeps up to date. The first time, before any registry exists, it
+/// is filled by [`scan_for_projects`]; a damaged registry is left alone and
+/// a scan stands in for it.
 pub fn discover_project_dbs() -> Vec<DiscoveredProject> {
-    let home = match std::env::var("HOME") {
-        Ok(h) => PathBuf::from(h),
-        Err(_) => return Vec::new(),
+    let registry = match Registry::load() {
+        Ok(Some(registry)) => registry,
+        loaded => {
+            let mut registry = Registry::default();
+            for project in scan_for_projects() {
+                registry.insert(&project.project_dir, &project.db_path);
+            }
+            match loaded {
+                Err(e) => eprintln!("Warning: {}", e),
+                Ok(_) => {
+                    let _ = registry.save();
+                }
+            }
+            registry
+        }
     };
 
-    let mut results = Vec::new();
+    let mut results: Vec<DiscoveredProject> = registry
+        .projects
+        .into_iter()
+        .filter(|(_, entry)| entry.db_path.is_file())
+        .map(|(project_dir, entry)| DiscoveredProject {
+            project_dir,
+            db_path: entry.db_path,
+        })
+        .collect();
 
-    // Depth 1: $HOME/<project>/.claude/memory.db
-    scan_depth(&home, &mut results);
+    results.sort_by(|a, b| a.project_dir.cmp(&b.project_dir));
+    results
+}
 
-    // Depth 2: $HOME/<dir>/<project>/.claude/memory.db
-    if let Ok(entries) = std::fs::read_dir(&home) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                scan_depth(&path, &mut results);
-            }
-        }
-    }
+/// Search the scan roots from the user settings (`storage.scan_roots`,
+/// `$HOME` by default) `storage.discovery_depth` levels down (2 by default):
+///   `<root>/*/.claude/`   (depth 1)
+///   `<root>/*/*/.claude/` (depth 2)
+pub fn scan_for_projects() -> Vec<DiscoveredProject> {
+    let storage = load_user_settings().map(|s| s.storage).unwrap_or_default();
+    let home = std::env::var("HOME").ok().map(PathBuf::from);
+    let roots: Vec<PathBuf> = if storage.scan_roots.is_empty() {
+        home.into_iter().collect()
+    } else {
+        storage
+            .scan_roots
+            .iter()
+            .map(|root| match (root.strip_prefix("~/"), &home) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => PathBuf::from(root),
+            })
+            .collect()
+    };
 
+    let mut results = Vec::new();
+    for root in roots {
+        scan_depth(&root, storage.discovery_depth, &mut results);
+    }
     results.sort_by(|a, b| a.project_dir.cmp(&b.project_dir));
+    results.dedup_by(|a, b| a.db_path == b.db_path);
     results
 }
 
-/// Scan immediate children of `parent` for `.claude/memory.db`.
-fn scan_depth(parent: &Path, results: &mut Vec<DiscoveredProject>) {
+/// Scan children of `parent`, `depth` levels down, for projects with a database.
+fn scan_depth(parent: &Path, depth: usize, results: &mut Vec<DiscoveredProject>) {
+    if depth == 0 {
+        return;
+    }
     let entries = match std::fs::read_dir(parent) {
         Ok(e) => e,
         Err(_) => return,
@@ -48,23 +523,50 @@ fn scan_depth(parent: &Path, results: &mut Vec<DiscoveredProject>) {
         if !project_dir.is_dir() {
             continue;
         }
-        let db = project_dir.join(".claude").join("memory.db");
-        if db.is_file() {
-            results.push(DiscoveredProject {
-                project_dir,
-                db_path: db,
-            });
+        // Only projects with a .claude/ directory can have settings or a database
+        if project_dir.join(".claude").is_dir() {
+            // Broken settings hide the project until they are fixed
+            if let Some(db) = db_path(&project_dir).ok().filter(|db| db.is_file()) {
+                results.push(DiscoveredProject {
+                    project_dir: project_dir.clone(),
+                    db_path: db,
+                });
+            }
+        }
+        scan_depth(&project_dir, depth - 1, results);
+    }
+}
+
+/// Locate the transcript of a session that is still on disk.
+///
+/// Uses the path recorded at ingest when it still exists, otherwise looks for
+/// `<session_id>.jsonl` under Claude Code's `~/.claude/projects/*/`.
+pub fn locate_transcript(session_id: &str, recorded: Option<&str>) -> Option<PathBuf> {
+    if let Some(path) = recorded.map(PathBuf::from) {
+        if path.is_file() {
+            return Some(path);
         }
     }
+
+    let home = std::env::var("HOME").ok()?;
+    let projects = PathBuf::from(home).join(".claude").join("projects");
+    let file_name = format!("{}.jsonl", session_id);
+
+    std::fs::read_dir(projects)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path().join(&file_name))
+        .find(|path| path.is_file())
 }
 
 /// Find the project root by walking up from `start` looking for `.git/`.
-/// Falls back to `start` itself if no `.git/` found.
+/// Falls back to `start` itself if no `.git/` found. A linked git worktree
+/// resolves to its main checkout, so all worktrees share one memory.
 pub fn find_project_root(start: &Path) -> PathBuf {
     let mut dir = start.to_path_buf();
     loop {
         if dir.join(".git").exists() {
-            return dir;
+            return identity::main_worktree(&dir).unwrap_or(dir);
         }
         if !dir.pop() {
             return start.to_path_buf();
@@ -72,9 +574,44 @@ pub fn find_project_root(start: &Path) -> PathBuf {
     }
 }
 
-/// Resolve the database path for a project: `<project>/.claude/memory.db`
-pub fn db_path(project_dir: &Path) -> PathBuf {
-    project_dir.join(".claude").join("memory.db")
+/// Resolve the database path for a project: `storage.db_path` from the
+/// settings, `<project>/.claude/memory.db` by default, or
+/// `$XDG_DATA_HOME/claude-memory/<project-id>/memory.db` in `xdg` mode.
+///
+/// Unreadable settings are an error rather than a fallback to the default
+/// location, which would quietly write to a different database.
+pub fn db_path(project_dir: &Path) -> anyhow::Result<PathBuf> {
+    let storage = load_settings(project_dir)?.storage;
+    resolve_db_path(project_dir, &storage, storage.mode)
+}
+
+/// Database path for a storage mode, regardless of the configured one.
+pub fn db_path_for(project_dir: &Path, mode: StorageMode) -> anyhow::Result<PathBuf> {
+    let storage = load_settings(project_dir)?.storage;
+    resolve_db_path(project_dir, &storage, mode)
+}
+
+fn resolve_db_path(project_dir: &Path, storage: &StorageSettings, mode: StorageMode) -> anyhow::Result<PathBuf> {
+    match mode {
+        StorageMode::Project => Ok(project_dir.join(&storage.db_path)),
+        StorageMode::Xdg => storage::data_db_path(project_dir)
+            .ok_or_else(|| anyhow::anyhow!("Cannot locate the data directory: HOME is not set")),
+    }
+}
+
+/// Directory for database snapshots: `<project>/.claude/memory-backups/`, or
+/// `memory-backups/` next to the database in `xdg` mode.
+pub fn backup_dir(project_dir: &Path) -> anyhow::Result<PathBuf> {
+    let mode = load_settings(project_dir)?.storage.mode;
+    Ok(backup_dir_for(project_dir, mode))
+}
+
+/// Snapshot directory for a storage mode, regardless of the configured one.
+pub fn backup_dir_for(project_dir: &Path, mode: StorageMode) -> PathBuf {
+    match (mode, storage::data_db_path(project_dir)) {
+        (StorageMode::Xdg, Some(db)) => db.with_file_name("memory-backups"),
+        _ => project_dir.join(".claude").join("memory-backups"),
+    }
 }
 
 /// Detect the project directory from the environment.
@@ -92,3 +629,19 @@ pub fn detect_project_dir() -> anyhow::Result<PathBuf> {
     let cwd = std::env::current_dir()?;
     Ok(find_project_root(&cwd))
 }
+
+/// Resolve a project given by path or by name.
+///
+/// A path to an existing directory is walked up to its project root. Anything
+/// else is matched against the directory names of discovered projects.
+pub fn resolve_project(spec: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(spec);
+    if path.is_dir() {
+        return Some(find_project_root(&path));
+    }
+
+    discover_project_dbs()
+        .into_iter()
+        .find(|p| p.project_dir.file_name().is_some_and(|n| n == spec))
+        .map(|p| p.project_dir)
+}
@@ -0,0 +1,199 @@
+//! Sessions that modified files of other projects are noted in those
+//! projects' memory (`cross_references`), with the session's first prompt.
+//! The notes are written at ingest and follow the session when it is
+//! forgotten or redacted, so no copy of the text outlives the original.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::db;
+use crate::db::crossrefs::CrossReference;
+use crate::db::sessions::SessionRow;
+use crate::transcript::exclude::PathFilter;
+use crate::transcript::metadata::SessionMetadata;
+
+/// Note the session in the memory of every other project it modified files
+/// of, so that project knows about the change. Only projects that already
+/// have a database are written to, and their own `privacy.exclude_paths`
+/// apply. Best-effort: failures only warn. Returns how many were written.
+pub fn record(meta: &SessionMetadata, project_dir: &Path) -> usize {
+    let mut recorded = 0;
+    for (other, files) in other_projects(meta.files_modified.iter().map(|f| f.as_str()), project_dir) {
+        match record_in(&other, files, meta, project_dir) {
+            Ok(true) => recorded += 1,
+            Ok(false) => {}
+            Err(e) => eprintln!("claude-memory: cannot note the session in {}: {}", other.display(), e),
+        }
+    }
+    recorded
+}
+
+fn record_in(other: &Path, mut files: Vec<&str>, meta: &SessionMetadata, project_dir: &Path) -> anyhow::Result<bool> {
+    let db_path = config::db_path(other)?;
+    if !db_path.is_file() {
+        return Ok(false);
+    }
+    let settings = config::load_settings(other)?;
+    let filter = PathFilter::new(&settings.privacy.exclude_paths, other)?;
+    files.retain(|f| !filter.is_excluded(f));
+    if files.is_empty() {
+        return Ok(false);
+    }
+    files.sort();
+
+    let conn = db::open(&db_path)?;
+    db::crossrefs::record_cross_reference(
+        &conn,
+        &CrossReference {
+            session_id: meta.session_id.clone(),
+            source_project: project_dir.to_string_lossy().to_string(),
+            started_at: meta.first_timestamp.clone().unwrap_or_else(|| "unknown".to_string()),
+            prompt: meta.user_prompts.first().cloned(),
+            files: serde_json::to_string(&files)?,
+        },
+    )?;
+    Ok(true)
+}
+
+/// Remove the notes of forgotten sessions from the other projects.
+/// Best-effort like `record`. Returns how many were removed.
+pub fn forget(sessions: &[SessionRow], project_dir: &Path) -> usize {
+    each_note(sessions, project_dir, |conn, session| {
+        db::crossrefs::delete_cross_reference(conn, &session.id)
+    })
+}
+
+/// Copy the (redacted) first prompt of each session over its notes in the
+/// other projects. Best-effort like `record`. Returns how many changed.
+pub fn update_prompts(sessions: &[SessionRow], project_dir: &Path) -> usize {
+    each_note(sessions, project_dir, |conn, session| {
+        let prompts: Vec<String> = serde_json::from_str(&session.user_prompts).unwrap_or_default();
+        db::crossrefs::set_cross_reference_prompt(conn, &session.id, prompts.first().map(|p| p.as_str()))
+    })
+}
+
+/// Apply `change` to the note of each session in every other project its
+/// modified files belong to, where that project has a database.
+fn each_note(
+    sessions: &[SessionRow],
+    project_dir: &Path,
+    change: impl Fn(&rusqlite::Connection, &SessionRow) -> anyhow::Result<bool>,
+) -> usize {
+    let mut by_project: BTreeMap<PathBuf, Vec<&SessionRow>> = BTreeMap::new();
+    for session in sessions {
+        let files: Vec<String> = serde_json::from_str(&session.files_modified).unwrap_or_default();
+        for other in other_projects(files.iter().map(|f| f.as_str()), project_dir).into_keys() {
+            by_project.entry(other).or_default().push(session);
+        }
+    }
+
+    let mut changed = 0;
+    for (other, sessions) in by_project {
+        let result = config::db_path(&other).and_then(|db_path| {
+            if !db_path.is_file() {
+                return Ok(0);
+            }
+            let conn = db::open(&db_path)?;
+            sessions
+                .iter()
+                .try_fold(0, |n, session| Ok(n + change(&conn, session)? as usize))
+        });
+        match result {
+            Ok(n) => changed += n,
+            Err(e) => eprintln!("claude-memory: cannot update the notes in {}: {}", other.display(), e),
+        }
+    }
+    changed
+}
+
+/// The modified files outside `project_dir`, by the project they belong to.
+fn other_projects<'a>(files: impl Iterator<Item = &'a str>, project_dir: &Path) -> BTreeMap<PathBuf, Vec<&'a str>> {
+    let mut by_project: BTreeMap<PathBuf, Vec<&str>> = BTreeMap::new();
+    for file in files {
+        let path = Path::new(file);
+        if !path.is_absolute() || path.starts_with(project_dir) {
+            continue;
+        }
+        let Some(parent) = path.parent() else { continue };
+        let other = config::find_project_root(parent);
+        if other != project_dir {
+            by_project.entry(other).or_default().push(file);
+        }
+    }
+    by_project
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::crossrefs::recent_cross_references;
+
+    fn project(root: &Path, name: &str) -> PathBuf {
+        let dir = root.join(name);
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn notes_follow_the_session_into_projects_with_a_database() {
+        let root = tempfile::tempdir().unwrap();
+        let app = project(root.path(), "app");
+        let lib = project(root.path(), "lib");
+        let bare = project(root.path(), "bare");
+        std::fs::create_dir_all(lib.join(".claude")).unwrap();
+        std::fs::write(config::settings_path(&lib), "[privacy]\nexclude_paths = [\"secret/**\"]\n").unwrap();
+        let lib_db = db::open(&config::db_path(&lib).unwrap()).unwrap();
+
+        let file = |dir: &Path, rel: &str| dir.join(rel).to_string_lossy().to_string();
+        let meta = SessionMetadata {
+            session_id: "s1".to_string(),
+            first_timestamp: Some("2026-03-01T10:00:00Z".to_string()),
+            user_prompts: vec!["bump the lexer with token sk-live-1234".to_string()],
+            files_modified: [
+                file(&app, "src/main.rs"),
+                file(&lib, "src/lexer.rs"),
+                file(&lib, "secret/keys.rs"),
+                file(&bare, "src/x.rs"),
+            ]
+            .into(),
+            ..Default::default()
+        };
+        assert_eq!(record(&meta, &app), 1);
+
+        // Excluded paths stay out, and no database is created for `bare`
+        let noted = recent_cross_references(&lib_db, 10, None, None).unwrap();
+        assert_eq!(noted.len(), 1);
+        assert_eq!(noted[0].files, serde_json::to_string(&[file(&lib, "src/lexer.rs")]).unwrap());
+        assert!(!config::db_path(&bare).unwrap().exists());
+
+        let mut session = SessionRow {
+            id: "s1".to_string(),
+            project_dir: app.to_string_lossy().to_string(),
+            git_branch: None,
+            started_at: "2026-03-01T10:00:00Z".to_string(),
+            ended_at: None,
+            duration_seconds: None,
+            model: None,
+            user_prompts: r#"["bump the lexer with token [REDACTED]"]"#.to_string(),
+            files_modified: serde_json::to_string(&meta.files_modified).unwrap(),
+            files_read: "[]".to_string(),
+            commands_run: "[]".to_string(),
+            git_commits: "[]".to_string(),
+            tools_used: "{}".to_string(),
+            input_tokens: 0,
+            output_tokens: 0,
+            summary: None,
+            transcript_path: None,
+            ingested_at: String::new(),
+            scope: None,
+        };
+        assert_eq!(update_prompts(std::slice::from_ref(&session), &app), 1);
+        let noted = recent_cross_references(&lib_db, 10, None, None).unwrap();
+        assert_eq!(noted[0].prompt.as_deref(), Some("bump the lexer with token [REDACTED]"));
+
+        session.user_prompts = "[]".to_string();
+        assert_eq!(forget(&[session], &app), 1);
+        assert!(recent_cross_references(&lib_db, 10, None, None).unwrap().is_empty());
+    }
+}
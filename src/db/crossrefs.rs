@@ -0,0 +1,178 @@
+use rusqlite::{params, Connection};
+
+/// A session of another project that edited files of this one. The session
+/// itself is stored in `source_project`'s memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossReference {
+    pub session_id: String,
+    pub source_project: String,
+    pub started_at: String,
+    /// The session's first prompt.
+    pub prompt: Option<String>,
+    /// JSON array of the files of this project it modified.
+    pub files: String,
+}
+
+const COLUMNS: &str = "session_id, source_project, started_at, prompt, files";
+
+impl CrossReference {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(CrossReference {
+            session_id: row.get(0)?,
+            source_project: row.get(1)?,
+            started_at: row.get(2)?,
+            prompt: row.get(3)?,
+            files: row.get(4)?,
+        })
+    }
+}
+
+/// Record (or, for a re-ingested session, replace) a cross-reference.
+pub fn record_cross_reference(conn: &Connection, xref: &CrossReference) -> anyhow::Result<()> {
+    conn.execute(
+        "INSERT INTO cross_references (session_id, source_project, started_at, prompt, files)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(session_id) DO UPDATE SET
+            source_project = excluded.source_project, started_at = excluded.started_at,
+            prompt = excluded.prompt, files = excluded.files",
+        params![xref.session_id, xref.source_project, xref.started_at, xref.prompt, xref.files],
+    )?;
+    Ok(())
+}
+
+/// Cross-references whose files or prompt contain any word of `query`,
+/// newest first. There is no full-text index: these are few and short.
+pub fn search_cross_references(conn: &Connection, query: &str, limit: usize) -> anyhow::Result<Vec<CrossReference>> {
+    let terms = terms(query);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut results = matching(conn, &terms, None, None)?;
+    results.truncate(limit);
+    Ok(results)
+}
+
+/// Every cross-reference matching `query` (if given) within a date range,
+/// newest first. Without a query, the date range selects.
+pub fn matching_cross_references(
+    conn: &Connection,
+    query: Option<&str>,
+    date_from: Option<&str>,
+    date_to: Option<&str>,
+) -> anyhow::Result<Vec<CrossReference>> {
+    let terms = query.map(terms).unwrap_or_default();
+    if query.is_some() && terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    matching(conn, &terms, date_from, date_to)
+}
+
+fn terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .filter(|t| !matches!(*t, "AND" | "OR" | "NOT" | "NEAR"))
+        .map(|t| t.trim_matches(|c: char| matches!(c, '"' | '(' | ')' | '*')).to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Cross-references in the date range containing any of `terms`, or all of them without terms.
+fn matching(
+    conn: &Connection,
+    terms: &[String],
+    date_from: Option<&str>,
+    date_to: Option<&str>,
+) -> anyhow::Result<Vec<CrossReference>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM cross_references
+         WHERE (?1 IS NULL OR started_at >= ?1)
+           AND (?2 IS NULL OR substr(started_at, 1, length(?2)) <= ?2)
+         ORDER BY started_at DESC",
+        COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![date_from, date_to], CrossReference::from_row)?;
+    let mut results = Vec::new();
+    for row in rows {
+        let xref = row?;
+        let text = format!("{} {}", xref.files, xref.prompt.as_deref().unwrap_or("")).to_lowercase();
+        if terms.is_empty() || terms.iter().any(|t| text.contains(t.as_str())) {
+            results.push(xref);
+        }
+    }
+    Ok(results)
+}
+
+/// The newest cross-references, optionally within a date range.
+pub fn recent_cross_references(
+    conn: &Connection,
+    limit: usize,
+    date_from: Option<&str>,
+    date_to: Option<&str>,
+) -> anyhow::Result<Vec<CrossReference>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM cross_references
+         WHERE (?1 IS NULL OR started_at >= ?1) AND (?2 IS NULL OR started_at <= ?2)
+         ORDER BY started_at DESC LIMIT ?3",
+        COLUMNS
+    ))?;
+    let rows = stmt
+        .query_map(params![date_from, date_to, limit as i64], CrossReference::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Replace the copied prompt, e.g. after the source session was redacted.
+pub fn set_cross_reference_prompt(conn: &Connection, session_id: &str, prompt: Option<&str>) -> anyhow::Result<bool> {
+    let changed = conn.execute(
+        "UPDATE cross_references SET prompt = ?2 WHERE session_id = ?1 AND prompt IS NOT ?2",
+        params![session_id, prompt],
+    )?;
+    Ok(changed > 0)
+}
+
+pub fn delete_cross_reference(conn: &Connection, session_id: &str) -> anyhow::Result<bool> {
+    let deleted = conn.execute("DELETE FROM cross_references WHERE session_id = ?1", params![session_id])?;
+    Ok(deleted > 0)
+}
+
+pub fn cross_reference_count(conn: &Connection) -> anyhow::Result<i64> {
+    let count = conn.query_row("SELECT COUNT(*) FROM cross_references", [], |row| row.get(0))?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xref(id: &str, started_at: &str, files: &[&str]) -> CrossReference {
+        CrossReference {
+            session_id: id.to_string(),
+            source_project: "/work/app".to_string(),
+            started_at: started_at.to_string(),
+            prompt: Some("bump the parser".to_string()),
+            files: serde_json::to_string(files).unwrap(),
+        }
+    }
+
+    #[test]
+    fn re_recording_replaces_and_search_matches_files_or_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = crate::db::open(&dir.path().join("memory.db")).unwrap();
+        record_cross_reference(&conn, &xref("a", "2026-01-01", &["/work/lib/src/lexer.rs"])).unwrap();
+        record_cross_reference(&conn, &xref("b", "2026-01-02", &["/work/lib/README.md"])).unwrap();
+        record_cross_reference(&conn, &xref("a", "2026-01-03", &["/work/lib/src/token.rs"])).unwrap();
+        assert_eq!(cross_reference_count(&conn).unwrap(), 2);
+
+        let ids = |refs: Vec<CrossReference>| refs.into_iter().map(|x| x.session_id).collect::<Vec<_>>();
+        assert_eq!(ids(search_cross_references(&conn, "token.rs", 10).unwrap()), ["a"]);
+        assert!(search_cross_references(&conn, "lexer", 10).unwrap().is_empty());
+        assert_eq!(ids(search_cross_references(&conn, "Parser", 10).unwrap()), ["a", "b"]);
+        assert_eq!(ids(recent_cross_references(&conn, 10, None, Some("2026-01-02")).unwrap()), ["b"]);
+        assert_eq!(ids(matching_cross_references(&conn, None, Some("2026-01-03"), None).unwrap()), ["a"]);
+
+        assert!(set_cross_reference_prompt(&conn, "a", Some("bump the [REDACTED]")).unwrap());
+        assert!(!set_cross_reference_prompt(&conn, "a", Some("bump the [REDACTED]")).unwrap());
+        assert!(delete_cross_reference(&conn, "b").unwrap());
+        assert_eq!(ids(search_cross_references(&conn, "Parser", 10).unwrap()), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,218 @@
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection};
+
+/// Environment variable holding the database key itself.
+pub const KEY_ENV: &str = "CLAUDE_MEMORY_KEY";
+/// Environment variable pointing at a key file, instead of `~/.claude/memory.key`.
+pub const KEY_FILE_ENV: &str = "CLAUDE_MEMORY_KEY_FILE";
+
+/// Whether this build can read and write encrypted (SQLCipher) databases.
+pub const SUPPORTED: bool = cfg!(feature = "encryption");
+
+/// Why an encrypted database could not be opened.
+#[derive(Debug, thiserror::Error)]
+pub enum KeyError {
+    #[error("{} is encrypted and no key is configured (set {KEY_ENV} or write the key to {})", .0.display(), default_key_file())]
+    Locked(PathBuf),
+    #[error("Wrong key for {}", .0.display())]
+    WrongKey(PathBuf),
+    #[error("{} is encrypted, but this build has no encryption support (rebuild with --features encryption)", .0.display())]
+    Unsupported(PathBuf),
+}
+
+/// What a database file on disk looks like, judged by its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileState {
+    /// Missing or empty; created on open.
+    New,
+    Plaintext,
+    Encrypted,
+}
+
+/// Plain SQLite files start with this; SQLCipher encrypts the whole first page.
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+pub fn file_state(path: &Path) -> FileState {
+    use std::io::Read;
+    let mut header = [0u8; 16];
+    let read = std::fs::File::open(path).and_then(|mut f| f.read(&mut header));
+    match read {
+        Err(_) | Ok(0) => FileState::New,
+        Ok(_) if &header == SQLITE_HEADER => FileState::Plaintext,
+        Ok(_) => FileState::Encrypted,
+    }
+}
+
+/// Fail with a hint to rebuild when encryption is compiled out.
+pub fn ensure_supported() -> anyhow::Result<()> {
+    anyhow::ensure!(SUPPORTED, "This build has no encryption support (rebuild with --features encryption)");
+    Ok(())
+}
+
+/// Where the key was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+    Env,
+    File(PathBuf),
+}
+
+/// The configured database key. One key serves every project of the user.
+pub struct Key {
+    secret: String,
+    pub source: KeySource,
+}
+
+#[cfg(all(test, feature = "encryption"))]
+impl Key {
+    pub(crate) fn for_tests(secret: &str) -> Self {
+        Key { secret: secret.to_string(), source: KeySource::Env }
+    }
+}
+
+/// `$CLAUDE_MEMORY_KEY_FILE`, or `~/.claude/memory.key`.
+pub fn key_file_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(KEY_FILE_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".claude").join("memory.key"))
+}
+
+fn default_key_file() -> String {
+    key_file_path().map_or_else(|| "~/.claude/memory.key".to_string(), |p| p.display().to_string())
+}
+
+/// The key from `$CLAUDE_MEMORY_KEY`, else from the key file if it exists.
+pub fn configured_key() -> anyhow::Result<Option<Key>> {
+    if let Ok(secret) = std::env::var(KEY_ENV) {
+        if !secret.is_empty() {
+            return Ok(Some(Key { secret, source: KeySource::Env }));
+        }
+    }
+    let path = match key_file_path() {
+        Some(p) if p.is_file() => p,
+        _ => return Ok(None),
+    };
+    let secret = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("Cannot read key file {}: {}", path.display(), e))?
+        .trim_end_matches(['\r', '\n'])
+        .to_string();
+    anyhow::ensure!(!secret.is_empty(), "Key file {} is empty", path.display());
+    Ok(Some(Key { secret, source: KeySource::File(path) }))
+}
+
+/// Key a just-opened connection as its file requires: an encrypted file needs
+/// the configured key, a new file is encrypted if a key is configured, and a
+/// plaintext file is left as it is.
+pub(crate) fn unlock(conn: &Connection, path: &Path, state: FileState) -> anyhow::Result<()> {
+    match state {
+        FileState::Plaintext => Ok(()),
+        _ => unlock_with(conn, path, state, configured_key()?.as_ref()),
+    }
+}
+
+pub(crate) fn unlock_with(conn: &Connection, path: &Path, state: FileState, key: Option<&Key>) -> anyhow::Result<()> {
+    match state {
+        FileState::Plaintext => Ok(()),
+        FileState::New => match key {
+            Some(key) if SUPPORTED => apply_key(conn, &key.secret),
+            _ => Ok(()),
+        },
+        FileState::Encrypted => {
+            if !SUPPORTED {
+                return Err(KeyError::Unsupported(path.to_path_buf()).into());
+            }
+            let key = key.ok_or_else(|| KeyError::Locked(path.to_path_buf()))?;
+            apply_key(conn, &key.secret)?;
+            // A wrong key only shows on the first read
+            conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(()))
+                .map_err(|_| KeyError::WrongKey(path.to_path_buf()))?;
+            Ok(())
+        }
+    }
+}
+
+/// Give `target`, a connection to a new file, the same encryption as the
+/// database at `source`, so pages can be copied between them.
+pub(crate) fn match_encryption(target: &Connection, source: &Path) -> anyhow::Result<()> {
+    if file_state(source) != FileState::Encrypted {
+        return Ok(());
+    }
+    let key = configured_key()?.ok_or_else(|| KeyError::Locked(source.to_path_buf()))?;
+    apply_key(target, &key.secret)
+}
+
+fn apply_key(conn: &Connection, secret: &str) -> anyhow::Result<()> {
+    // Depending on the SQLCipher version this answers with a row or not
+    let mut stmt = conn.prepare(&format!("PRAGMA key = '{}'", secret.replace('\'', "''")))?;
+    stmt.query([])?.next()?;
+    // Otherwise SQLCipher logs a wrong key to stderr itself, and the level is
+    // only settable once keying has set up its defaults
+    conn.prepare("PRAGMA cipher_log_level = NONE")?.query([])?.next()?;
+    Ok(())
+}
+
+/// Write the database behind `conn` to a new file at `dest`: encrypted with
+/// `key`, or in plaintext without one.
+pub fn export(conn: &Connection, dest: &Path, key: Option<&Key>) -> anyhow::Result<()> {
+    ensure_supported()?;
+    let secret = key.map_or("", |k| k.secret.as_str());
+    conn.execute(
+        "ATTACH DATABASE ?1 AS export KEY ?2",
+        params![dest.to_string_lossy(), secret],
+    )?;
+    let exported = conn.query_row("SELECT sqlcipher_export('export')", [], |_| Ok(()));
+    conn.execute("DETACH DATABASE export", [])?;
+    exported?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_state_reads_the_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memory.db");
+        assert_eq!(file_state(&path), FileState::New);
+
+        Connection::open(&path).unwrap().execute_batch("CREATE TABLE t (x)").unwrap();
+        assert_eq!(file_state(&path), FileState::Plaintext);
+
+        std::fs::write(&path, [0x8au8; 64]).unwrap();
+        assert_eq!(file_state(&path), FileState::Encrypted);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypted_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let (plain, secret, back) = (dir.path().join("a.db"), dir.path().join("b.db"), dir.path().join("c.db"));
+        let key = |s: &str| Key { secret: s.to_string(), source: KeySource::Env };
+        let notes = |conn: &Connection| crate::db::notes::search_notes(conn, Some("launch"), None, 5).unwrap().len();
+
+        let conn = Connection::open(&plain).unwrap();
+        crate::db::schema::ensure_schema(&conn).unwrap();
+        crate::db::notes::insert_note(&conn, "launch codes", &[], None).unwrap();
+        export(&conn, &secret, Some(&key("correct horse"))).unwrap();
+        assert_eq!(file_state(&secret), FileState::Encrypted);
+        assert!(!std::fs::read(&secret).unwrap().windows(6).any(|w| w == b"launch"));
+
+        let open = |k: Option<&Key>| {
+            let conn = Connection::open(&secret).unwrap();
+            unlock_with(&conn, &secret, FileState::Encrypted, k).map(|_| conn)
+        };
+        let err = open(Some(&key("wrong"))).unwrap_err();
+        assert!(matches!(err.downcast_ref::<KeyError>(), Some(KeyError::WrongKey(_))));
+        let err = open(None).unwrap_err();
+        assert!(matches!(err.downcast_ref::<KeyError>(), Some(KeyError::Locked(_))));
+
+        let unlocked = open(Some(&key("correct horse"))).unwrap();
+        assert_eq!(notes(&unlocked), 1);
+        export(&unlocked, &back, None).unwrap();
+        assert_eq!(file_state(&back), FileState::Plaintext);
+        assert_eq!(notes(&Connection::open(&back).unwrap()), 1);
+    }
+}
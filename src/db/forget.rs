@@ -0,0 +1,320 @@
+use rusqlite::Connection;
+
+use super::crossrefs::{self, CrossReference};
+use super::notes::{self, NoteRow};
+use super::sessions::{self, SessionRow};
+use crate::text::one_line;
+
+/// Rows listed individually in a preview; the rest are only counted.
+const PREVIEW_LIMIT: usize = 20;
+
+/// Which kind of row a query or date range selects. IDs always select
+/// their own kind.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Scope {
+    /// Sessions and notes
+    #[default]
+    All,
+    /// Sessions only
+    Sessions,
+    /// Notes only
+    Notes,
+}
+
+/// What to forget: explicit IDs (or unique prefixes), plus everything matching
+/// a query within a date range. The query and dates combine; either alone is
+/// enough.
+#[derive(Debug, Default)]
+pub struct Selector {
+    pub session_ids: Vec<String>,
+    pub note_ids: Vec<String>,
+    pub query: Option<String>,
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub scope: Scope,
+    /// Also delete the notes attached to selected sessions, instead of
+    /// unlinking them.
+    pub with_notes: bool,
+}
+
+impl Selector {
+    fn has_filter(&self) -> bool {
+        self.query.is_some() || self.date_from.is_some() || self.date_to.is_some()
+    }
+}
+
+/// The rows a `Selector` resolved to.
+#[derive(Debug, Default)]
+pub struct Selection {
+    pub sessions: Vec<SessionRow>,
+    pub notes: Vec<NoteRow>,
+    /// Other projects' sessions noted here, selected like sessions by a
+    /// query or date range.
+    pub cross_references: Vec<CrossReference>,
+    /// Notes not selected themselves but attached to a selected session.
+    pub attached_notes: Vec<String>,
+    pub with_notes: bool,
+}
+
+impl Selection {
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty() && self.notes.is_empty() && self.cross_references.is_empty()
+    }
+
+    /// A listing of what `forget` would do, for confirmation.
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        if !self.sessions.is_empty() {
+            out.push_str(&format!("{} session(s):\n", self.sessions.len()));
+            for s in self.sessions.iter().take(PREVIEW_LIMIT) {
+                let prompts: Vec<String> = serde_json::from_str(&s.user_prompts).unwrap_or_default();
+                let first = prompts.first().map_or("", |p| p.as_str());
+                out.push_str(&format!("  {}  {}  {}\n", short(&s.id), short_date(&s.started_at), one_line(first, 60)));
+            }
+            more(&mut out, self.sessions.len());
+        }
+        if !self.notes.is_empty() {
+            out.push_str(&format!("{} note(s):\n", self.notes.len()));
+            for n in self.notes.iter().take(PREVIEW_LIMIT) {
+                out.push_str(&format!("  {}  {}  {}\n", short(&n.id), short_date(&n.created_at), one_line(&n.content, 60)));
+            }
+            more(&mut out, self.notes.len());
+        }
+        if !self.cross_references.is_empty() {
+            out.push_str(&format!("{} session(s) of other projects noted here:\n", self.cross_references.len()));
+            for x in self.cross_references.iter().take(PREVIEW_LIMIT) {
+                let prompt = x.prompt.as_deref().unwrap_or("");
+                out.push_str(&format!("  {}  {}  {}\n", short(&x.session_id), short_date(&x.started_at), one_line(prompt, 60)));
+            }
+            more(&mut out, self.cross_references.len());
+        }
+        if !self.attached_notes.is_empty() {
+            let action = if self.with_notes { "deleted too" } else { "kept, unlinked from the session" };
+            out.push_str(&format!(
+                "{} other note(s) attached to these sessions will be {}.\n",
+                self.attached_notes.len(),
+                action
+            ));
+        }
+        out
+    }
+}
+
+/// Resolve a selector to the rows it covers, without changing anything.
+pub fn select(conn: &Connection, selector: &Selector) -> anyhow::Result<Selection> {
+    anyhow::ensure!(
+        !selector.session_ids.is_empty() || !selector.note_ids.is_empty() || selector.has_filter(),
+        "Nothing to forget: give session or note IDs, a query, or a date range"
+    );
+
+    let mut selected_sessions = Vec::new();
+    for prefix in &selector.session_ids {
+        let id = sessions::resolve_session_id(conn, prefix)?;
+        selected_sessions.extend(sessions::get_session(conn, &id)?);
+    }
+    let mut selected_notes = Vec::new();
+    let mut cross_references = Vec::new();
+    for prefix in &selector.note_ids {
+        let id = notes::resolve_note_id(conn, prefix)?;
+        selected_notes.extend(notes::get_note(conn, &id)?);
+    }
+
+    if selector.has_filter() {
+        let (query, from, to) = (
+            selector.query.as_deref(),
+            selector.date_from.as_deref(),
+            selector.date_to.as_deref(),
+        );
+        if selector.scope != Scope::Notes {
+            selected_sessions.extend(sessions::matching_sessions(conn, query, from, to)?);
+            cross_references = crossrefs::matching_cross_references(conn, query, from, to)?;
+        }
+        if selector.scope != Scope::Sessions {
+            selected_notes.extend(notes::matching_notes(conn, query, from, to)?);
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    selected_sessions.retain(|s| seen.insert(s.id.clone()));
+    selected_notes.retain(|n| seen.insert(n.id.clone()));
+
+    let mut attached_notes = Vec::new();
+    for session in &selected_sessions {
+        for id in notes::session_note_ids(conn, &session.id)? {
+            if seen.insert(id.clone()) {
+                attached_notes.push(id);
+            }
+        }
+    }
+
+    Ok(Selection {
+        sessions: selected_sessions,
+        notes: selected_notes,
+        cross_references,
+        attached_notes,
+        with_notes: selector.with_notes,
+    })
+}
+
+/// What `forget` deleted.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Forgotten {
+    pub sessions: usize,
+    pub notes: usize,
+    pub cross_references: usize,
+}
+
+impl std::fmt::Display for Forgotten {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Forgot {} session(s) and {} note(s)", self.sessions, self.notes)?;
+        if self.cross_references > 0 {
+            write!(f, ", and the notice of {} session(s) of other projects", self.cross_references)?;
+        }
+        write!(f, ".")
+    }
+}
+
+/// Delete a selection in one transaction.
+///
+/// Runs with `secure_delete` so freed pages are zeroed, and optimizes the
+/// search indexes afterwards so the forgotten terms are merged out of them.
+pub fn forget(conn: &Connection, selection: &Selection) -> anyhow::Result<Forgotten> {
+    let secure: bool = conn.pragma_query_value(None, "secure_delete", |row| row.get(0))?;
+    // Setting this pragma echoes the new value back as a row
+    conn.pragma_update_and_check(None, "secure_delete", true, |_| Ok(()))?;
+    let result = delete(conn, selection);
+    conn.pragma_update_and_check(None, "secure_delete", secure, |_| Ok(()))?;
+    let counts = result?;
+
+    conn.execute_batch(
+        "INSERT INTO sessions_fts(sessions_fts) VALUES('optimize');
+         INSERT INTO notes_fts(notes_fts) VALUES('optimize');",
+    )?;
+    Ok(counts)
+}
+
+fn delete(conn: &Connection, selection: &Selection) -> anyhow::Result<Forgotten> {
+    let tx = conn.unchecked_transaction()?;
+    let mut forgotten = Forgotten::default();
+    let attached = if selection.with_notes { selection.attached_notes.as_slice() } else { &[] };
+    for id in selection.notes.iter().map(|n| &n.id).chain(attached) {
+        forgotten.notes += notes::delete_note(&tx, id)? as usize;
+    }
+    for session in &selection.sessions {
+        forgotten.sessions += sessions::delete_session(&tx, &session.id)? as usize;
+    }
+    for xref in &selection.cross_references {
+        forgotten.cross_references += crossrefs::delete_cross_reference(&tx, &xref.session_id)? as usize;
+    }
+    tx.commit()?;
+    Ok(forgotten)
+}
+
+fn short(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
+fn short_date(timestamp: &str) -> &str {
+    &timestamp[..10.min(timestamp.len())]
+}
+
+fn more(out: &mut String, total: usize) {
+    if total > PREVIEW_LIMIT {
+        out.push_str(&format!("  ... and {} more\n", total - PREVIEW_LIMIT));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded_db(dir: &std::path::Path) -> Connection {
+        let conn = crate::db::open(&dir.join("memory.db")).unwrap();
+        for (id, started, prompt) in [
+            ("s1", "2026-01-10T10:00:00Z", "set up the payment webhook"),
+            ("s2", "2026-02-10T10:00:00Z", "refactor the parser"),
+            ("s3", "2026-02-20T10:00:00Z", "debug payment retries"),
+        ] {
+            conn.execute(
+                "INSERT INTO sessions (id, project_dir, started_at, user_prompts) VALUES (?1, '/p', ?2, json_array(?3))",
+                [id, started, prompt],
+            )
+            .unwrap();
+        }
+        notes::insert_note(&conn, "payment provider keys live in vault", &[], Some("s1")).unwrap();
+        notes::insert_note(&conn, "parser uses a hand-written lexer", &[], Some("s2")).unwrap();
+        conn
+    }
+
+    fn ids(selection: &Selection) -> Vec<&str> {
+        selection.sessions.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    #[test]
+    fn query_and_dates_combine() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = seeded_db(dir.path());
+
+        let by_query = Selector { query: Some("payment".into()), ..Default::default() };
+        let selection = select(&conn, &by_query).unwrap();
+        assert_eq!(ids(&selection), ["s1", "s3"]);
+        assert_eq!(selection.notes.len(), 1);
+        assert!(selection.attached_notes.is_empty());
+
+        let in_february = Selector {
+            query: Some("payment".into()),
+            date_from: Some("2026-02-01".into()),
+            date_to: Some("2026-02-20".into()),
+            scope: Scope::Sessions,
+            ..Default::default()
+        };
+        let selection = select(&conn, &in_february).unwrap();
+        assert_eq!(ids(&selection), ["s3"]);
+        assert!(selection.notes.is_empty());
+
+        crossrefs::record_cross_reference(
+            &conn,
+            &CrossReference {
+                session_id: "x1".into(),
+                source_project: "/other".into(),
+                started_at: "2026-02-12T10:00:00Z".into(),
+                prompt: Some("wire the payment client".into()),
+                files: "[\"/p/client.rs\"]".into(),
+            },
+        )
+        .unwrap();
+        let selection = select(&conn, &in_february).unwrap();
+        assert_eq!(selection.cross_references.len(), 1);
+        assert_eq!(forget(&conn, &selection).unwrap().cross_references, 1);
+        assert_eq!(crossrefs::cross_reference_count(&conn).unwrap(), 0);
+
+        assert!(select(&conn, &Selector::default()).is_err());
+        let missing = Selector { session_ids: vec!["zz".into()], ..Default::default() };
+        assert!(select(&conn, &missing).is_err());
+    }
+
+    #[test]
+    fn forget_unlinks_or_deletes_attached_notes_and_clears_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = seeded_db(dir.path());
+
+        let selector = Selector { session_ids: vec!["s2".into()], ..Default::default() };
+        let selection = select(&conn, &selector).unwrap();
+        assert_eq!(selection.attached_notes.len(), 1);
+        assert!(selection.describe().contains("kept, unlinked"));
+        let forgotten = forget(&conn, &selection).unwrap();
+        assert_eq!((forgotten.sessions, forgotten.notes), (1, 0));
+        assert!(sessions::get_session(&conn, "s2").unwrap().is_none());
+        assert!(sessions::search_sessions(&conn, "parser", 10).unwrap().0.is_empty());
+        let kept = notes::search_notes(&conn, Some("lexer"), None, 10).unwrap();
+        assert_eq!(kept[0].session_id, None);
+
+        let selector = Selector { session_ids: vec!["s1".into()], with_notes: true, ..Default::default() };
+        let selection = select(&conn, &selector).unwrap();
+        let forgotten = forget(&conn, &selection).unwrap();
+        assert_eq!((forgotten.sessions, forgotten.notes), (1, 1));
+        assert!(notes::search_notes(&conn, Some("vault"), None, 10).unwrap().is_empty());
+        let secure: bool = conn.pragma_query_value(None, "secure_delete", |row| row.get(0)).unwrap();
+        assert!(!secure);
+    }
+}
@@ -1,10 +1,19 @@
+pub mod crossrefs;
+pub mod encryption;
+pub mod forget;
 pub mod notes;
+pub mod project;
 pub mod schema;
 pub mod sessions;
 
 use std::path::Path;
+use std::time::Duration;
 
-use rusqlite::Connection;
+use rusqlite::{params, Connection};
+
+/// How long to wait for another process (e.g. a `SessionEnd` ingest) to release
+/// the write lock before failing with `SQLITE_BUSY`.
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Sanitize a user query for safe FTS5 MATCH usage.
 ///
@@ -53,26 +62,254 @@ pub fn build_or_fallback(sanitized_query: &str) -> Option<String> {
     Some(terms.join(" OR "))
 }
 
+/// Failure to turn a (possibly abbreviated) ID into a single row.
+#[derive(Debug, thiserror::Error)]
+pub enum LookupError {
+    #[error("No {kind} found matching: {prefix}")]
+    NotFound { kind: &'static str, prefix: String },
+    #[error("Ambiguous {kind} ID prefix '{prefix}' matches:\n{}", candidates.iter().map(|c| format!("  {}", c)).collect::<Vec<_>>().join("\n"))]
+    Ambiguous {
+        kind: &'static str,
+        prefix: String,
+        candidates: Vec<String>,
+    },
+}
+
+/// What to do when an imported row's ID already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OnConflict {
+    /// Keep the existing row
+    Skip,
+    /// Replace the existing row
+    Overwrite,
+    /// Keep whichever row was ingested (notes: created) most recently
+    KeepNewest,
+}
+
+/// Result of importing one row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportOutcome {
+    Added,
+    Updated,
+    Skipped,
+}
+
+/// Whether an imported row should be written, given the existing row's
+/// timestamp (if any) and the imported row's.
+fn should_import(existing: Option<&str>, imported: &str, on_conflict: OnConflict) -> Option<ImportOutcome> {
+    match (existing, on_conflict) {
+        (None, _) => Some(ImportOutcome::Added),
+        (Some(_), OnConflict::Skip) => None,
+        (Some(current), OnConflict::KeepNewest) if imported <= current => None,
+        (Some(_), _) => Some(ImportOutcome::Updated),
+    }
+}
+
+/// Rebuild both full-text indexes from their content tables.
+pub fn rebuild_fts(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        "
+        INSERT INTO sessions_fts(sessions_fts) VALUES('rebuild');
+        INSERT INTO notes_fts(notes_fts) VALUES('rebuild');
+        ",
+    )?;
+    Ok(())
+}
+
+/// Most candidates listed in an ambiguity error.
+const MAX_CANDIDATES: usize = 10;
+
+/// Resolve an ID or unique ID prefix in `table` to the full ID.
+/// An exact match always wins, even if it is also a prefix of other IDs.
+fn resolve_id_prefix(
+    conn: &Connection,
+    table: &str,
+    kind: &'static str,
+    prefix: &str,
+) -> anyhow::Result<String> {
+    let not_found = || LookupError::NotFound {
+        kind,
+        prefix: prefix.to_string(),
+    };
+    if prefix.is_empty() {
+        return Err(not_found().into());
+    }
+
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT id FROM {table} WHERE id LIKE ?2 ESCAPE '\\'
+         ORDER BY id = ?1 DESC, id LIMIT ?3"
+    ))?;
+    let ids = stmt
+        .query_map(
+            params![prefix, like_prefix(prefix), MAX_CANDIDATES as i64 + 1],
+            |row| row.get::<_, String>(0),
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match ids.as_slice() {
+        [] => Err(not_found().into()),
+        [only] => Ok(only.clone()),
+        [first, ..] if first == prefix => Ok(first.clone()),
+        _ => {
+            let mut candidates = ids;
+            if candidates.len() > MAX_CANDIDATES {
+                candidates.truncate(MAX_CANDIDATES);
+                candidates.push("...".to_string());
+            }
+            Err(LookupError::Ambiguous {
+                kind,
+                prefix: prefix.to_string(),
+                candidates,
+            }
+            .into())
+        }
+    }
+}
+
+/// Build a `LIKE` pattern matching strings that start with `prefix`.
+fn like_prefix(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Bytes of the database file in use by pages that hold data (excluding free pages).
+pub fn used_bytes(conn: &Connection) -> anyhow::Result<u64> {
+    let (pages, free, page_size): (i64, i64, i64) = conn.query_row(
+        "SELECT p.page_count, f.freelist_count, s.page_size
+         FROM pragma_page_count() p, pragma_freelist_count() f, pragma_page_size() s",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    Ok(((pages - free) * page_size) as u64)
+}
+
+/// Merge FTS index segments and return free pages to the filesystem. The
+/// checkpoint moves the vacuumed pages out of the WAL so the file shrinks now.
+pub fn optimize_and_vacuum(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        "
+        INSERT INTO sessions_fts(sessions_fts) VALUES('optimize');
+        INSERT INTO notes_fts(notes_fts) VALUES('optimize');
+        VACUUM;
+        ",
+    )?;
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    Ok(())
+}
+
+/// Copy the database behind `conn` to `dest` with SQLite's online backup API.
+/// Safe while other connections (e.g. the MCP server) are reading or writing.
+/// The copy is encrypted when the original is.
+pub fn backup_to(conn: &Connection, dest: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut target = Connection::open(dest)?;
+    if let Some(source) = conn.path().filter(|p| !p.is_empty()) {
+        encryption::match_encryption(&target, Path::new(source))?;
+    }
+    copy_pages(conn, &mut target)
+}
+
+/// Copy every page of `from` into `to` with the online backup API.
+pub(crate) fn copy_pages(from: &Connection, to: &mut Connection) -> anyhow::Result<()> {
+    rusqlite::backup::Backup::new(from, to)?.run_to_completion(100, Duration::ZERO, None)?;
+    Ok(())
+}
+
 /// Open an existing memory database in read-only mode.
 /// Does not create directories or run migrations.
-/// Used for cross-project discovery.
+/// Used for cross-project discovery. An encrypted database needs the
+/// configured key and fails with an [`encryption::KeyError`] otherwise.
 pub fn open_readonly(db_path: &Path) -> anyhow::Result<Connection> {
+    let state = encryption::file_state(db_path);
     let conn = Connection::open_with_flags(
         db_path,
         rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
     )?;
+    encryption::unlock(&conn, db_path, state)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
     Ok(conn)
 }
 
+/// A read-only connection that sees the current schema. See [`open_current`].
+pub struct CurrentSchema {
+    conn: Connection,
+    /// Holds the migrated copy of an older database; dropped after `conn`.
+    _copy: Option<tempfile::TempDir>,
+}
+
+impl std::ops::Deref for CurrentSchema {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+/// Open an existing database for reading with the columns and tables current
+/// queries expect, without ever writing to it. A database at an older schema
+/// is copied to a temporary file and the copy is migrated instead.
+pub fn open_current(db_path: &Path) -> anyhow::Result<CurrentSchema> {
+    let conn = open_readonly(db_path)?;
+    if schema::get_schema_version(&conn)? >= schema::CURRENT_VERSION {
+        return Ok(CurrentSchema { conn, _copy: None });
+    }
+    let dir = tempfile::tempdir()?;
+    let copy = dir.path().join("memory.db");
+    backup_to(&conn, &copy)?;
+    drop(conn);
+    Ok(CurrentSchema { conn: open(&copy)?, _copy: Some(dir) })
+}
+
 /// Open (or create) the memory database at the given path.
-/// Enables WAL mode and creates schema if needed.
+/// Enables WAL mode and creates schema if needed. A new database is encrypted
+/// when a key is configured.
 pub fn open(db_path: &Path) -> anyhow::Result<Connection> {
+    let conn = open_unmigrated(db_path)?;
+    schema::ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+/// Open the user's global memory at `path`, which holds notes for every
+/// project. `None` when it doesn't exist yet.
+pub fn open_global(path: &Path) -> anyhow::Result<Option<Connection>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    Ok(Some(open(path)?))
+}
+
+/// Like [`open`], but leaves the schema as it is (for `claude-memory migrate`).
+pub fn open_unmigrated(db_path: &Path) -> anyhow::Result<Connection> {
     // Ensure parent directory exists
     if let Some(parent) = db_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
 
+    let state = encryption::file_state(db_path);
     let conn = Connection::open(db_path)?;
+    encryption::unlock(&conn, db_path, state)?;
+    configure(conn)
+}
+
+/// [`open_unmigrated`] with an explicit key instead of the configured one.
+pub(crate) fn open_unmigrated_with(db_path: &Path, key: Option<&encryption::Key>) -> anyhow::Result<Connection> {
+    let state = encryption::file_state(db_path);
+    let conn = Connection::open(db_path)?;
+    encryption::unlock_with(&conn, db_path, state, key)?;
+    configure(conn)
+}
+
+fn configure(conn: Connection) -> anyhow::Result<Connection> {
+    conn.busy_timeout(BUSY_TIMEOUT)?;
 
     // journal_mode returns a result row
     let mut stmt = conn.prepare("PRAGMA journal_mode=WAL")?;
@@ -84,7 +321,6 @@ pub fn open(db_path: &Path) -> anyhow::Result<Connection> {
     let _ = stmt.raw_execute();
     drop(stmt);
 
-    schema::ensure_schema(&conn)?;
     Ok(conn)
 }
 
@@ -164,14 +400,14 @@ mod tests {
     }
 
     #[test]
-    fn schema_version_starts_at_one() {
+    fn schema_version_is_current() {
         let dir = tempfile::tempdir().unwrap();
         let db_path = dir.path().join("memory.db");
         let conn = open(&db_path).unwrap();
         let version: i64 = conn
             .query_row("SELECT version FROM schema_version", [], |row| row.get(0))
             .unwrap();
-        assert_eq!(version, 1);
+        assert_eq!(version, schema::CURRENT_VERSION);
     }
 
     #[test]
@@ -186,7 +422,127 @@ mod tests {
         let version: i64 = conn
             .query_row("SELECT version FROM schema_version", [], |row| row.get(0))
             .unwrap();
-        assert_eq!(version, 1);
+        assert_eq!(version, schema::CURRENT_VERSION);
+    }
+
+    #[test]
+    fn v1_database_gains_transcript_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("memory.db");
+        {
+            let conn = open(&db_path).unwrap();
+            conn.execute_batch(
+                "DROP INDEX idx_sessions_scope;
+                 ALTER TABLE sessions DROP COLUMN scope;
+                 ALTER TABLE sessions DROP COLUMN transcript_path;
+                 ALTER TABLE notes DROP COLUMN pinned;
+                 UPDATE schema_version SET version = 1;
+                 INSERT INTO sessions (id, project_dir, started_at) VALUES ('old', '/p', '2025-01-01');",
+            ).unwrap();
+        }
+
+        let conn = open(&db_path).unwrap();
+        let session = sessions::get_session(&conn, "old").unwrap().unwrap();
+        assert_eq!(session.transcript_path, None);
+        assert_eq!(notes::search_notes(&conn, None, None, 10).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn destructive_migration_snapshots_existing_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("memory.db");
+        {
+            // A pre-versioning (v0) database with data
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE sessions (id TEXT PRIMARY KEY, project_dir TEXT NOT NULL,
+                    git_branch TEXT, started_at TEXT NOT NULL, ended_at TEXT,
+                    duration_seconds INTEGER, model TEXT,
+                    user_prompts TEXT NOT NULL DEFAULT '[]', files_modified TEXT NOT NULL DEFAULT '[]',
+                    files_read TEXT NOT NULL DEFAULT '[]', commands_run TEXT NOT NULL DEFAULT '[]',
+                    git_commits TEXT NOT NULL DEFAULT '[]', tools_used TEXT NOT NULL DEFAULT '{}',
+                    input_tokens INTEGER DEFAULT 0, output_tokens INTEGER DEFAULT 0, summary TEXT,
+                    ingested_at TEXT NOT NULL DEFAULT (datetime('now')));
+                 INSERT INTO sessions (id, project_dir, started_at, user_prompts)
+                 VALUES ('old', '/p', '2025-01-01', '[\"legacy layers\"]');",
+            ).unwrap();
+        }
+
+        let conn = open(&db_path).unwrap();
+        assert_eq!(sessions::search_sessions(&conn, "layer", 5).unwrap().0.len(), 1);
+
+        let snapshots: Vec<_> = std::fs::read_dir(dir.path().join("memory-backups"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(snapshots.len(), 1);
+        assert!(snapshots[0].starts_with("memory-pre-migration-v0-"));
+        let snapshot = open_readonly(&dir.path().join("memory-backups").join(&snapshots[0])).unwrap();
+        assert_eq!(schema::get_schema_version(&snapshot).unwrap(), 0);
+    }
+
+    #[test]
+    fn fresh_database_is_not_snapshotted() {
+        let dir = tempfile::tempdir().unwrap();
+        open(&dir.path().join("memory.db")).unwrap();
+        assert!(!dir.path().join("memory-backups").exists());
+    }
+
+    #[test]
+    fn concurrent_opens_of_old_database_migrate_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("memory.db");
+        {
+            let conn = open(&db_path).unwrap();
+            conn.execute_batch(
+                "DROP INDEX idx_sessions_scope;
+                 ALTER TABLE sessions DROP COLUMN scope;
+                 ALTER TABLE notes DROP COLUMN pinned;
+                 UPDATE schema_version SET version = 2;",
+            ).unwrap();
+        }
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let path = db_path.clone();
+                std::thread::spawn(move || open(&path).map(|_| ()).map_err(|e| e.to_string()))
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Ok(()));
+        }
+        let conn = open(&db_path).unwrap();
+        assert_eq!(schema::get_schema_version(&conn).unwrap(), schema::CURRENT_VERSION);
+    }
+
+    #[test]
+    fn newer_database_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("memory.db");
+        open(&db_path).unwrap()
+            .execute("UPDATE schema_version SET version = ?", [schema::CURRENT_VERSION + 1])
+            .unwrap();
+
+        let err = open(&db_path).unwrap_err();
+        assert!(err.downcast_ref::<schema::NewerSchemaError>().is_some());
+        assert!(err.to_string().contains("Upgrade claude-memory"));
+    }
+
+    #[test]
+    fn failed_migration_rolls_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("memory.db");
+        {
+            let conn = open(&db_path).unwrap();
+            // v1 → v2 adds transcript_path, which already exists: the step fails
+            conn.execute("UPDATE schema_version SET version = 1", []).unwrap();
+        }
+
+        assert!(open(&db_path).is_err());
+        let conn = open_unmigrated(&db_path).unwrap();
+        assert_eq!(schema::get_schema_version(&conn).unwrap(), 1);
+        assert_eq!(schema::migrate(&conn, 1).unwrap(), 1);
+        assert!(schema::migrate(&conn, 0).is_err());
     }
 
     #[test]
@@ -248,6 +604,69 @@ mod tests {
         assert_eq!(results.len(), 1);
     }
 
+    #[test]
+    fn id_prefix_resolves_unique_match() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open(&dir.path().join("memory.db")).unwrap();
+        for id in ["abc123", "abd456", "ab"] {
+            conn.execute(
+                "INSERT INTO sessions (id, project_dir, started_at) VALUES (?, '/test', '2025-01-01')",
+                [id],
+            ).unwrap();
+        }
+
+        assert_eq!(sessions::resolve_session_id(&conn, "abc").unwrap(), "abc123");
+        // Exact match wins over longer IDs sharing the prefix
+        assert_eq!(sessions::resolve_session_id(&conn, "ab").unwrap(), "ab");
+
+        let err = sessions::resolve_session_id(&conn, "a").unwrap_err();
+        match err.downcast_ref::<LookupError>() {
+            Some(LookupError::Ambiguous { candidates, .. }) => assert_eq!(candidates.len(), 3),
+            other => panic!("expected ambiguity, got {:?}", other),
+        }
+        assert!(err.to_string().contains("abd456"));
+
+        let err = sessions::resolve_session_id(&conn, "zz").unwrap_err();
+        assert!(matches!(err.downcast_ref::<LookupError>(), Some(LookupError::NotFound { .. })));
+    }
+
+    #[test]
+    fn id_prefix_treats_like_wildcards_literally() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open(&dir.path().join("memory.db")).unwrap();
+        conn.execute(
+            "INSERT INTO notes (id, content) VALUES ('a_b', 'x'), ('axb', 'y')",
+            [],
+        ).unwrap();
+
+        assert_eq!(notes::resolve_note_id(&conn, "a_").unwrap(), "a_b");
+    }
+
+    #[test]
+    fn note_edits_and_deletes_stay_searchable() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open(&dir.path().join("memory.db")).unwrap();
+        let tags = vec!["decision".to_string()];
+        let id = notes::insert_note(&conn, "use postgres for billing", &tags, None).unwrap();
+        notes::insert_note(&conn, "postgres upgrade scheduled", &[], None).unwrap();
+
+        // Query and tag filters combine
+        let results = notes::search_notes(&conn, Some("postgres"), Some("decision"), 10).unwrap();
+        assert_eq!(results.len(), 1);
+
+        assert!(notes::update_note(&conn, &id, "use sqlite for billing", &tags).unwrap());
+        assert!(notes::search_notes(&conn, Some("postgres"), Some("decision"), 10).unwrap().is_empty());
+        assert_eq!(notes::search_notes(&conn, Some("sqlite"), None, 10).unwrap().len(), 1);
+
+        let other = notes::search_notes(&conn, None, None, 10).unwrap()[1].id.clone();
+        assert!(notes::set_note_pinned(&conn, &other, true).unwrap());
+        assert_eq!(notes::search_notes(&conn, None, None, 10).unwrap()[0].id, other);
+
+        assert!(notes::delete_note(&conn, &id).unwrap());
+        assert!(!notes::delete_note(&conn, &id).unwrap());
+        assert!(notes::search_notes(&conn, Some("sqlite"), None, 10).unwrap().is_empty());
+    }
+
     #[test]
     fn files_read_is_searchable() {
         let dir = tempfile::tempdir().unwrap();
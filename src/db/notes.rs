@@ -1,4 +1,6 @@
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{ImportOutcome, OnConflict};
 use serde::Serialize;
 
 #[derive(Debug, Serialize)]
@@ -8,6 +10,23 @@ pub struct NoteRow {
     pub content: String,
     pub tags: String,
     pub created_at: String,
+    pub pinned: bool,
+}
+
+/// Columns selected for a `NoteRow`, in `from_row` order. Queries alias `notes` as `n`.
+const NOTE_COLUMNS: &str = "n.id, n.session_id, n.content, n.tags, n.created_at, n.pinned";
+
+impl NoteRow {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(NoteRow {
+            id: row.get(0)?,
+            session_id: row.get(1)?,
+            content: row.get(2)?,
+            tags: row.get(3)?,
+            created_at: row.get(4)?,
+            pinned: row.get(5)?,
+        })
+    }
 }
 
 /// Insert a new note.
@@ -28,6 +47,39 @@ pub fn insert_note(
     Ok(id)
 }
 
+/// Import a note row as-is, keeping its ID and `created_at`. An existing note
+/// with the same ID is resolved per `on_conflict`. A note whose session isn't
+/// in this database is kept, but unlinked.
+pub fn import_note(
+    conn: &Connection,
+    n: &NoteRow,
+    on_conflict: OnConflict,
+) -> anyhow::Result<ImportOutcome> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT created_at FROM notes WHERE id = ?",
+            params![n.id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let outcome = match super::should_import(existing.as_deref(), &n.created_at, on_conflict) {
+        Some(outcome) => outcome,
+        None => return Ok(ImportOutcome::Skipped),
+    };
+
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO notes (id, session_id, content, tags, created_at, pinned)
+         VALUES (?1, (SELECT id FROM sessions WHERE id = ?2), ?3, ?4, ?5, ?6)
+         ON CONFLICT(id) DO UPDATE SET
+            session_id = excluded.session_id, content = excluded.content,
+            tags = excluded.tags, created_at = excluded.created_at, pinned = excluded.pinned",
+    )?;
+    stmt.execute(params![n.id, n.session_id, n.content, n.tags, n.created_at, n.pinned])?;
+
+    Ok(outcome)
+}
+
 /// Full-text search notes.
 pub fn search_notes(
     conn: &Connection,
@@ -35,79 +87,204 @@ pub fn search_notes(
     tag: Option<&str>,
     limit: usize,
 ) -> anyhow::Result<Vec<NoteRow>> {
-    // If we have an FTS query, use the FTS5 table
+    // Tags are stored as a JSON array; match the start of any element
+    let tag_pattern = tag.map(|t| format!("%\"{}%", t));
+
+    // If we have an FTS query, use the FTS5 table (still honoring the tag filter)
     if let Some(q) = query {
         let sanitized = super::sanitize_fts_query(q);
 
-        let mut stmt = conn.prepare(
-            "SELECT n.id, n.session_id, n.content, n.tags, n.created_at
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {NOTE_COLUMNS}
              FROM notes_fts
              JOIN notes n ON notes_fts.rowid = n.rowid
-             WHERE notes_fts MATCH ?
+             WHERE notes_fts MATCH ?1 AND (?2 IS NULL OR n.tags LIKE ?2)
              ORDER BY rank
-             LIMIT ?",
-        )?;
+             LIMIT ?3"
+        ))?;
 
         let rows = stmt
-            .query_map(params![sanitized, limit as i64], |row| {
-                Ok(NoteRow {
-                    id: row.get(0)?,
-                    session_id: row.get(1)?,
-                    content: row.get(2)?,
-                    tags: row.get(3)?,
-                    created_at: row.get(4)?,
-                })
-            })?
+            .query_map(params![sanitized, tag_pattern, limit as i64], NoteRow::from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         return Ok(rows);
     }
 
     // If we only have a tag filter, use LIKE on the tags JSON array
-    if let Some(t) = tag {
-        let pattern = format!("%\"{}%", t);
-        let mut stmt = conn.prepare(
-            "SELECT id, session_id, content, tags, created_at
-             FROM notes WHERE tags LIKE ?
-             ORDER BY created_at DESC LIMIT ?",
-        )?;
+    if let Some(pattern) = tag_pattern {
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {NOTE_COLUMNS}
+             FROM notes n WHERE n.tags LIKE ?
+             ORDER BY n.pinned DESC, n.created_at DESC LIMIT ?"
+        ))?;
 
         let rows = stmt
-            .query_map(params![pattern, limit as i64], |row| {
-                Ok(NoteRow {
-                    id: row.get(0)?,
-                    session_id: row.get(1)?,
-                    content: row.get(2)?,
-                    tags: row.get(3)?,
-                    created_at: row.get(4)?,
-                })
-            })?
+            .query_map(params![pattern, limit as i64], NoteRow::from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         return Ok(rows);
     }
 
-    // No filter — return recent notes
-    let mut stmt = conn.prepare(
-        "SELECT id, session_id, content, tags, created_at
-         FROM notes ORDER BY created_at DESC LIMIT ?",
-    )?;
+    // No filter — return pinned notes, then recent ones
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {NOTE_COLUMNS}
+         FROM notes n ORDER BY n.pinned DESC, n.created_at DESC LIMIT ?"
+    ))?;
 
     let rows = stmt
-        .query_map(params![limit as i64], |row| {
-            Ok(NoteRow {
-                id: row.get(0)?,
-                session_id: row.get(1)?,
-                content: row.get(2)?,
-                tags: row.get(3)?,
-                created_at: row.get(4)?,
-            })
-        })?
+        .query_map(params![limit as i64], NoteRow::from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(rows)
 }
 
+/// Project and global notes as one list of at most `limit`, each marked
+/// `true` when global. Search results alternate between the two, as ranks
+/// from different databases don't compare; listings put pinned notes first,
+/// then the newest.
+pub fn merge_scopes(project: Vec<NoteRow>, global: Vec<NoteRow>, ranked: bool, limit: usize) -> Vec<(bool, NoteRow)> {
+    let mut merged = Vec::with_capacity(project.len() + global.len());
+    if ranked {
+        let (mut project, mut global) = (project.into_iter(), global.into_iter());
+        loop {
+            let (p, g) = (project.next(), global.next());
+            if p.is_none() && g.is_none() {
+                break;
+            }
+            merged.extend(p.map(|n| (false, n)));
+            merged.extend(g.map(|n| (true, n)));
+        }
+    } else {
+        merged.extend(project.into_iter().map(|n| (false, n)));
+        merged.extend(global.into_iter().map(|n| (true, n)));
+        merged.sort_by(|(_, a), (_, b)| b.pinned.cmp(&a.pinned).then_with(|| b.created_at.cmp(&a.created_at)));
+    }
+    merged.truncate(limit);
+    merged
+}
+
+/// All notes created at or after `since` (or all notes), oldest first.
+pub fn notes_since(conn: &Connection, since: Option<&str>) -> anyhow::Result<Vec<NoteRow>> {
+    // `created_at` uses SQLite's "YYYY-MM-DD HH:MM:SS"; compare in ISO form
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {NOTE_COLUMNS} FROM notes n
+         WHERE ?1 IS NULL OR replace(n.created_at, ' ', 'T') >= ?1
+         ORDER BY n.created_at, n.id"
+    ))?;
+    let rows = stmt
+        .query_map(params![since], NoteRow::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Notes matching an FTS query and created within `[from, to]`, oldest
+/// first. Dates compare as in `matching_sessions`.
+pub fn matching_notes(
+    conn: &Connection,
+    query: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> anyhow::Result<Vec<NoteRow>> {
+    let query = query.map(super::sanitize_fts_query);
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {NOTE_COLUMNS} FROM notes n
+         WHERE (?1 IS NULL OR n.rowid IN (SELECT rowid FROM notes_fts WHERE notes_fts MATCH ?1))
+           AND (?2 IS NULL OR replace(n.created_at, ' ', 'T') >= ?2)
+           AND (?3 IS NULL OR substr(replace(n.created_at, ' ', 'T'), 1, length(?3)) <= ?3)
+         ORDER BY n.created_at, n.id"
+    ))?;
+    let rows = stmt
+        .query_map(params![query, from, to], NoteRow::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// IDs of the notes attached to a session.
+pub fn session_note_ids(conn: &Connection, session_id: &str) -> anyhow::Result<Vec<String>> {
+    let mut stmt = conn.prepare_cached("SELECT id FROM notes WHERE session_id = ? ORDER BY created_at")?;
+    let ids = stmt
+        .query_map(params![session_id], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
+/// Resolve a full note ID or unique prefix to the full ID.
+pub fn resolve_note_id(conn: &Connection, prefix: &str) -> anyhow::Result<String> {
+    super::resolve_id_prefix(conn, "notes", "note", prefix)
+}
+
+/// Get a single note by ID.
+pub fn get_note(conn: &Connection, note_id: &str) -> anyhow::Result<Option<NoteRow>> {
+    let mut stmt =
+        conn.prepare_cached(&format!("SELECT {NOTE_COLUMNS} FROM notes n WHERE n.id = ?"))?;
+
+    let mut rows = stmt.query_map(params![note_id], NoteRow::from_row)?;
+
+    match rows.next() {
+        Some(row) => Ok(Some(row?)),
+        None => Ok(None),
+    }
+}
+
+/// Replace a note's content and tags. Returns false if the note doesn't exist.
+pub fn update_note(
+    conn: &Connection,
+    note_id: &str,
+    content: &str,
+    tags: &[String],
+) -> anyhow::Result<bool> {
+    let tags_json = serde_json::to_string(tags)?;
+    let changed = conn.execute(
+        "UPDATE notes SET content = ?, tags = ? WHERE id = ?",
+        params![content, tags_json, note_id],
+    )?;
+    Ok(changed > 0)
+}
+
+/// Pin or unpin a note. Pinned notes are listed before all others.
+pub fn set_note_pinned(conn: &Connection, note_id: &str, pinned: bool) -> anyhow::Result<bool> {
+    let changed = conn.execute(
+        "UPDATE notes SET pinned = ? WHERE id = ?",
+        params![pinned, note_id],
+    )?;
+    Ok(changed > 0)
+}
+
+/// Delete a note. Returns false if the note doesn't exist.
+pub fn delete_note(conn: &Connection, note_id: &str) -> anyhow::Result<bool> {
+    let changed = conn.execute("DELETE FROM notes WHERE id = ?", params![note_id])?;
+    Ok(changed > 0)
+}
+
+/// Note IDs starting with `prefix`, most recent first (for completion).
+pub fn complete_note_ids(
+    conn: &Connection,
+    prefix: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<String>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id FROM notes WHERE id LIKE ? ESCAPE '\\'
+         ORDER BY created_at DESC LIMIT ?",
+    )?;
+    let ids = stmt
+        .query_map(params![super::like_prefix(prefix), limit as i64], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
+/// Distinct note tags starting with `prefix`, alphabetically (for completion).
+pub fn complete_tags(conn: &Connection, prefix: &str, limit: usize) -> anyhow::Result<Vec<String>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT DISTINCT t.value FROM notes, json_each(notes.tags) t
+         WHERE t.value LIKE ? ESCAPE '\\'
+         ORDER BY t.value LIMIT ?",
+    )?;
+    let tags = stmt
+        .query_map(params![super::like_prefix(prefix), limit as i64], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(tags)
+}
+
 /// Get note count.
 pub fn note_count(conn: &Connection) -> anyhow::Result<i64> {
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0))?;
@@ -0,0 +1,112 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// What the database records about its project.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ProjectInfo {
+    /// See [`crate::identity::project_identity`].
+    pub identity: Option<String>,
+    /// The project root the stored paths are under.
+    pub path: Option<String>,
+}
+
+pub fn project_info(conn: &Connection) -> anyhow::Result<ProjectInfo> {
+    let get = |key: &str| -> rusqlite::Result<Option<String>> {
+        conn.query_row("SELECT value FROM project_info WHERE key = ?1", [key], |row| row.get(0))
+            .optional()
+    };
+    Ok(ProjectInfo {
+        identity: get("identity")?,
+        path: get("path")?,
+    })
+}
+
+/// Record the project's identity, and its path unless one is recorded: a
+/// different stored path means the project moved and needs `relink`.
+pub fn record_project(conn: &Connection, identity: &str, path: &str) -> anyhow::Result<()> {
+    conn.execute(
+        "INSERT INTO project_info (key, value) VALUES ('identity', ?1)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value WHERE value != excluded.value",
+        [identity],
+    )?;
+    conn.execute(
+        "INSERT INTO project_info (key, value) VALUES ('path', ?1) ON CONFLICT(key) DO NOTHING",
+        [path],
+    )?;
+    Ok(())
+}
+
+/// Rewrite paths under `old` to be under `new`: session directories and the
+/// files sessions touched. Returns the number of sessions changed.
+pub fn relink(conn: &Connection, old: &str, new: &str) -> anyhow::Result<usize> {
+    let old = old.trim_end_matches('/');
+    let new = new.trim_end_matches('/');
+    // File lists are JSON arrays of strings; match the encoded prefix
+    let encode = |path: &str| {
+        let quoted = serde_json::to_string(&format!("{}/", path)).unwrap_or_default();
+        quoted[..quoted.len() - 1].to_string()
+    };
+
+    let tx = conn.unchecked_transaction()?;
+    let changed = tx.execute(
+        "UPDATE sessions SET
+            project_dir = CASE
+                WHEN project_dir = ?1 THEN ?2
+                WHEN substr(project_dir, 1, length(?1) + 1) = ?1 || '/'
+                    THEN ?2 || substr(project_dir, length(?1) + 1)
+                ELSE project_dir END,
+            files_modified = replace(files_modified, ?3, ?4),
+            files_read = replace(files_read, ?3, ?4)
+         WHERE project_dir = ?1
+            OR substr(project_dir, 1, length(?1) + 1) = ?1 || '/'
+            OR instr(files_modified, ?3) > 0
+            OR instr(files_read, ?3) > 0",
+        params![old, new, encode(old), encode(new)],
+    )?;
+    // An old path nothing was stored under is a typo, not a move
+    if changed > 0 || project_info(&tx)?.path.as_deref() == Some(old) {
+        tx.execute(
+            "INSERT INTO project_info (key, value) VALUES ('path', ?1)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            [new],
+        )?;
+    }
+    tx.commit()?;
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relink_rewrites_paths_under_the_old_root_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = crate::db::open(&dir.path().join("memory.db")).unwrap();
+        conn.execute_batch(
+            r#"INSERT INTO sessions (id, project_dir, started_at, user_prompts, files_modified) VALUES
+                ('a', '/old/app', '2026-01-01', '["fix"]', '["/old/app/src/main.rs"]'),
+                ('b', '/old/app/sub', '2026-01-02', '["fix"]', '[]'),
+                ('c', '/old/apple', '2026-01-03', '["fix"]', '["/old/apple/x.rs"]');"#,
+        )
+        .unwrap();
+        record_project(&conn, "remote:example.com/app", "/old/app").unwrap();
+        record_project(&conn, "remote:example.com/app", "/elsewhere").unwrap();
+        assert_eq!(project_info(&conn).unwrap().path.as_deref(), Some("/old/app"));
+
+        assert_eq!(relink(&conn, "/typo", "/new/app").unwrap(), 0);
+        assert_eq!(project_info(&conn).unwrap().path.as_deref(), Some("/old/app"));
+        assert_eq!(relink(&conn, "/old/app/", "/new/app").unwrap(), 2);
+        let dirs: Vec<(String, String)> = conn
+            .prepare("SELECT project_dir, files_modified FROM sessions ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(dirs[0], ("/new/app".into(), r#"["/new/app/src/main.rs"]"#.into()));
+        assert_eq!(dirs[1].0, "/new/app/sub");
+        assert_eq!(dirs[2], ("/old/apple".into(), r#"["/old/apple/x.rs"]"#.into()));
+        assert_eq!(project_info(&conn).unwrap().path.as_deref(), Some("/new/app"));
+        assert_eq!(crate::db::sessions::search_sessions(&conn, "new", 10).unwrap().0.len(), 1);
+    }
+}
@@ -1,18 +1,150 @@
 use rusqlite::Connection;
 
-/// Current schema version. Bump this and add a migration function when changing the schema.
-const CURRENT_VERSION: i64 = 1;
+/// Current schema version. Bump this and add an entry to `MIGRATIONS` when changing the schema.
+pub const CURRENT_VERSION: i64 = 7;
+
+/// One schema upgrade step, from `version - 1` to `version`.
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    /// Drops or rewrites existing data; the database file is snapshotted first.
+    pub destructive: bool,
+    apply: fn(&Connection) -> anyhow::Result<()>,
+}
+
+/// All migrations, in order. `MIGRATIONS[i].version == i + 1`.
+pub const MIGRATIONS: [Migration; 7] = [
+    Migration {
+        version: 1,
+        description: "Recreate FTS indexes with porter stemming and files_read",
+        destructive: true,
+        apply: migrate_v0_to_v1,
+    },
+    Migration {
+        version: 2,
+        description: "Add sessions.transcript_path",
+        destructive: false,
+        apply: migrate_v1_to_v2,
+    },
+    Migration {
+        version: 3,
+        description: "Add notes.pinned",
+        destructive: false,
+        apply: migrate_v2_to_v3,
+    },
+    Migration {
+        version: 4,
+        description: "Add skipped_sessions for private-session tombstones",
+        destructive: false,
+        apply: migrate_v3_to_v4,
+    },
+    Migration {
+        version: 5,
+        description: "Add project_info for the project's identity and path",
+        destructive: false,
+        apply: migrate_v4_to_v5,
+    },
+    Migration {
+        version: 6,
+        description: "Add sessions.scope for monorepo sub-projects",
+        destructive: false,
+        apply: migrate_v5_to_v6,
+    },
+    Migration {
+        version: 7,
+        description: "Add cross_references for edits made from other projects' sessions",
+        destructive: false,
+        apply: migrate_v6_to_v7,
+    },
+];
+
+/// The database was written by a newer claude-memory than this one.
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "This memory database uses schema v{found}, but this claude-memory only supports up to v{supported}. \
+     Upgrade claude-memory (or restore an older backup with `claude-memory restore`)."
+)]
+pub struct NewerSchemaError {
+    pub found: i64,
+    pub supported: i64,
+}
 
 /// Create all tables, FTS5 indexes, and triggers if they don't exist.
 /// Runs migrations if the schema is outdated.
 pub fn ensure_schema(conn: &Connection) -> anyhow::Result<()> {
-    // Core tables (idempotent)
+    // Fast path: an up-to-date database needs no lock
+    if get_schema_version(conn)? == CURRENT_VERSION {
+        return Ok(());
+    }
+    migrate(conn, CURRENT_VERSION)?;
+    Ok(())
+}
+
+/// Migrate to `target` (at most `CURRENT_VERSION`) and return the resulting version.
+///
+/// Runs inside `BEGIN IMMEDIATE`, so a second process opening the same old
+/// database waits for the first to finish and then sees the new version instead
+/// of migrating again. Any failure rolls the whole upgrade back. If a pending
+/// step is destructive, the database is snapshotted to `memory-backups/` first.
+pub fn migrate(conn: &Connection, target: i64) -> anyhow::Result<i64> {
+    anyhow::ensure!(
+        (0..=CURRENT_VERSION).contains(&target),
+        "Unknown schema version v{} (latest is v{})",
+        target,
+        CURRENT_VERSION
+    );
+
+    conn.execute_batch("BEGIN IMMEDIATE")?;
+    let result = migrate_locked(conn, target);
+    match &result {
+        Ok(_) => conn.execute_batch("COMMIT")?,
+        Err(_) => {
+            let _ = conn.execute_batch("ROLLBACK");
+        }
+    }
+    result
+}
+
+fn migrate_locked(conn: &Connection, target: i64) -> anyhow::Result<i64> {
+    // Re-read under the write lock: another process may have just migrated
+    let version = get_schema_version(conn)?;
+    if version > CURRENT_VERSION {
+        return Err(NewerSchemaError { found: version, supported: CURRENT_VERSION }.into());
+    }
+    anyhow::ensure!(
+        target >= version,
+        "Database is at v{}; downgrading to v{} isn't supported (restore a backup instead)",
+        version,
+        target
+    );
+    if version == target {
+        return Ok(version);
+    }
+
+    let pending = &MIGRATIONS[version as usize..target as usize];
+    let has_data = table_exists(conn, "sessions")?;
+    if has_data && pending.iter().any(|m| m.destructive) {
+        snapshot(conn, version)?;
+    }
+
+    create_core_tables(conn)?;
+    for migration in pending {
+        (migration.apply)(conn).map_err(|e| {
+            anyhow::anyhow!("Migration to schema v{} failed: {}", migration.version, e)
+        })?;
+    }
+
+    set_schema_version(conn, target)?;
+    Ok(target)
+}
+
+/// Core tables (idempotent). Later columns are added by migrations.
+fn create_core_tables(conn: &Connection) -> anyhow::Result<()> {
     conn.execute_batch(
         "
         CREATE TABLE IF NOT EXISTS schema_version (
             version INTEGER NOT NULL
         );
-
         CREATE TABLE IF NOT EXISTS sessions (
             id TEXT PRIMARY KEY,
             project_dir TEXT NOT NULL,
@@ -48,26 +180,48 @@ pub fn ensure_schema(conn: &Connection) -> anyhow::Result<()> {
         CREATE INDEX IF NOT EXISTS idx_notes_created_at ON notes(created_at);
         ",
     )?;
+    Ok(())
+}
 
-    let version = get_schema_version(conn)?;
-
-    if version < CURRENT_VERSION {
-        run_migrations(conn, version)?;
-    }
+/// Copy the database to `memory-backups/memory-pre-migration-v<N>-<timestamp>.db`
+/// next to it. In-memory databases are skipped.
+fn snapshot(conn: &Connection, version: i64) -> anyhow::Result<()> {
+    let path = match conn.path().filter(|p| !p.is_empty()) {
+        Some(p) => std::path::PathBuf::from(p),
+        None => return Ok(()),
+    };
+    let dir = path
+        .parent()
+        .map(|p| p.join("memory-backups"))
+        .unwrap_or_else(|| "memory-backups".into());
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let dest = dir.join(format!("memory-pre-migration-v{}-{}.db", version, stamp));
+    // Read through a second connection: this one holds the write lock, and WAL
+    // readers still see the committed (pre-migration) state
+    super::open_readonly(&path)
+        .and_then(|reader| super::backup_to(&reader, &dest))
+        .map_err(|e| anyhow::anyhow!("Could not snapshot the database before migrating: {}", e))
+}
 
-    Ok(())
+fn table_exists(conn: &Connection, name: &str) -> anyhow::Result<bool> {
+    let exists = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?",
+        [name],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
 }
 
 /// Get the current schema version (0 if table is empty or freshly created).
-fn get_schema_version(conn: &Connection) -> anyhow::Result<i64> {
-    let version: Option<i64> = conn
-        .query_row(
-            "SELECT version FROM schema_version ORDER BY version DESC LIMIT 1",
-            [],
-            |row| row.get(0),
-        )
-        .ok();
-
+pub fn get_schema_version(conn: &Connection) -> anyhow::Result<i64> {
+    if !table_exists(conn, "schema_version")? {
+        return Ok(0);
+    }
+    let version: Option<i64> = conn.query_row(
+        "SELECT MAX(version) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )?;
     Ok(version.unwrap_or(0))
 }
 
@@ -78,16 +232,6 @@ fn set_schema_version(conn: &Connection, version: i64) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Run all pending migrations from `from_version` to `CURRENT_VERSION`.
-fn run_migrations(conn: &Connection, from_version: i64) -> anyhow::Result<()> {
-    if from_version < 1 {
-        migrate_v0_to_v1(conn)?;
-    }
-
-    set_schema_version(conn, CURRENT_VERSION)?;
-    Ok(())
-}
-
 /// Migration v0 → v1:
 /// - Drop old FTS5 tables and triggers (no porter stemming, missing files_read)
 /// - Recreate with `tokenize='porter unicode61'` and `files_read` column
@@ -179,3 +323,82 @@ fn migrate_v0_to_v1(conn: &Connection) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Migration v1 → v2:
+/// - Add `transcript_path` so the original transcript can be found again
+fn migrate_v1_to_v2(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch("ALTER TABLE sessions ADD COLUMN transcript_path TEXT;")?;
+    Ok(())
+}
+
+/// Migration v2 → v3:
+/// - Add `pinned` to notes so important ones stay at the top of listings
+fn migrate_v2_to_v3(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch("ALTER TABLE notes ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;")?;
+    Ok(())
+}
+
+/// Migration v3 → v4:
+/// - Add `skipped_sessions`, tombstones of sessions kept out of memory on
+///   purpose: only the ID and why
+fn migrate_v3_to_v4(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS skipped_sessions (
+            id TEXT PRIMARY KEY,
+            reason TEXT NOT NULL,
+            skipped_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        ",
+    )?;
+    Ok(())
+}
+
+/// Migration v4 → v5:
+/// - Add `project_info`, which project the database belongs to, so moved or
+///   renamed checkouts can be recognized and their stored paths rewritten
+fn migrate_v4_to_v5(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS project_info (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        ",
+    )?;
+    Ok(())
+}
+
+/// Migration v5 → v6:
+/// - Add `scope`, the monorepo sub-project a session worked in (NULL for the
+///   whole repository)
+fn migrate_v5_to_v6(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        "
+        ALTER TABLE sessions ADD COLUMN scope TEXT;
+        CREATE INDEX IF NOT EXISTS idx_sessions_scope ON sessions(scope);
+        ",
+    )?;
+    Ok(())
+}
+
+/// Migration v6 → v7:
+/// - Add `cross_references`, files of this project edited by sessions of
+///   other projects: one row per session, with just enough to find it in the
+///   other project's memory
+fn migrate_v6_to_v7(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS cross_references (
+            session_id TEXT PRIMARY KEY,
+            source_project TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            prompt TEXT,
+            files TEXT NOT NULL DEFAULT '[]',
+            recorded_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_cross_references_started_at ON cross_references(started_at);
+        ",
+    )?;
+    Ok(())
+}
@@ -1,4 +1,6 @@
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{ImportOutcome, OnConflict};
 use serde::Serialize;
 
 use crate::transcript::metadata::SessionMetadata;
@@ -21,6 +23,42 @@ pub struct SessionRow {
     pub input_tokens: i64,
     pub output_tokens: i64,
     pub summary: Option<String>,
+    pub transcript_path: Option<String>,
+    pub ingested_at: String,
+    /// Monorepo sub-project, `None` for the whole repository.
+    pub scope: Option<String>,
+}
+
+/// Columns selected for a `SessionRow`, in `from_row` order. Queries alias `sessions` as `s`.
+const SESSION_COLUMNS: &str = "s.id, s.project_dir, s.git_branch, s.started_at, s.ended_at,
+    s.duration_seconds, s.model, s.user_prompts, s.files_modified,
+    s.files_read, s.commands_run, s.git_commits, s.tools_used,
+    s.input_tokens, s.output_tokens, s.summary, s.transcript_path, s.ingested_at, s.scope";
+
+impl SessionRow {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(SessionRow {
+            id: row.get(0)?,
+            project_dir: row.get(1)?,
+            git_branch: row.get(2)?,
+            started_at: row.get(3)?,
+            ended_at: row.get(4)?,
+            duration_seconds: row.get(5)?,
+            model: row.get(6)?,
+            user_prompts: row.get(7)?,
+            files_modified: row.get(8)?,
+            files_read: row.get(9)?,
+            commands_run: row.get(10)?,
+            git_commits: row.get(11)?,
+            tools_used: row.get(12)?,
+            input_tokens: row.get(13)?,
+            output_tokens: row.get(14)?,
+            summary: row.get(15)?,
+            transcript_path: row.get(16)?,
+            ingested_at: row.get(17)?,
+            scope: row.get(18)?,
+        })
+    }
 }
 
 /// Check if a session has already been ingested.
@@ -33,6 +71,36 @@ pub fn session_exists(conn: &Connection, session_id: &str) -> anyhow::Result<boo
     Ok(exists)
 }
 
+/// Whether a session was deliberately kept out of memory.
+pub fn session_skipped(conn: &Connection, session_id: &str) -> anyhow::Result<bool> {
+    let skipped: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM skipped_sessions WHERE id = ?",
+        params![session_id],
+        |row| row.get(0),
+    )?;
+    Ok(skipped)
+}
+
+/// Leave a tombstone for a session that was kept out of memory.
+pub fn record_skipped(conn: &Connection, session_id: &str, reason: &str) -> anyhow::Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO skipped_sessions (id, reason) VALUES (?, ?)",
+        params![session_id, reason],
+    )?;
+    Ok(())
+}
+
+/// Tombstone counts by reason.
+pub fn skipped_counts(conn: &Connection) -> anyhow::Result<Vec<(String, i64)>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT reason, COUNT(*) FROM skipped_sessions GROUP BY reason ORDER BY COUNT(*) DESC, reason",
+    )?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
 /// Insert a session from parsed metadata.
 pub fn insert_session(conn: &Connection, meta: &SessionMetadata) -> anyhow::Result<()> {
     let user_prompts = serde_json::to_string(&meta.user_prompts)?;
@@ -47,8 +115,8 @@ pub fn insert_session(conn: &Connection, meta: &SessionMetadata) -> anyhow::Resu
     conn.execute(
         "INSERT INTO sessions (id, project_dir, git_branch, started_at, ended_at,
          duration_seconds, model, user_prompts, files_modified, files_read,
-         commands_run, git_commits, tools_used, input_tokens, output_tokens)
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+         commands_run, git_commits, tools_used, input_tokens, output_tokens, transcript_path, scope)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         params![
             meta.session_id,
             meta.project_dir,
@@ -65,12 +133,76 @@ pub fn insert_session(conn: &Connection, meta: &SessionMetadata) -> anyhow::Resu
             tools_used,
             meta.total_input_tokens as i64,
             meta.total_output_tokens as i64,
+            meta.transcript_path,
+            meta.scope,
         ],
     )?;
 
     Ok(())
 }
 
+/// Import a session row as-is, keeping its ID and `ingested_at`. An existing
+/// session with the same ID is resolved per `on_conflict`.
+pub fn import_session(
+    conn: &Connection,
+    s: &SessionRow,
+    on_conflict: OnConflict,
+) -> anyhow::Result<ImportOutcome> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT ingested_at FROM sessions WHERE id = ?",
+            params![s.id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let outcome = match super::should_import(existing.as_deref(), &s.ingested_at, on_conflict) {
+        Some(outcome) => outcome,
+        None => return Ok(ImportOutcome::Skipped),
+    };
+
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO sessions (id, project_dir, git_branch, started_at, ended_at,
+         duration_seconds, model, user_prompts, files_modified, files_read,
+         commands_run, git_commits, tools_used, input_tokens, output_tokens,
+         summary, transcript_path, ingested_at, scope)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET
+            project_dir = excluded.project_dir, git_branch = excluded.git_branch,
+            started_at = excluded.started_at, ended_at = excluded.ended_at,
+            duration_seconds = excluded.duration_seconds, model = excluded.model,
+            user_prompts = excluded.user_prompts, files_modified = excluded.files_modified,
+            files_read = excluded.files_read, commands_run = excluded.commands_run,
+            git_commits = excluded.git_commits, tools_used = excluded.tools_used,
+            input_tokens = excluded.input_tokens, output_tokens = excluded.output_tokens,
+            summary = excluded.summary, transcript_path = excluded.transcript_path,
+            ingested_at = excluded.ingested_at, scope = excluded.scope",
+    )?;
+    stmt.execute(params![
+        s.id,
+        s.project_dir,
+        s.git_branch,
+        s.started_at,
+        s.ended_at,
+        s.duration_seconds,
+        s.model,
+        s.user_prompts,
+        s.files_modified,
+        s.files_read,
+        s.commands_run,
+        s.git_commits,
+        s.tools_used,
+        s.input_tokens,
+        s.output_tokens,
+        s.summary,
+        s.transcript_path,
+        s.ingested_at,
+        s.scope,
+    ])?;
+
+    Ok(outcome)
+}
+
 /// Full-text search across sessions using FTS5.
 ///
 /// Returns `(results, is_fallback)` where `is_fallback` is true if the results
@@ -79,10 +211,20 @@ pub fn search_sessions(
     conn: &Connection,
     query: &str,
     limit: usize,
+) -> anyhow::Result<(Vec<SessionRow>, bool)> {
+    search_sessions_in(conn, query, limit, None)
+}
+
+/// [`search_sessions`] limited to one scope and repository-wide sessions.
+pub fn search_sessions_in(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+    scope: Option<&str>,
 ) -> anyhow::Result<(Vec<SessionRow>, bool)> {
     let sanitized = super::sanitize_fts_query(query);
 
-    let rows = fts_match(conn, &sanitized, limit)?;
+    let rows = fts_match(conn, &sanitized, limit, scope)?;
 
     if !rows.is_empty() {
         return Ok((rows, false));
@@ -90,7 +232,7 @@ pub fn search_sessions(
 
     // AND returned nothing — try OR fallback for multi-word queries
     if let Some(or_query) = super::build_or_fallback(&sanitized) {
-        let fallback_rows = fts_match(conn, &or_query, limit)?;
+        let fallback_rows = fts_match(conn, &or_query, limit, scope)?;
         if !fallback_rows.is_empty() {
             return Ok((fallback_rows, true));
         }
@@ -104,59 +246,36 @@ fn fts_match(
     conn: &Connection,
     match_expr: &str,
     limit: usize,
+    scope: Option<&str>,
 ) -> anyhow::Result<Vec<SessionRow>> {
-    let mut stmt = conn.prepare(
-        "SELECT s.id, s.project_dir, s.git_branch, s.started_at, s.ended_at,
-                s.duration_seconds, s.model, s.user_prompts, s.files_modified,
-                s.files_read, s.commands_run, s.git_commits, s.tools_used,
-                s.input_tokens, s.output_tokens, s.summary
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {SESSION_COLUMNS}
          FROM sessions_fts
          JOIN sessions s ON sessions_fts.rowid = s.rowid
-         WHERE sessions_fts MATCH ?
+         WHERE sessions_fts MATCH ?1
+           AND (?3 IS NULL OR s.scope = ?3 OR s.scope IS NULL)
          ORDER BY rank
-         LIMIT ?",
-    )?;
+         LIMIT ?2"
+    ))?;
 
     let rows = stmt
-        .query_map(params![match_expr, limit as i64], |row| {
-            Ok(SessionRow {
-                id: row.get(0)?,
-                project_dir: row.get(1)?,
-                git_branch: row.get(2)?,
-                started_at: row.get(3)?,
-                ended_at: row.get(4)?,
-                duration_seconds: row.get(5)?,
-                model: row.get(6)?,
-                user_prompts: row.get(7)?,
-                files_modified: row.get(8)?,
-                files_read: row.get(9)?,
-                commands_run: row.get(10)?,
-                git_commits: row.get(11)?,
-                tools_used: row.get(12)?,
-                input_tokens: row.get(13)?,
-                output_tokens: row.get(14)?,
-                summary: row.get(15)?,
-            })
-        })?
+        .query_map(params![match_expr, limit as i64, scope], SessionRow::from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(rows)
 }
 
-/// List sessions ordered by date, optionally filtered.
+/// List sessions ordered by date, optionally filtered by date range, branch
+/// and scope. A scope keeps repository-wide sessions too.
 pub fn list_sessions(
     conn: &Connection,
     limit: usize,
     date_from: Option<&str>,
     date_to: Option<&str>,
+    branch: Option<&str>,
+    scope: Option<&str>,
 ) -> anyhow::Result<Vec<SessionRow>> {
-    let mut sql = String::from(
-        "SELECT id, project_dir, git_branch, started_at, ended_at,
-                duration_seconds, model, user_prompts, files_modified,
-                files_read, commands_run, git_commits, tools_used,
-                input_tokens, output_tokens, summary
-         FROM sessions WHERE 1=1",
-    );
+    let mut sql = format!("SELECT {SESSION_COLUMNS} FROM sessions s WHERE 1=1");
 
     let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
 
@@ -168,69 +287,109 @@ pub fn list_sessions(
         sql.push_str(" AND started_at <= ?");
         param_values.push(Box::new(to.to_string()));
     }
+    if let Some(b) = branch {
+        sql.push_str(" AND git_branch = ?");
+        param_values.push(Box::new(b.to_string()));
+    }
+    if let Some(scope) = scope {
+        sql.push_str(" AND (scope = ? OR scope IS NULL)");
+        param_values.push(Box::new(scope.to_string()));
+    }
 
     sql.push_str(" ORDER BY started_at DESC LIMIT ?");
     param_values.push(Box::new(limit as i64));
 
     let params: Vec<&dyn rusqlite::types::ToSql> = param_values.iter().map(|p| p.as_ref()).collect();
 
-    let mut stmt = conn.prepare(&sql)?;
+    let mut stmt = conn.prepare_cached(&sql)?;
     let rows = stmt
-        .query_map(params.as_slice(), |row| {
-            Ok(SessionRow {
-                id: row.get(0)?,
-                project_dir: row.get(1)?,
-                git_branch: row.get(2)?,
-                started_at: row.get(3)?,
-                ended_at: row.get(4)?,
-                duration_seconds: row.get(5)?,
-                model: row.get(6)?,
-                user_prompts: row.get(7)?,
-                files_modified: row.get(8)?,
-                files_read: row.get(9)?,
-                commands_run: row.get(10)?,
-                git_commits: row.get(11)?,
-                tools_used: row.get(12)?,
-                input_tokens: row.get(13)?,
-                output_tokens: row.get(14)?,
-                summary: row.get(15)?,
-            })
-        })?
+        .query_map(params.as_slice(), SessionRow::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(rows)
+}
+
+/// Session counts by scope, `None` being repository-wide, largest first.
+pub fn scope_counts(conn: &Connection) -> anyhow::Result<Vec<(Option<String>, i64)>> {
+    let mut stmt = conn.prepare("SELECT scope, COUNT(*) FROM sessions GROUP BY scope ORDER BY 2 DESC, 1")?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Set a session's scope. Returns whether it changed.
+pub fn set_session_scope(conn: &Connection, session_id: &str, scope: Option<&str>) -> anyhow::Result<bool> {
+    let changed = conn.execute(
+        "UPDATE sessions SET scope = ?2 WHERE id = ?1 AND scope IS NOT ?2",
+        params![session_id, scope],
+    )?;
+    Ok(changed > 0)
+}
+
+/// All sessions started at or after `since` (or all sessions), oldest first.
+pub fn sessions_since(conn: &Connection, since: Option<&str>) -> anyhow::Result<Vec<SessionRow>> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {SESSION_COLUMNS} FROM sessions s
+         WHERE ?1 IS NULL OR s.started_at >= ?1
+         ORDER BY s.started_at, s.id"
+    ))?;
+    let rows = stmt
+        .query_map(params![since], SessionRow::from_row)?
         .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
 
+/// Sessions matching an FTS query (no OR fallback) and started within
+/// `[from, to]`, oldest first. Each filter is optional; `to` compares by
+/// prefix, so a bare date includes that whole day.
+pub fn matching_sessions(
+    conn: &Connection,
+    query: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> anyhow::Result<Vec<SessionRow>> {
+    let query = query.map(super::sanitize_fts_query);
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {SESSION_COLUMNS} FROM sessions s
+         WHERE (?1 IS NULL OR s.rowid IN (SELECT rowid FROM sessions_fts WHERE sessions_fts MATCH ?1))
+           AND (?2 IS NULL OR s.started_at >= ?2)
+           AND (?3 IS NULL OR substr(s.started_at, 1, length(?3)) <= ?3)
+         ORDER BY s.started_at, s.id"
+    ))?;
+    let rows = stmt
+        .query_map(params![query, from, to], SessionRow::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
     Ok(rows)
 }
 
+/// Resolve a full session ID or unique prefix to the full ID.
+pub fn resolve_session_id(conn: &Connection, prefix: &str) -> anyhow::Result<String> {
+    super::resolve_id_prefix(conn, "sessions", "session", prefix)
+}
+
+/// Session IDs starting with `prefix`, most recent first (for completion).
+pub fn complete_session_ids(
+    conn: &Connection,
+    prefix: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<String>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id FROM sessions WHERE id LIKE ? ESCAPE '\\'
+         ORDER BY started_at DESC LIMIT ?",
+    )?;
+    let ids = stmt
+        .query_map(params![super::like_prefix(prefix), limit as i64], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
 /// Get a single session by ID.
 pub fn get_session(conn: &Connection, session_id: &str) -> anyhow::Result<Option<SessionRow>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project_dir, git_branch, started_at, ended_at,
-                duration_seconds, model, user_prompts, files_modified,
-                files_read, commands_run, git_commits, tools_used,
-                input_tokens, output_tokens, summary
-         FROM sessions WHERE id = ?",
-    )?;
+    let mut stmt =
+        conn.prepare_cached(&format!("SELECT {SESSION_COLUMNS} FROM sessions s WHERE s.id = ?"))?;
 
-    let mut rows = stmt.query_map(params![session_id], |row| {
-        Ok(SessionRow {
-            id: row.get(0)?,
-            project_dir: row.get(1)?,
-            git_branch: row.get(2)?,
-            started_at: row.get(3)?,
-            ended_at: row.get(4)?,
-            duration_seconds: row.get(5)?,
-            model: row.get(6)?,
-            user_prompts: row.get(7)?,
-            files_modified: row.get(8)?,
-            files_read: row.get(9)?,
-            commands_run: row.get(10)?,
-            git_commits: row.get(11)?,
-            tools_used: row.get(12)?,
-            input_tokens: row.get(13)?,
-            output_tokens: row.get(14)?,
-            summary: row.get(15)?,
-        })
-    })?;
+    let mut rows = stmt.query_map(params![session_id], SessionRow::from_row)?;
 
     match rows.next() {
         Some(row) => Ok(Some(row?)),
@@ -238,6 +397,111 @@ pub fn get_session(conn: &Connection, session_id: &str) -> anyhow::Result<Option
     }
 }
 
+/// The facts retention decisions are based on, for one session.
+pub struct SessionOutline {
+    pub id: String,
+    pub started_at: String,
+    pub has_notes: bool,
+    pub has_commits: bool,
+    /// Has `files_read` or `commands_run` left to drop.
+    pub has_details: bool,
+}
+
+/// Outlines of all sessions, newest first.
+pub fn session_outlines(conn: &Connection) -> anyhow::Result<Vec<SessionOutline>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT s.id, s.started_at,
+                EXISTS (SELECT 1 FROM notes n WHERE n.session_id = s.id),
+                s.git_commits != '[]',
+                s.files_read != '[]' OR s.commands_run != '[]'
+         FROM sessions s
+         ORDER BY s.started_at DESC, s.id DESC",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(SessionOutline {
+                id: row.get(0)?,
+                started_at: row.get(1)?,
+                has_notes: row.get(2)?,
+                has_commits: row.get(3)?,
+                has_details: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Delete a session, unlinking (not deleting) any notes that refer to it.
+pub fn delete_session(conn: &Connection, session_id: &str) -> anyhow::Result<bool> {
+    conn.execute("UPDATE notes SET session_id = NULL WHERE session_id = ?", params![session_id])?;
+    let changed = conn.execute("DELETE FROM sessions WHERE id = ?", params![session_id])?;
+    Ok(changed > 0)
+}
+
+/// Reduce a session to its summary fields by dropping files read and commands run.
+pub fn summarize_session(conn: &Connection, session_id: &str) -> anyhow::Result<()> {
+    conn.execute(
+        "UPDATE sessions SET files_read = '[]', commands_run = '[]' WHERE id = ?",
+        params![session_id],
+    )?;
+    Ok(())
+}
+
+/// The free-text columns of a session, which redaction rewrites.
+pub struct SessionText {
+    pub id: String,
+    pub user_prompts: Vec<String>,
+    pub commands_run: Vec<String>,
+    pub git_commits: Vec<String>,
+    pub summary: Option<String>,
+}
+
+/// Free text of every session.
+pub fn session_texts(conn: &Connection) -> anyhow::Result<Vec<SessionText>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, user_prompts, commands_run, git_commits, summary FROM sessions ORDER BY started_at",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    rows.into_iter()
+        .map(|(id, prompts, commands, commits, summary)| {
+            Ok(SessionText {
+                user_prompts: serde_json::from_str(&prompts)?,
+                commands_run: serde_json::from_str(&commands)?,
+                git_commits: serde_json::from_str(&commits)?,
+                id,
+                summary,
+            })
+        })
+        .collect()
+}
+
+/// Write back a session's free text.
+pub fn update_session_text(conn: &Connection, text: &SessionText) -> anyhow::Result<()> {
+    conn.execute(
+        "UPDATE sessions SET user_prompts = ?2, commands_run = ?3, git_commits = ?4, summary = ?5
+         WHERE id = ?1",
+        params![
+            text.id,
+            serde_json::to_string(&text.user_prompts)?,
+            serde_json::to_string(&text.commands_run)?,
+            serde_json::to_string(&text.git_commits)?,
+            text.summary,
+        ],
+    )?;
+    Ok(())
+}
+
 /// Lightweight project-level summary for cross-project listing.
 #[allow(dead_code)]
 pub struct ProjectSummary {
@@ -1,8 +1,15 @@
 mod cli;
 mod config;
+mod cross_project;
 mod db;
+mod identity;
 mod mcp;
+mod registry;
+mod scopes;
+mod storage;
+mod text;
 mod transcript;
+mod tui;
 
 use clap::{Parser, Subcommand};
 
@@ -18,7 +25,14 @@ enum Commands {
     /// Ingest a session transcript (called automatically by the SessionEnd hook)
     Ingest,
     /// Start MCP server for recall during sessions
-    Serve,
+    Serve {
+        /// Serve over streamable HTTP on this address (e.g. 127.0.0.1:8765) instead of stdio
+        #[arg(long, value_name = "ADDR")]
+        http: Option<String>,
+        /// Require this bearer token on HTTP requests (default: $CLAUDE_MEMORY_TOKEN)
+        #[arg(long, requires = "http")]
+        token: Option<String>,
+    },
     /// Install hooks and MCP configuration
     Install,
     /// Show database statistics for current project
@@ -27,10 +41,269 @@ enum Commands {
     Search {
         /// Search query (FTS5 syntax)
         query: String,
-        /// Maximum results
-        #[arg(short, long, default_value = "5")]
+        /// Maximum results [default: search.recall_limit, normally 5]
+        #[arg(short, long)]
+        limit: Option<usize>,
+    },
+    /// Browse past sessions
+    Sessions {
+        #[command(subcommand)]
+        command: SessionsCommand,
+    },
+    /// Read and write notes
+    Notes {
+        #[command(subcommand)]
+        command: NotesCommand,
+    },
+    /// Browse sessions and notes in a full-screen terminal UI
+    Tui,
+    /// Export sessions and notes
+    Export {
+        /// Output format
+        #[arg(long, value_enum)]
+        format: cli::export::Format,
+        /// Only data since this date (YYYY-MM-DD) or age (3d, 2w, 12h)
+        #[arg(long)]
+        since: Option<String>,
+        /// Output file for jsonl (default: stdout) or directory for markdown and csv
+        /// (default: ./memory-export)
+        #[arg(long)]
+        out: Option<std::path::PathBuf>,
+    },
+    /// Import sessions and notes from a JSON Lines export
+    Import {
+        /// File written by `export --format jsonl`
+        file: std::path::PathBuf,
+        /// What to do when a session or note ID already exists
+        #[arg(long, value_enum, default_value = "skip")]
+        on_conflict: db::OnConflict,
+    },
+    /// Merge sessions and notes from another memory database
+    Merge {
+        /// Path to the other memory.db (only read, never modified)
+        other: std::path::PathBuf,
+        /// What to do when a session or note ID already exists
+        #[arg(long, value_enum, default_value = "skip")]
+        on_conflict: db::OnConflict,
+    },
+    /// Snapshot the database (safe while the MCP server is running)
+    Backup {
+        /// Backup file (default: .claude/memory-backups/memory-<timestamp>.db)
+        #[arg(long)]
+        to: Option<std::path::PathBuf>,
+    },
+    /// Replace the database with a backup (the current one is snapshotted first)
+    Restore {
+        /// Backup file to restore
+        file: std::path::PathBuf,
+    },
+    /// Check the database and search indexes for damage
+    Doctor {
+        /// Rebuild search indexes, reindex and migrate where possible
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Show or apply database schema migrations
+    Migrate {
+        /// List applied and pending migrations without changing anything
+        #[arg(long, conflicts_with = "to")]
+        status: bool,
+        /// Migrate to this schema version instead of the latest
+        #[arg(long, value_name = "VERSION")]
+        to: Option<i64>,
+    },
+    /// Redact secrets from stdin, or from stored sessions with --rescan
+    Redact {
+        /// Scrub sessions already in the database and rebuild the search indexes
+        #[arg(long)]
+        rescan: bool,
+        /// Count what --rescan would redact without changing anything
+        #[arg(long, requires = "rescan")]
+        dry_run: bool,
+    },
+    /// Encrypt the database with the key from CLAUDE_MEMORY_KEY or ~/.claude/memory.key
+    Encrypt,
+    /// Convert an encrypted database back to plaintext
+    Decrypt,
+    /// Rewrite the paths stored in memory after the project moved from OLD_PATH
+    Relink {
+        /// Where the project used to be
+        old_path: std::path::PathBuf,
+        /// Relink even if the memory seems to belong to another repository
+        #[arg(long)]
+        force: bool,
+    },
+    /// Move the database to the user's data directory, or back into the project
+    Relocate {
+        /// Where to keep it
+        #[arg(long, value_enum, default_value = "xdg")]
+        to: config::StorageMode,
+    },
+    /// Show or change settings from memory.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Show monorepo scopes and their session counts
+    Scopes {
+        /// Recompute every session's scope from the current [scopes] settings
+        #[arg(long)]
+        reassign: bool,
+    },
+    /// List and manage the registry of projects with memory
+    Projects {
+        #[command(subcommand)]
+        command: ProjectsCommand,
+    },
+    /// Delete or slim down old sessions according to [retention] in memory.toml
+    Prune {
+        /// Show what would be pruned without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Permanently delete sessions or notes by ID, search query or date range
+    Forget {
+        /// Session or note IDs (or unique prefixes)
+        ids: Vec<String>,
+        /// Forget sessions and notes matching this search query
+        #[arg(short, long)]
+        query: Option<String>,
+        /// Only those from this date on (YYYY-MM-DD, or an age like 3d, 2w)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only those up to this date (YYYY-MM-DD, or an age like 90d for older ones)
+        #[arg(long)]
+        until: Option<String>,
+        /// Restrict --query/--since/--until to one kind
+        #[arg(long, value_enum, default_value = "all")]
+        only: db::forget::Scope,
+        /// Also delete notes attached to forgotten sessions (default: keep them, unlinked)
+        #[arg(long)]
+        with_notes: bool,
+        /// List what would be forgotten without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Delete without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionsCommand {
+    /// List recent sessions
+    List {
+        /// Only sessions started since this date (YYYY-MM-DD) or age (3d, 2w, 12h)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only sessions on this git branch
+        #[arg(long)]
+        branch: Option<String>,
+        /// Maximum sessions
+        #[arg(short, long, default_value = "20")]
         limit: usize,
     },
+    /// Show everything recorded about a session
+    Show {
+        /// Session ID or unique prefix
+        id: String,
+    },
+    /// Print the original transcript, if it is still on disk
+    Transcript {
+        /// Session ID or unique prefix
+        id: String,
+        /// Print the raw JSONL instead of a readable conversation
+        #[arg(long)]
+        raw: bool,
+        /// Don't pipe through $PAGER
+        #[arg(long)]
+        no_pager: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProjectsCommand {
+    /// List registered projects and when they were last used
+    List,
+    /// Register a project's database
+    Add {
+        /// Project directory
+        path: std::path::PathBuf,
+    },
+    /// Unregister a project (its database is kept)
+    Remove {
+        /// Project directory or name
+        project: String,
+    },
+    /// Search storage.scan_roots for databases and drop projects whose database is gone
+    Rescan,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Show every effective setting and where it comes from
+    Show,
+    /// Print one setting, e.g. `search.recall_limit`
+    Get {
+        /// Setting name: <section>.<key>
+        key: String,
+    },
+    /// Write a setting to the project's memory.toml
+    Set {
+        /// Setting name: <section>.<key>
+        key: String,
+        /// New value
+        value: String,
+        /// Write to ~/.claude/memory.toml instead
+        #[arg(long)]
+        user: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum NotesCommand {
+    /// Save a note the agent will see in future sessions
+    Add {
+        /// Note text
+        text: String,
+        /// Tag the note (repeatable)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Save to the global memory, seen in every project
+        #[arg(long)]
+        global: bool,
+    },
+    /// List notes, most recent first
+    List {
+        /// Only notes with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Full-text search query
+        #[arg(short, long)]
+        query: Option<String>,
+        /// Maximum notes
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+        /// Only global notes (by default they follow the project's)
+        #[arg(long)]
+        global: bool,
+    },
+    /// Edit a note in $EDITOR
+    Edit {
+        /// Note ID or unique prefix
+        id: String,
+        /// A note of the global memory
+        #[arg(long)]
+        global: bool,
+    },
+    /// Delete a note
+    Rm {
+        /// Note ID or unique prefix
+        id: String,
+        /// A note of the global memory
+        #[arg(long)]
+        global: bool,
+    },
 }
 
 fn main() -> anyhow::Result<()> {
@@ -38,10 +311,71 @@ fn main() -> anyhow::Result<()> {
 
     match cli.command {
         Commands::Ingest => cli::ingest::run()?,
-        Commands::Serve => mcp::server::run()?,
+        Commands::Serve { http: None, .. } => mcp::server::run()?,
+        Commands::Serve { http: Some(addr), token } => {
+            let token = token.or_else(|| std::env::var("CLAUDE_MEMORY_TOKEN").ok());
+            mcp::http::run(&addr, token)?
+        }
         Commands::Install => cli::install::run()?,
         Commands::Status => cli::status::run()?,
         Commands::Search { query, limit } => cli::search::run(&query, limit)?,
+        Commands::Sessions { command } => match command {
+            SessionsCommand::List { since, branch, limit } => {
+                cli::sessions::list(since.as_deref(), branch.as_deref(), limit)?
+            }
+            SessionsCommand::Show { id } => cli::sessions::show(&id)?,
+            SessionsCommand::Transcript { id, raw, no_pager } => {
+                cli::sessions::transcript(&id, raw, no_pager)?
+            }
+        },
+        Commands::Notes { command } => match command {
+            NotesCommand::Add { text, tags, global } => cli::notes::add(&text, &tags, global)?,
+            NotesCommand::List { tag, query, limit, global } => {
+                cli::notes::list(tag.as_deref(), query.as_deref(), limit, global)?
+            }
+            NotesCommand::Edit { id, global } => cli::notes::edit(&id, global)?,
+            NotesCommand::Rm { id, global } => cli::notes::rm(&id, global)?,
+        },
+        Commands::Tui => tui::run()?,
+        Commands::Export { format, since, out } => {
+            cli::export::run(format, since.as_deref(), out.as_deref())?
+        }
+        Commands::Import { file, on_conflict } => cli::import::import(&file, on_conflict)?,
+        Commands::Merge { other, on_conflict } => cli::import::merge(&other, on_conflict)?,
+        Commands::Backup { to } => cli::backup::backup(to.as_deref())?,
+        Commands::Restore { file } => cli::backup::restore(&file)?,
+        Commands::Doctor { fix } => cli::doctor::run(fix)?,
+        Commands::Migrate { status, to } => cli::migrate::run(status, to)?,
+        Commands::Redact { rescan, dry_run } => cli::redact::run(rescan, dry_run)?,
+        Commands::Encrypt => cli::encrypt::encrypt()?,
+        Commands::Decrypt => cli::encrypt::decrypt()?,
+        Commands::Relink { old_path, force } => cli::relink::run(&old_path, force)?,
+        Commands::Relocate { to } => cli::relocate::run(to)?,
+        Commands::Config { command } => match command {
+            ConfigCommand::Show => cli::settings::show()?,
+            ConfigCommand::Get { key } => cli::settings::get(&key)?,
+            ConfigCommand::Set { key, value, user } => cli::settings::set(&key, &value, user)?,
+        },
+        Commands::Scopes { reassign } => cli::scopes::run(reassign)?,
+        Commands::Projects { command } => match command {
+            ProjectsCommand::List => cli::projects::list()?,
+            ProjectsCommand::Add { path } => cli::projects::add(&path)?,
+            ProjectsCommand::Remove { project } => cli::projects::remove(&project)?,
+            ProjectsCommand::Rescan => cli::projects::rescan()?,
+        },
+        Commands::Prune { dry_run } => cli::prune::run(dry_run)?,
+        Commands::Forget { ids, query, since, until, only, with_notes, dry_run, yes } => {
+            cli::forget::run(cli::forget::ForgetArgs {
+                ids,
+                query,
+                since,
+                until,
+                only,
+                with_notes,
+                dry_run,
+                yes,
+            })?
+        }
     }
 
     Ok(())
@@ -0,0 +1,226 @@
+//! Token budgets for tool output.
+//!
+//! Tool results are rendered at the most detailed level that fits the
+//! caller's estimated token budget. Each level caps how much of every section
+//! is shown; when even the tightest level is too large, trailing items are
+//! dropped and the output says how to fetch the rest.
+
+use serde_json::Value;
+
+use crate::config::{OutputSettings, Verbosity};
+
+/// Index into the detail ladders where rendering starts.
+fn start_level(verbosity: Verbosity) -> usize {
+    match verbosity {
+        Verbosity::Full => 0,
+        Verbosity::Normal => 1,
+        Verbosity::Brief => 2,
+    }
+}
+
+/// How much of each section of a session (or note) to include.
+#[derive(Debug, Clone, Copy)]
+pub struct Detail {
+    pub prompts: usize,
+    pub prompt_len: usize,
+    pub files: usize,
+    pub commits: usize,
+    pub files_read: usize,
+    pub commands: usize,
+    /// Cap on note content length.
+    pub note_len: usize,
+}
+
+const ALL: usize = usize::MAX;
+
+/// Levels for session summaries in `recall` and `list_sessions`, most detailed first.
+pub const SUMMARY_LEVELS: [Detail; 4] = [
+    Detail { prompts: ALL, prompt_len: 500, files: ALL, commits: ALL, files_read: 0, commands: 0, note_len: ALL },
+    Detail { prompts: 2, prompt_len: 150, files: 8, commits: 5, files_read: 0, commands: 0, note_len: 1000 },
+    Detail { prompts: 1, prompt_len: 80, files: 3, commits: 1, files_read: 0, commands: 0, note_len: 200 },
+    Detail { prompts: 1, prompt_len: 60, files: 0, commits: 0, files_read: 0, commands: 0, note_len: 80 },
+];
+
+/// Levels for a single session in `get_session`, most detailed first.
+pub const DETAIL_LEVELS: [Detail; 4] = [
+    Detail { prompts: ALL, prompt_len: ALL, files: ALL, commits: ALL, files_read: ALL, commands: ALL, note_len: ALL },
+    Detail { prompts: 10, prompt_len: 500, files: 30, commits: 20, files_read: 20, commands: 30, note_len: ALL },
+    Detail { prompts: 3, prompt_len: 150, files: 8, commits: 5, files_read: 0, commands: 10, note_len: ALL },
+    Detail { prompts: 1, prompt_len: 100, files: 3, commits: 3, files_read: 0, commands: 0, note_len: ALL },
+];
+
+/// Rough token estimate: about four characters per token for English and code.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// The output limits for one tool call.
+#[derive(Debug, Clone, Copy)]
+pub struct Budget {
+    pub verbosity: Verbosity,
+    /// `None` means unlimited.
+    pub max_tokens: Option<usize>,
+}
+
+impl Budget {
+    /// Read `verbosity` and `max_tokens` from tool arguments, falling back to
+    /// the project's output settings.
+    pub fn from_args(args: &Value, defaults: &OutputSettings) -> anyhow::Result<Self> {
+        let verbosity = match args.get("verbosity").and_then(|v| v.as_str()) {
+            Some(v) => Verbosity::parse(v).ok_or_else(|| {
+                anyhow::anyhow!("Invalid verbosity: {} (expected brief, normal or full)", v)
+            })?,
+            None => defaults.verbosity,
+        };
+
+        let max_tokens = args
+            .get("max_tokens")
+            .and_then(|m| m.as_u64())
+            .map(|m| m as usize)
+            .unwrap_or(defaults.max_tokens);
+
+        Ok(Self {
+            verbosity,
+            max_tokens: (max_tokens > 0).then_some(max_tokens),
+        })
+    }
+
+    pub fn fits(&self, text: &str) -> bool {
+        self.max_tokens.is_none_or(|max| estimate_tokens(text) <= max)
+    }
+}
+
+/// Render `header` followed by `items` at the most detailed level of `levels`
+/// that fits the budget, starting from the requested verbosity.
+///
+/// If even the last level is too large, trailing items are dropped (at least
+/// one is always kept) and a footer says how many were omitted. `hint` is
+/// appended to the footer whenever anything was trimmed.
+pub fn fit_items<T>(
+    budget: &Budget,
+    levels: &[Detail],
+    header: &str,
+    items: &[T],
+    hint: &str,
+    render: impl Fn(&T, &Detail) -> String,
+) -> String {
+    let start = start_level(budget.verbosity).min(levels.len() - 1);
+
+    for (i, detail) in levels.iter().enumerate().skip(start) {
+        let mut out = header.to_string();
+        for item in items {
+            out.push_str(&render(item, detail));
+        }
+        if budget.fits(&out) {
+            if i > start {
+                out.push_str(&trimmed_footer(budget, hint));
+            }
+            return out;
+        }
+    }
+
+    let tightest = &levels[levels.len() - 1];
+    let mut out = header.to_string();
+    let mut shown = 0;
+    for item in items {
+        let rendered = render(item, tightest);
+        if shown > 0 && !budget.fits(&format!("{}{}", out, rendered)) {
+            break;
+        }
+        out.push_str(&rendered);
+        shown += 1;
+    }
+
+    out.push_str(&trimmed_footer(budget, hint));
+    if shown < items.len() {
+        out.push_str(&format!(
+            "_{} more result(s) omitted — raise `max_tokens` or narrow the request._\n",
+            items.len() - shown
+        ));
+    }
+    out
+}
+
+fn trimmed_footer(budget: &Budget, hint: &str) -> String {
+    let mut footer = format!(
+        "\n_Output trimmed to fit ~{} tokens; pass a larger `max_tokens` to see more.",
+        budget.max_tokens.unwrap_or(0)
+    );
+    if !hint.is_empty() {
+        footer.push(' ');
+        footer.push_str(hint);
+    }
+    footer.push_str("_\n");
+    footer
+}
+
+/// Take up to `max` items, noting how many were left out.
+pub fn capped<T>(items: &[T], max: usize) -> (&[T], usize) {
+    let shown = items.len().min(max);
+    (&items[..shown], items.len() - shown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::truncate;
+    use serde_json::json;
+
+    fn budget(verbosity: Verbosity, max_tokens: Option<usize>) -> Budget {
+        Budget { verbosity, max_tokens }
+    }
+
+    fn render(item: &&str, detail: &Detail) -> String {
+        format!("{}\n", truncate(item, detail.prompt_len))
+    }
+
+    #[test]
+    fn args_override_settings() {
+        let defaults = OutputSettings::default();
+        let b = Budget::from_args(&json!({ "verbosity": "brief", "max_tokens": 100 }), &defaults).unwrap();
+        assert_eq!(b.verbosity, Verbosity::Brief);
+        assert_eq!(b.max_tokens, Some(100));
+
+        let b = Budget::from_args(&json!({ "max_tokens": 0 }), &defaults).unwrap();
+        assert_eq!(b.verbosity, Verbosity::Normal);
+        assert_eq!(b.max_tokens, None);
+
+        assert!(Budget::from_args(&json!({ "verbosity": "loud" }), &defaults).is_err());
+    }
+
+    #[test]
+    fn fits_at_requested_level_without_footer() {
+        let a = "a".repeat(100);
+        let items = vec![a.as_str()];
+        let out = fit_items(&budget(Verbosity::Full, None), &SUMMARY_LEVELS, "# h\n", &items, "", render);
+        assert_eq!(out, format!("# h\n{}\n", "a".repeat(100)));
+    }
+
+    #[test]
+    fn steps_down_levels_before_dropping_items() {
+        let (a, b) = ("a".repeat(400), "b".repeat(400));
+        let items = vec![a.as_str(), b.as_str()];
+        // 800+ chars at full, ~2*150 at normal: a 100-token budget forces normal
+        let out = fit_items(&budget(Verbosity::Full, Some(100)), &SUMMARY_LEVELS, "", &items, "", render);
+        assert!(out.contains(&format!("{}...", "a".repeat(150))));
+        assert!(out.contains("Output trimmed"));
+        assert!(!out.contains("omitted"));
+    }
+
+    #[test]
+    fn drops_trailing_items_at_tightest_level() {
+        let owned: Vec<String> = (0..50).map(|i| format!("item {} {}", i, "x".repeat(100))).collect();
+        let items: Vec<&str> = owned.iter().map(|s| s.as_str()).collect();
+        let out = fit_items(&budget(Verbosity::Brief, Some(100)), &SUMMARY_LEVELS, "", &items, "", render);
+        assert!(out.contains("item 0"));
+        assert!(!out.contains("item 49"));
+        assert!(out.contains("more result(s) omitted"));
+    }
+
+    #[test]
+    fn estimate_counts_chars_not_bytes() {
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(estimate_tokens("ääää"), 1);
+    }
+}
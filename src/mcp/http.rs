@@ -0,0 +1,947 @@
+//! MCP streamable HTTP transport.
+//!
+//! A single endpoint (`/mcp`) accepts JSON-RPC messages via POST and answers
+//! either with a JSON body or a one-event SSE stream, depending on the
+//! client's `Accept` header. Sessions are tracked with the `Mcp-Session-Id`
+//! header, each with its own server, so connections, roots and held-back
+//! confirmations carry over between requests. The project can be chosen per
+//! request with the `X-Claude-Memory-Project` header or a `?project=` query
+//! parameter.
+//!
+//! Server-initiated requests (`roots/list`, `elicitation/create`) reach the
+//! client only on event streams: queued ones go out ahead of the next
+//! streamed response, and a `forget` call that asks for confirmation keeps
+//! its stream open until the answer arrives.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::config;
+use crate::mcp::server::{self, MemoryServer};
+
+/// Largest request body accepted, to keep a misbehaving client from exhausting memory.
+const MAX_BODY_LEN: usize = 4 * 1024 * 1024;
+
+/// Longest request or header line accepted, and most header lines.
+const MAX_LINE_LEN: usize = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+
+/// How long a connection may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Most connections served at once; more are answered with 503.
+const MAX_CONNECTIONS: usize = 64;
+
+/// Sessions unused for this long are dropped, for clients that never end them.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// How long a stream waits for the user to answer a confirmation.
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+const PROJECT_HEADER: &str = "x-claude-memory-project";
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Shared state for all connections.
+pub struct HttpState {
+    default_project: PathBuf,
+    token: Option<String>,
+    sessions: Mutex<HashMap<String, Arc<Session>>>,
+    session_idle_timeout: Duration,
+    connections: AtomicUsize,
+    max_connections: usize,
+    /// Projects a request may select besides the default: those with a memory
+    /// database already, so a request can't create one anywhere on disk.
+    known_projects: Box<dyn Fn() -> Vec<PathBuf> + Send + Sync>,
+}
+
+impl HttpState {
+    pub fn new(default_project: PathBuf, token: Option<String>) -> Self {
+        Self {
+            default_project,
+            token,
+            sessions: Mutex::new(HashMap::new()),
+            session_idle_timeout: SESSION_IDLE_TIMEOUT,
+            connections: AtomicUsize::new(0),
+            max_connections: MAX_CONNECTIONS,
+            known_projects: Box::new(|| {
+                config::discover_project_dbs()
+                    .into_iter()
+                    .map(|p| p.project_dir)
+                    .collect()
+            }),
+        }
+    }
+
+    /// The session with this ID, unless it went unused too long.
+    fn session(&self, sid: &str) -> Option<Arc<Session>> {
+        let mut sessions = self.sessions.lock().unwrap();
+        self.expire_sessions(&mut sessions);
+        let session = sessions.get(sid)?;
+        *session.last_used.lock().unwrap() = Instant::now();
+        Some(Arc::clone(session))
+    }
+
+    fn add_session(&self, sid: String, session: Arc<Session>) {
+        let mut sessions = self.sessions.lock().unwrap();
+        self.expire_sessions(&mut sessions);
+        sessions.insert(sid, session);
+    }
+
+    fn expire_sessions(&self, sessions: &mut HashMap<String, Arc<Session>>) {
+        sessions.retain(|_, session| session.last_used.lock().unwrap().elapsed() < self.session_idle_timeout);
+    }
+}
+
+/// A counted connection, released when the connection ends.
+struct ConnectionSlot(Arc<HttpState>);
+
+impl ConnectionSlot {
+    fn take(state: &Arc<HttpState>) -> Option<Self> {
+        let taken = state.connections.fetch_add(1, Ordering::SeqCst);
+        if taken >= state.max_connections {
+            state.connections.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(Self(Arc::clone(state)))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.connections.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// One MCP session.
+struct Session {
+    state: Mutex<SessionState>,
+    /// Signalled after every request, since a client's answer may complete a
+    /// held-back call.
+    changed: Condvar,
+    last_used: Mutex<Instant>,
+}
+
+struct SessionState {
+    server: MemoryServer,
+    /// Responses to held-back calls, waiting for their stream to pick them up.
+    responses: Vec<Value>,
+}
+
+impl Session {
+    fn new(server: MemoryServer) -> Self {
+        Self {
+            state: Mutex::new(SessionState { server, responses: Vec::new() }),
+            changed: Condvar::new(),
+            last_used: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl SessionState {
+    /// Take the requests and notifications the server queued for the client,
+    /// setting aside responses for the streams they belong to.
+    fn take_outgoing(&mut self) -> Vec<Value> {
+        let (messages, responses): (Vec<Value>, Vec<Value>) =
+            self.server.drain_outgoing().into_iter().partition(|m| m.get("method").is_some());
+        self.responses.extend(responses);
+        messages
+    }
+}
+
+/// A parsed HTTP/1.1 request.
+struct HttpRequest {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(|s| s.as_str())
+    }
+}
+
+/// An HTTP response ready to be written.
+struct HttpResponse {
+    status: u16,
+    content_type: Option<&'static str>,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+    /// An event stream that stays open after `body`.
+    follow: Option<FollowUp>,
+}
+
+/// The rest of an event stream: what the session sends until the response
+/// to the held-back call `call_id` is ready.
+struct FollowUp {
+    session: Arc<Session>,
+    call_id: Value,
+}
+
+impl HttpResponse {
+    fn empty(status: u16) -> Self {
+        Self {
+            status,
+            content_type: None,
+            headers: Vec::new(),
+            body: String::new(),
+            follow: None,
+        }
+    }
+
+    fn text(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            content_type: Some("text/plain; charset=utf-8"),
+            headers: Vec::new(),
+            body: format!("{}\n", body),
+            follow: None,
+        }
+    }
+
+    fn event_stream(status: u16, messages: &[Value]) -> Self {
+        Self {
+            status,
+            content_type: Some("text/event-stream"),
+            headers: vec![("Cache-Control", "no-cache".to_string())],
+            body: messages.iter().map(sse_event).collect(),
+            follow: None,
+        }
+    }
+}
+
+fn sse_event(message: &Value) -> String {
+    format!("event: message\ndata: {}\n\n", message)
+}
+
+/// Start the HTTP server on `addr` (e.g. `127.0.0.1:8765`) and serve forever.
+pub fn run(addr: &str, token: Option<String>) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    check_exposure(listener.local_addr()?, token.as_deref())?;
+    let default_project = config::detect_project_dir()?;
+
+    eprintln!(
+        "claude-memory: serving MCP over HTTP at http://{}/mcp{}",
+        listener.local_addr()?,
+        if token.is_some() { " (bearer auth)" } else { "" }
+    );
+
+    serve(listener, Arc::new(HttpState::new(default_project, token)))
+}
+
+/// Anyone who can reach a non-loopback address could read and write memory,
+/// so those need a token.
+fn check_exposure(addr: SocketAddr, token: Option<&str>) -> anyhow::Result<()> {
+    if !addr.ip().is_loopback() && token.is_none() {
+        anyhow::bail!(
+            "refusing to serve on {} without a token; use a loopback address or pass --token",
+            addr
+        );
+    }
+    Ok(())
+}
+
+/// Accept connections on `listener`, one thread per connection, up to
+/// `MAX_CONNECTIONS` at a time.
+pub fn serve(listener: TcpListener, state: Arc<HttpState>) -> anyhow::Result<()> {
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+        let Some(slot) = ConnectionSlot::take(&state) else {
+            let _ = write_response(&mut stream, &HttpResponse::text(503, "Too many connections"));
+            continue;
+        };
+        let state = Arc::clone(&state);
+        std::thread::spawn(move || {
+            let _slot = slot;
+            if let Err(e) = handle_connection(stream, &state) {
+                eprintln!("claude-memory: http connection error: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream, state: &HttpState) -> anyhow::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+
+    let response = match read_request(&mut reader) {
+        Ok(Some(request)) => route(&request, state),
+        Ok(None) => return Ok(()),
+        Err(e) => {
+            let Some(rejected) = e.downcast_ref::<Rejected>() else {
+                return Err(e);
+            };
+            write_response(&mut stream, &HttpResponse::text(rejected.status, rejected.reason))?;
+            // Closing with the rest unread would reset the connection before
+            // the client reads the answer
+            stream.shutdown(std::net::Shutdown::Write)?;
+            let _ = std::io::copy(&mut std::io::Read::take(reader, MAX_BODY_LEN as u64), &mut std::io::sink());
+            return Ok(());
+        }
+    };
+
+    write_response(&mut stream, &response)
+}
+
+fn route(request: &HttpRequest, state: &HttpState) -> HttpResponse {
+    if request.path != "/mcp" {
+        return HttpResponse::text(404, "Not found");
+    }
+
+    // Reject cross-site browser requests (DNS rebinding protection)
+    if let Some(origin) = request.header("origin") {
+        if !is_local_origin(origin) {
+            return HttpResponse::text(403, "Forbidden origin");
+        }
+    }
+
+    if !is_authorized(request, state) {
+        let mut response = HttpResponse::text(401, "Unauthorized");
+        response
+            .headers
+            .push(("WWW-Authenticate", "Bearer".to_string()));
+        return response;
+    }
+
+    match request.method.as_str() {
+        "POST" => handle_post(request, state),
+        "DELETE" => handle_delete(request, state),
+        _ => {
+            let mut response = HttpResponse::text(405, "Method not allowed");
+            response.headers.push(("Allow", "POST, DELETE".to_string()));
+            response
+        }
+    }
+}
+
+fn handle_post(request: &HttpRequest, state: &HttpState) -> HttpResponse {
+    let body = match std::str::from_utf8(&request.body) {
+        Ok(b) => b,
+        Err(_) => return HttpResponse::text(400, "Body is not valid UTF-8"),
+    };
+
+    let message = serde_json::from_str::<Value>(body).ok();
+    let method = message.as_ref().and_then(|v| v.get("method")).and_then(|m| m.as_str());
+    let is_initialize = method == Some("initialize");
+    let call_id = message
+        .as_ref()
+        .and_then(|v| v.get("id"))
+        .filter(|id| method.is_some() && !id.is_null())
+        .cloned();
+
+    // Every request after initialize must carry a known session ID
+    let session = if is_initialize {
+        Arc::new(Session::new(MemoryServer::for_project(&state.default_project)))
+    } else {
+        let sid = match request.header(SESSION_HEADER) {
+            Some(sid) => sid,
+            None => return HttpResponse::text(400, "Missing Mcp-Session-Id header"),
+        };
+        match state.session(sid) {
+            Some(session) => session,
+            None => return HttpResponse::text(404, "Unknown session"),
+        }
+    };
+
+    let project = match select_project(request, state) {
+        Ok(p) => p,
+        Err((status, msg)) => return HttpResponse::text(status, &msg),
+    };
+    let stream = accepts_event_stream(request);
+
+    let (rpc_response, queued) = {
+        let mut session_state = session.state.lock().unwrap();
+        session_state.server.pin_project(project);
+        session_state.server.set_can_send_requests(stream);
+        let rpc_response = server::handle_request(&mut session_state.server, body);
+        let queued = if stream { session_state.take_outgoing() } else { Vec::new() };
+        (rpc_response, queued)
+    };
+    session.changed.notify_all();
+
+    let rpc_response = match (rpc_response, call_id) {
+        (Some(r), _) => r,
+        // A call held back for confirmation answers on its stream later
+        (None, Some(call_id)) => {
+            let mut response = HttpResponse::event_stream(200, &queued);
+            response.follow = Some(FollowUp { session, call_id });
+            return response;
+        }
+        // Notifications and client responses are acknowledged without a body
+        (None, None) => return HttpResponse::empty(202),
+    };
+
+    let payload = match serde_json::to_value(&rpc_response) {
+        Ok(p) => p,
+        Err(e) => return HttpResponse::text(500, &e.to_string()),
+    };
+
+    let status = if rpc_response.id.is_null() && rpc_response.error.is_some() {
+        400
+    } else {
+        200
+    };
+
+    let mut response = if stream {
+        let mut messages = queued;
+        messages.push(payload);
+        HttpResponse::event_stream(status, &messages)
+    } else {
+        HttpResponse {
+            status,
+            content_type: Some("application/json"),
+            headers: Vec::new(),
+            body: payload.to_string(),
+            follow: None,
+        }
+    };
+
+    if is_initialize && rpc_response.error.is_none() {
+        let sid = uuid::Uuid::new_v4().to_string();
+        state.add_session(sid.clone(), session);
+        response.headers.push(("Mcp-Session-Id", sid));
+    }
+
+    response
+}
+
+fn handle_delete(request: &HttpRequest, state: &HttpState) -> HttpResponse {
+    match request.header(SESSION_HEADER) {
+        Some(sid) if state.sessions.lock().unwrap().remove(sid).is_some() => HttpResponse::empty(204),
+        Some(_) => HttpResponse::text(404, "Unknown session"),
+        None => HttpResponse::text(400, "Missing Mcp-Session-Id header"),
+    }
+}
+
+/// The project this request asks for with the header or the query
+/// parameter. Without either, the session's roots or the server default apply.
+/// Only the default project and projects with a memory database are allowed.
+fn select_project(request: &HttpRequest, state: &HttpState) -> Result<Option<PathBuf>, (u16, String)> {
+    let spec = request
+        .header(PROJECT_HEADER)
+        .or_else(|| request.query.get("project").map(|s| s.as_str()));
+    let Some(spec) = spec else {
+        return Ok(None);
+    };
+
+    let project = config::resolve_project(spec).ok_or_else(|| (400, format!("Unknown project: {}", spec)))?;
+    if project != state.default_project && !(state.known_projects)().contains(&project) {
+        return Err((403, format!("Project has no memory database: {}", project.display())));
+    }
+    Ok(Some(project))
+}
+
+fn is_authorized(request: &HttpRequest, state: &HttpState) -> bool {
+    let expected = match &state.token {
+        Some(t) => t,
+        None => return true,
+    };
+
+    let provided = request
+        .header("authorization")
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .unwrap_or("");
+
+    constant_time_eq(provided.as_bytes(), expected.as_bytes())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let host = origin
+        .split("://")
+        .nth(1)
+        .unwrap_or(origin)
+        .split('/')
+        .next()
+        .unwrap_or("");
+    let host = match host.rsplit_once(':') {
+        Some((h, port)) if port.chars().all(|c| c.is_ascii_digit()) => h,
+        _ => host,
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// Prefer SSE only when the client explicitly accepts it.
+fn accepts_event_stream(request: &HttpRequest) -> bool {
+    request
+        .header("accept")
+        .is_some_and(|a| a.contains("text/event-stream"))
+}
+
+/// A request refused before it is read in full, answered with `status`.
+#[derive(Debug)]
+struct Rejected {
+    status: u16,
+    reason: &'static str,
+}
+
+impl std::fmt::Display for Rejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.reason, self.status)
+    }
+}
+
+impl std::error::Error for Rejected {}
+
+const HEADERS_TOO_LARGE: Rejected = Rejected {
+    status: 431,
+    reason: "Request header fields too large",
+};
+
+/// Read one line of the request head, refusing lines over `MAX_LINE_LEN`.
+fn read_head_line(reader: &mut impl BufRead, line: &mut String) -> anyhow::Result<usize> {
+    let read = std::io::Read::take(reader, MAX_LINE_LEN as u64 + 1).read_line(line)?;
+    if read > MAX_LINE_LEN {
+        return Err(HEADERS_TOO_LARGE.into());
+    }
+    Ok(read)
+}
+
+/// Read one request from the connection. Returns `None` if the peer closed it.
+fn read_request(reader: &mut impl BufRead) -> anyhow::Result<Option<HttpRequest>> {
+    let mut request_line = String::new();
+    if read_head_line(reader, &mut request_line)? == 0 {
+        return Ok(None);
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let target = parts.next().unwrap_or("/");
+
+    let (path, query) = match target.split_once('?') {
+        Some((p, q)) => (p.to_string(), parse_query(q)),
+        None => (target.to_string(), HashMap::new()),
+    };
+
+    let mut headers = HashMap::new();
+    for count in 0.. {
+        let mut line = String::new();
+        if read_head_line(reader, &mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if count == MAX_HEADERS {
+            return Err(HEADERS_TOO_LARGE.into());
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let content_length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_LEN {
+        return Err(Rejected { status: 413, reason: "Request body too large" }.into());
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Some(HttpRequest {
+        method,
+        path,
+        query,
+        headers,
+        body,
+    }))
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (form_decode(k), form_decode(v)))
+        .collect()
+}
+
+/// Query strings are form-encoded: `+` is a space there, unlike in paths.
+fn form_decode(s: &str) -> String {
+    percent_decode(&s.replace('+', " "))
+}
+
+pub fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            // `from_str_radix` alone would also take a sign, as in `%+1`
+            b'%' if i + 2 < bytes.len() && bytes[i + 1].is_ascii_hexdigit() && bytes[i + 2].is_ascii_hexdigit() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).expect("hex digits are ASCII");
+                out.push(u8::from_str_radix(hex, 16).expect("two hex digits"));
+                i += 3;
+                continue;
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn write_response(stream: &mut impl Write, response: &HttpResponse) -> anyhow::Result<()> {
+    let mut head = format!("HTTP/1.1 {} {}\r\n", response.status, reason_phrase(response.status));
+    // An open-ended stream ends when the connection closes
+    if response.follow.is_none() {
+        head.push_str(&format!("Content-Length: {}\r\n", response.body.len()));
+    }
+    head.push_str("Connection: close\r\n");
+    if let Some(ct) = response.content_type {
+        head.push_str(&format!("Content-Type: {}\r\n", ct));
+    }
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()?;
+    if let Some(follow) = &response.follow {
+        follow_up(stream, follow)?;
+    }
+    Ok(())
+}
+
+/// Keep writing what the session sends until the held-back call is answered
+/// (or the user takes too long).
+fn follow_up(stream: &mut impl Write, follow: &FollowUp) -> anyhow::Result<()> {
+    let deadline = Instant::now() + CONFIRMATION_TIMEOUT;
+    let mut session_state = follow.session.state.lock().unwrap();
+    loop {
+        for message in session_state.take_outgoing() {
+            stream.write_all(sse_event(&message).as_bytes())?;
+        }
+        if let Some(i) = session_state.responses.iter().position(|r| r.get("id") == Some(&follow.call_id)) {
+            let response = session_state.responses.remove(i);
+            stream.write_all(sse_event(&response).as_bytes())?;
+            stream.flush()?;
+            return Ok(());
+        }
+        stream.flush()?;
+
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Ok(());
+        }
+        session_state = follow.session.changed.wait_timeout(session_state, left).unwrap().0;
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Content Too Large",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Read;
+
+    /// Start a server on an ephemeral localhost port and return its address.
+    fn start(project: PathBuf, token: Option<&str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let state = Arc::new(HttpState::new(project, token.map(String::from)));
+        std::thread::spawn(move || serve(listener, state));
+        addr
+    }
+
+    /// Like `start`, with `configure` applied to the state first.
+    fn start_with(project: PathBuf, configure: impl FnOnce(&mut HttpState)) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let mut state = HttpState::new(project, None);
+        configure(&mut state);
+        std::thread::spawn(move || serve(listener, Arc::new(state)));
+        addr
+    }
+
+    /// Like `start`, with `known` standing in for the discovered projects.
+    fn start_with_known(project: PathBuf, known: Vec<PathBuf>) -> String {
+        start_with(project, |state| state.known_projects = Box::new(move || known.clone()))
+    }
+
+    /// Send a raw HTTP request and return (status, headers, body).
+    fn send(addr: &str, method: &str, headers: &[(&str, &str)], body: &str) -> (u16, String, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let mut req = format!("{} /mcp HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\n", method, addr, body.len());
+        for (k, v) in headers {
+            req.push_str(&format!("{}: {}\r\n", k, v));
+        }
+        req.push_str("\r\n");
+        req.push_str(body);
+        stream.write_all(req.as_bytes()).unwrap();
+
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).unwrap();
+        let (head, body) = raw.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, head.to_string(), body.to_string())
+    }
+
+    fn session_id(head: &str) -> String {
+        head.lines()
+            .find_map(|l| l.strip_prefix("Mcp-Session-Id: "))
+            .unwrap()
+            .to_string()
+    }
+
+    const INITIALIZE: &str = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#;
+
+    #[test]
+    fn initialize_assigns_session_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let addr = start(dir.path().to_path_buf(), None);
+
+        let (status, head, body) = send(&addr, "POST", &[("Accept", "application/json")], INITIALIZE);
+        assert_eq!(status, 200);
+        assert!(!session_id(&head).is_empty());
+        assert!(body.contains("\"protocolVersion\""));
+    }
+
+    #[test]
+    fn requests_without_session_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let addr = start(dir.path().to_path_buf(), None);
+
+        let list = r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#;
+        let (status, _, _) = send(&addr, "POST", &[], list);
+        assert_eq!(status, 400);
+
+        let (status, _, _) = send(&addr, "POST", &[("Mcp-Session-Id", "bogus")], list);
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn bearer_token_is_required() {
+        let dir = tempfile::tempdir().unwrap();
+        let addr = start(dir.path().to_path_buf(), Some("s3cret"));
+
+        let (status, _, _) = send(&addr, "POST", &[], INITIALIZE);
+        assert_eq!(status, 401);
+
+        let (status, _, _) = send(&addr, "POST", &[("Authorization", "Bearer wrong")], INITIALIZE);
+        assert_eq!(status, 401);
+
+        let (status, _, _) = send(&addr, "POST", &[("Authorization", "Bearer s3cret")], INITIALIZE);
+        assert_eq!(status, 200);
+    }
+
+    #[test]
+    fn foreign_origin_is_forbidden() {
+        let dir = tempfile::tempdir().unwrap();
+        let addr = start(dir.path().to_path_buf(), None);
+
+        let (status, _, _) = send(&addr, "POST", &[("Origin", "https://evil.example")], INITIALIZE);
+        assert_eq!(status, 403);
+
+        let (status, _, _) = send(&addr, "POST", &[("Origin", "http://localhost:3000")], INITIALIZE);
+        assert_eq!(status, 200);
+    }
+
+    #[test]
+    fn tool_call_streams_sse_for_selected_project() {
+        let default = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let addr = start_with_known(default.path().to_path_buf(), vec![other.path().to_path_buf()]);
+
+        let (_, head, _) = send(&addr, "POST", &[], INITIALIZE);
+        let sid = session_id(&head);
+        let project = other.path().to_str().unwrap();
+
+        let log = r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"log_note","arguments":{"content":"routed by header"}}}"#;
+        let headers = [
+            ("Mcp-Session-Id", sid.as_str()),
+            ("Accept", "application/json, text/event-stream"),
+            ("X-Claude-Memory-Project", project),
+        ];
+        let (status, head, body) = send(&addr, "POST", &headers, log);
+        assert_eq!(status, 200);
+        assert!(head.contains("Content-Type: text/event-stream"));
+        assert!(body.starts_with("event: message\ndata: "));
+        assert!(body.contains("Note saved"));
+
+        // The note landed in the selected project, not the default one
+        assert!(config::db_path(other.path()).unwrap().exists());
+        assert!(!config::db_path(default.path()).unwrap().exists());
+    }
+
+    #[test]
+    fn unknown_projects_cannot_be_selected() {
+        let default = tempfile::tempdir().unwrap();
+        let stranger = tempfile::tempdir().unwrap();
+        let addr = start_with_known(default.path().to_path_buf(), Vec::new());
+
+        let (_, head, _) = send(&addr, "POST", &[], INITIALIZE);
+        let sid = session_id(&head);
+        let log = r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"log_note","arguments":{"content":"planted"}}}"#;
+        let headers = [("Mcp-Session-Id", sid.as_str()), ("X-Claude-Memory-Project", stranger.path().to_str().unwrap())];
+        let (status, _, _) = send(&addr, "POST", &headers, log);
+        assert_eq!(status, 403);
+        assert!(!config::db_path(stranger.path()).unwrap().exists());
+    }
+
+    #[test]
+    fn forget_is_confirmed_through_elicitation_across_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let addr = start(dir.path().to_path_buf(), None);
+
+        let init = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{"elicitation":{}}}}"#;
+        let (_, head, _) = send(&addr, "POST", &[], init);
+        let sid = session_id(&head);
+        let headers = [("Mcp-Session-Id", sid.as_str()), ("Accept", "application/json, text/event-stream")];
+
+        let log = r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"log_note","arguments":{"content":"temporary api workaround"}}}"#;
+        send(&addr, "POST", &headers, log);
+
+        // The forget call's stream asks first and stays open for the answer
+        let forget = r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"forget","arguments":{"query":"workaround"}}}"#;
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        let mut req = format!("POST /mcp HTTP/1.1\r\nContent-Length: {}\r\n", forget.len());
+        for (k, v) in headers {
+            req.push_str(&format!("{}: {}\r\n", k, v));
+        }
+        stream.write_all(format!("{}\r\n{}", req, forget).as_bytes()).unwrap();
+        let mut reader = BufReader::new(stream);
+        let elicitation: Value = loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(data) = line.strip_prefix("data: ") {
+                break serde_json::from_str(data).unwrap();
+            }
+        };
+        assert_eq!(elicitation["method"], "elicitation/create");
+
+        let answer = json!({ "jsonrpc": "2.0", "id": elicitation["id"], "result": { "action": "accept" } });
+        let (status, _, _) = send(&addr, "POST", &headers, &answer.to_string());
+        assert_eq!(status, 202);
+
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        let data = rest.lines().find_map(|l| l.strip_prefix("data: ")).unwrap();
+        let response: Value = serde_json::from_str(data).unwrap();
+        assert_eq!(response["id"], 3);
+        assert!(response["result"]["content"][0]["text"].is_string());
+
+        let conn = crate::db::open(&config::db_path(dir.path()).unwrap()).unwrap();
+        assert_eq!(crate::db::notes::note_count(&conn).unwrap(), 0);
+    }
+
+    #[test]
+    fn notifications_are_accepted_and_sessions_can_be_closed() {
+        let dir = tempfile::tempdir().unwrap();
+        let addr = start(dir.path().to_path_buf(), None);
+
+        let (_, head, _) = send(&addr, "POST", &[], INITIALIZE);
+        let sid = session_id(&head);
+
+        let note = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        let (status, _, body) = send(&addr, "POST", &[("Mcp-Session-Id", &sid)], note);
+        assert_eq!(status, 202);
+        assert!(body.is_empty());
+
+        let (status, _, _) = send(&addr, "DELETE", &[("Mcp-Session-Id", &sid)], "");
+        assert_eq!(status, 204);
+
+        let (status, _, _) = send(&addr, "POST", &[("Mcp-Session-Id", &sid)], note);
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn non_loopback_addresses_need_a_token() {
+        let any: SocketAddr = "0.0.0.0:8765".parse().unwrap();
+        assert!(check_exposure(any, None).is_err());
+        assert!(check_exposure(any, Some("s3cret")).is_ok());
+        assert!(check_exposure("127.0.0.1:8765".parse().unwrap(), None).is_ok());
+        assert!(check_exposure("[::1]:8765".parse().unwrap(), None).is_ok());
+    }
+
+    #[test]
+    fn oversized_requests_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let addr = start(dir.path().to_path_buf(), None);
+
+        let long = "x".repeat(MAX_LINE_LEN);
+        let (status, _, _) = send(&addr, "POST", &[("X-Long", &long)], INITIALIZE);
+        assert_eq!(status, 431);
+
+        let names: Vec<String> = (0..=MAX_HEADERS).map(|i| format!("X-Header-{}", i)).collect();
+        let many: Vec<(&str, &str)> = names.iter().map(|n| (n.as_str(), "1")).collect();
+        let (status, _, _) = send(&addr, "POST", &many, INITIALIZE);
+        assert_eq!(status, 431);
+
+        let (status, _, _) = send(&addr, "POST", &[("Accept", "application/json")], INITIALIZE);
+        assert_eq!(status, 200);
+    }
+
+    #[test]
+    fn connections_over_the_limit_are_turned_away() {
+        let dir = tempfile::tempdir().unwrap();
+        let addr = start_with(dir.path().to_path_buf(), |state| state.max_connections = 1);
+
+        let _open = TcpStream::connect(&addr).unwrap();
+        let mut raw = String::new();
+        TcpStream::connect(&addr).unwrap().read_to_string(&mut raw).unwrap();
+        assert!(raw.starts_with("HTTP/1.1 503 "), "{}", raw);
+    }
+
+    #[test]
+    fn idle_sessions_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let addr = start_with(dir.path().to_path_buf(), |state| state.session_idle_timeout = Duration::ZERO);
+
+        let (_, head, _) = send(&addr, "POST", &[("Accept", "application/json")], INITIALIZE);
+        let sid = session_id(&head);
+        let list = r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#;
+        let (status, _, _) = send(&addr, "POST", &[("Mcp-Session-Id", &sid)], list);
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn percent_decoding() {
+        assert_eq!(percent_decode("%2Fhome%2Fme%20x"), "/home/me x");
+        assert_eq!(percent_decode("c++"), "c++");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%+1%-f%zz"), "%+1%-f%zz");
+        assert_eq!(parse_query("project=%2Fhome%2Fme+x")["project"], "/home/me x");
+    }
+}
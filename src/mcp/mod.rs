@@ -1,2 +1,5 @@
+pub mod budget;
+pub mod http;
+pub mod resources;
 pub mod server;
 pub mod tools;
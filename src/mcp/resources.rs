@@ -0,0 +1,101 @@
+//! MCP resources and argument completion.
+//!
+//! Sessions and notes are exposed as resource templates so clients can open
+//! them directly and autocomplete their IDs (and note tags) via
+//! `completion/complete`.
+
+use rusqlite::Connection;
+use serde_json::{json, Value};
+
+use crate::db::{notes, sessions};
+use crate::mcp::budget::DETAIL_LEVELS;
+use crate::mcp::http::percent_decode;
+use crate::mcp::tools;
+
+const SESSION_PREFIX: &str = "memory://session/";
+const NOTE_PREFIX: &str = "memory://note/";
+const TAG_PREFIX: &str = "memory://notes/tag/";
+
+/// Most notes listed for a tag resource.
+const MAX_TAG_NOTES: usize = 100;
+
+/// Most values returned by one completion request (the MCP maximum).
+const MAX_COMPLETIONS: usize = 100;
+
+/// Return all resource templates for MCP resources/templates/list.
+pub fn resource_templates() -> Vec<Value> {
+    vec![
+        json!({
+            "uriTemplate": format!("{}{{session_id}}", SESSION_PREFIX),
+            "name": "session",
+            "description": "Full record of a past session (ID or unique ID prefix)",
+            "mimeType": "text/markdown"
+        }),
+        json!({
+            "uriTemplate": format!("{}{{note_id}}", NOTE_PREFIX),
+            "name": "note",
+            "description": "A single note (ID or unique ID prefix)",
+            "mimeType": "text/markdown"
+        }),
+        json!({
+            "uriTemplate": format!("{}{{tag}}", TAG_PREFIX),
+            "name": "notes-by-tag",
+            "description": "All notes with a given tag",
+            "mimeType": "text/markdown"
+        }),
+    ]
+}
+
+/// Read a resource. Returns `None` for URIs this server doesn't serve.
+pub fn read(uri: &str, conn: &Connection) -> anyhow::Result<Option<String>> {
+    if let Some(prefix) = uri.strip_prefix(SESSION_PREFIX) {
+        let id = sessions::resolve_session_id(conn, &percent_decode(prefix))?;
+        let session = sessions::get_session(conn, &id)?
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", id))?;
+        return Ok(Some(tools::format_session_detail(&session, &DETAIL_LEVELS[0])));
+    }
+
+    if let Some(prefix) = uri.strip_prefix(NOTE_PREFIX) {
+        let id = notes::resolve_note_id(conn, &percent_decode(prefix))?;
+        let note = notes::get_note(conn, &id)?
+            .ok_or_else(|| anyhow::anyhow!("Note not found: {}", id))?;
+        return Ok(Some(tools::format_note(&note, &DETAIL_LEVELS[0])));
+    }
+
+    if let Some(tag) = uri.strip_prefix(TAG_PREFIX) {
+        let tag = percent_decode(tag);
+        let results = notes::search_notes(conn, None, Some(&tag), MAX_TAG_NOTES)?;
+        let mut out = format!("# {} Note(s) tagged {}\n\n", results.len(), tag);
+        for note in &results {
+            out.push_str(&tools::format_note(note, &DETAIL_LEVELS[0]));
+        }
+        return Ok(Some(out));
+    }
+
+    Ok(None)
+}
+
+/// Complete an argument value. Arguments are matched by name, so the same
+/// completions apply to resource templates and tool-like arguments alike.
+pub fn complete(argument: &str, value: &str, conn: &Connection) -> anyhow::Result<Vec<String>> {
+    // Fetch one extra so callers can tell whether there are more
+    let limit = MAX_COMPLETIONS + 1;
+    match argument {
+        "session_id" => sessions::complete_session_ids(conn, value, limit),
+        "note_id" => notes::complete_note_ids(conn, value, limit),
+        "tag" => notes::complete_tags(conn, value, limit),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Build the `completion` result object from raw candidate values.
+pub fn completion_result(mut values: Vec<String>) -> Value {
+    let has_more = values.len() > MAX_COMPLETIONS;
+    values.truncate(MAX_COMPLETIONS);
+    json!({
+        "completion": {
+            "values": values,
+            "hasMore": has_more
+        }
+    })
+}
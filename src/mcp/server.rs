@@ -1,62 +1,303 @@
+use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::config;
-use crate::mcp::tools;
+use crate::mcp::{resources, tools};
 
 #[derive(Debug, Deserialize)]
 struct JsonRpcRequest {
     #[allow(dead_code)]
     jsonrpc: String,
     id: Option<Value>,
-    method: String,
+    /// Absent when the message is a response to a server-initiated request.
+    method: Option<String>,
     #[serde(default)]
     params: Option<Value>,
+    #[serde(default)]
+    result: Option<Value>,
 }
 
 #[derive(Debug, Serialize)]
-struct JsonRpcResponse {
+pub struct JsonRpcResponse {
     jsonrpc: String,
-    id: Value,
+    pub id: Value,
     #[serde(skip_serializing_if = "Option::is_none")]
     result: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    error: Option<JsonRpcError>,
+    pub error: Option<JsonRpcError>,
 }
 
 #[derive(Debug, Serialize)]
-struct JsonRpcError {
+pub struct JsonRpcError {
     code: i32,
     message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     data: Option<Value>,
 }
 
-struct MemoryServer {
-    db_path: PathBuf,
+/// An open project database, kept across tool calls.
+struct CachedConnection {
+    conn: rusqlite::Connection,
+    /// Identity of the file when it was opened, to notice it being replaced.
+    identity: Option<FileIdentity>,
+}
+
+/// A `forget` call held back while the user is asked to approve it.
+struct PendingConfirmation {
+    elicitation_id: Value,
+    call_id: Value,
+    params: Value,
+}
+
+pub struct MemoryServer {
+    /// Project used when the client reports no roots.
+    default_project: PathBuf,
+    /// Project roots from the client's `roots/list`, in client order.
+    roots: Vec<PathBuf>,
+    /// Where the agent works: the first root as the client reported it, else
+    /// the server's working directory. Picks the monorepo scope tools default to.
+    workspace: PathBuf,
+    client_supports_roots: bool,
+    pending_roots_request: Option<Value>,
+    /// Whether the client can ask the user questions (`elicitation/create`).
+    client_supports_elicitation: bool,
+    /// Whether a request sent now reaches the client. Over HTTP only while
+    /// answering a POST with an event stream.
+    can_send_requests: bool,
+    /// Project chosen by the transport for the current request (HTTP's
+    /// `X-Claude-Memory-Project`); takes precedence over the roots.
+    pinned_project: Option<PathBuf>,
+    pending_confirmation: Option<PendingConfirmation>,
+    next_request_id: u64,
+    /// Server-initiated messages waiting to be written to the client.
+    outgoing: Vec<Value>,
+    /// Long-lived read-write connections, keyed by database path.
+    connections: HashMap<PathBuf, CachedConnection>,
+    /// Database path of each project, so settings aren't re-read on every call.
+    db_paths: HashMap<PathBuf, PathBuf>,
+    /// The user's global memory; `None` without a home directory.
+    global_db: Option<PathBuf>,
 }
 
 impl MemoryServer {
-    fn new() -> anyhow::Result<Self> {
+    pub fn new() -> anyhow::Result<Self> {
         let project_dir = config::detect_project_dir()?;
-        let db_path = config::db_path(&project_dir);
-        Ok(Self { db_path })
+        let mut server = Self::for_project(&project_dir);
+        server.workspace = std::env::current_dir()?;
+        Ok(server)
+    }
+
+    /// Serve the memory database of a specific project.
+    pub fn for_project(project_dir: &Path) -> Self {
+        Self {
+            default_project: project_dir.to_path_buf(),
+            roots: Vec::new(),
+            workspace: project_dir.to_path_buf(),
+            client_supports_roots: false,
+            pending_roots_request: None,
+            client_supports_elicitation: false,
+            can_send_requests: true,
+            pinned_project: None,
+            pending_confirmation: None,
+            next_request_id: 1,
+            outgoing: Vec::new(),
+            connections: HashMap::new(),
+            db_paths: HashMap::new(),
+            global_db: config::global_db_path().ok(),
+        }
+    }
+
+    /// Pick the project a tool call operates on.
+    ///
+    /// An explicit `project` (root name or path) must match one of the client's
+    /// roots. Without one, the first root is used, falling back to the project
+    /// detected at startup when the client reported no roots.
+    fn select_project(&self, requested: Option<&str>) -> Result<PathBuf, JsonRpcError> {
+        let fallback = self.pinned_project.as_ref().or(self.roots.first()).unwrap_or(&self.default_project);
+        let requested = match requested {
+            Some(r) => r,
+            None => return Ok(fallback.clone()),
+        };
+
+        let mut candidates: Vec<&PathBuf> = self.pinned_project.iter().chain(&self.roots).collect();
+        if candidates.is_empty() {
+            candidates.push(&self.default_project);
+        }
+
+        let resolved = Path::new(requested)
+            .is_dir()
+            .then(|| config::find_project_root(Path::new(requested)));
+
+        candidates
+            .iter()
+            .find(|root| {
+                resolved.as_ref() == Some(**root)
+                    || root.file_name().is_some_and(|n| n == requested)
+            })
+            .map(|root| (*root).clone())
+            .ok_or_else(|| {
+                let names: Vec<String> = candidates.iter().map(|r| r.display().to_string()).collect();
+                JsonRpcError {
+                    code: -32602,
+                    message: format!(
+                        "Unknown project: {} (available: {})",
+                        requested,
+                        names.join(", ")
+                    ),
+                    data: None,
+                }
+            })
     }
 
-    fn open_db(&self) -> Result<rusqlite::Connection, JsonRpcError> {
-        crate::db::open(&self.db_path).map_err(|e| JsonRpcError {
+    /// Use `project` for the following calls instead of the roots, or go back
+    /// to the roots with `None`.
+    pub fn pin_project(&mut self, project: Option<PathBuf>) {
+        self.pinned_project = project;
+    }
+
+    /// Whether server-initiated requests (roots, confirmations) can be sent
+    /// to the client right now.
+    pub fn set_can_send_requests(&mut self, can_send: bool) {
+        self.can_send_requests = can_send;
+    }
+
+    /// Where a project's database lives, resolved on first use. A path whose
+    /// file has disappeared (e.g. `relocate` removed it) is resolved again.
+    fn db_path(&mut self, project_dir: &Path) -> Result<PathBuf, JsonRpcError> {
+        if let Some(db_path) = self.db_paths.get(project_dir).filter(|p| p.exists()) {
+            return Ok(db_path.clone());
+        }
+        let db_path = config::db_path(project_dir).map_err(|e| JsonRpcError {
             code: -32603,
-            message: format!("Failed to open database: {}", e),
+            message: format!("Failed to locate the database: {:#}", e),
             data: None,
+        })?;
+        self.db_paths.insert(project_dir.to_path_buf(), db_path.clone());
+        Ok(db_path)
+    }
+
+    /// Get the connection for a project, reopening it if the database file
+    /// was replaced (e.g. restored from a backup) since it was opened.
+    fn connection(&mut self, project_dir: &Path) -> Result<&rusqlite::Connection, JsonRpcError> {
+        let db_path = self.db_path(project_dir)?;
+        let current = file_identity(&db_path);
+
+        let stale = match self.connections.get(&db_path) {
+            Some(cached) => current.is_none() || cached.identity != current,
+            None => true,
+        };
+
+        if stale {
+            let conn = crate::db::open(&db_path).map_err(|e| JsonRpcError {
+                code: -32603,
+                message: format!("Failed to open database: {}", e),
+                data: None,
+            })?;
+            let identity = file_identity(&db_path);
+            self.connections
+                .insert(db_path.clone(), CachedConnection { conn, identity });
+        }
+
+        Ok(&self.connections[&db_path].conn)
+    }
+
+    /// Whether the file the project's cached connection has open was removed
+    /// or replaced since it was opened. Settings edits alone don't count.
+    fn moved(&self, project_dir: &Path) -> bool {
+        self.db_paths.get(project_dir).is_some_and(|db_path| {
+            self.connections
+                .get(db_path)
+                .is_some_and(|cached| cached.identity != file_identity(db_path))
         })
     }
+
+    /// Drop a cached connection so the next call starts fresh.
+    fn discard_connection(&mut self, project_dir: &Path) {
+        if let Some(db_path) = self.db_paths.remove(project_dir) {
+            self.connections.remove(&db_path);
+        }
+    }
+
+    /// Queue a `roots/list` request to the client.
+    fn request_roots(&mut self) {
+        let id = json!(format!("roots-{}", self.next_request_id));
+        self.next_request_id += 1;
+        self.pending_roots_request = Some(id.clone());
+        self.outgoing.push(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "roots/list"
+        }));
+    }
+
+    /// Ask the user to approve a `forget` call that would delete something,
+    /// deferring the call's response until they answer. Returns false when the
+    /// call should run now instead: the client cannot be asked, the call is a
+    /// dry run or already confirmed, or nothing matches.
+    fn ask_to_confirm(&mut self, call_id: &Value, params: &Option<Value>) -> bool {
+        if !self.client_supports_elicitation || !self.can_send_requests || self.pending_confirmation.is_some() {
+            return false;
+        }
+        let params = match params {
+            Some(p) if p.get("name").and_then(|n| n.as_str()) == Some("forget") => p,
+            _ => return false,
+        };
+        let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+        let flag = |key: &str| args.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+        if flag("dry_run") || flag("confirm") {
+            return false;
+        }
+
+        // Errors and empty selections are reported by the call itself
+        let dir = match self.select_project(args.get("project").and_then(|p| p.as_str())) {
+            Ok(d) => d,
+            Err(_) => return false,
+        };
+        let global_db = self.global_db.clone();
+        let selection = match self.connection(&dir).map(|conn| tools::forget_selection(&args, conn, global_db.as_deref())) {
+            Ok(Ok(s)) if !s.is_empty() => s,
+            _ => return false,
+        };
+        let from = match tools::forgets_global(&args) {
+            Ok(true) => "your global notes".to_string(),
+            _ => dir.display().to_string(),
+        };
+
+        let id = json!(format!("elicit-{}", self.next_request_id));
+        self.next_request_id += 1;
+        self.outgoing.push(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "elicitation/create",
+            "params": {
+                "message": format!(
+                    "Permanently forget these from {}?\n\n{}",
+                    from,
+                    selection.describe()
+                ),
+                "requestedSchema": { "type": "object", "properties": {} }
+            }
+        }));
+        self.pending_confirmation = Some(PendingConfirmation {
+            elicitation_id: id,
+            call_id: call_id.clone(),
+            params: params.clone(),
+        });
+        true
+    }
+
+    /// Take server-initiated messages that should be sent to the client.
+    pub fn drain_outgoing(&mut self) -> Vec<Value> {
+        std::mem::take(&mut self.outgoing)
+    }
 }
 
 pub fn run() -> anyhow::Result<()> {
-    let server = MemoryServer::new()?;
+    let mut server = MemoryServer::new()?;
 
     let stdin = std::io::stdin();
     let mut stdout = std::io::stdout();
@@ -72,18 +313,21 @@ pub fn run() -> anyhow::Result<()> {
             continue;
         }
 
-        let response = handle_request(&server, &line);
+        let response = handle_request(&mut server, &line);
         if let Some(resp) = response {
             let resp_str = serde_json::to_string(&resp)?;
             writeln!(stdout, "{}", resp_str)?;
-            stdout.flush()?;
         }
+        for message in server.drain_outgoing() {
+            writeln!(stdout, "{}", serde_json::to_string(&message)?)?;
+        }
+        stdout.flush()?;
     }
 
     Ok(())
 }
 
-fn handle_request(server: &MemoryServer, line: &str) -> Option<JsonRpcResponse> {
+pub fn handle_request(server: &mut MemoryServer, line: &str) -> Option<JsonRpcResponse> {
     let request: JsonRpcRequest = match serde_json::from_str(line) {
         Ok(r) => r,
         Err(e) => {
@@ -100,22 +344,48 @@ fn handle_request(server: &MemoryServer, line: &str) -> Option<JsonRpcResponse>
         }
     };
 
-    let id = request.id.as_ref()?.clone();
+    let method = match request.method.as_deref() {
+        Some(m) => m,
+        None => {
+            handle_client_response(server, &request);
+            return None;
+        }
+    };
+
+    let id = match &request.id {
+        Some(id) => id.clone(),
+        None => {
+            handle_notification(server, method);
+            return None;
+        }
+    };
 
-    let result = match request.method.as_str() {
-        "initialize" => handle_initialize(),
-        "initialized" => return None,
-        "notifications/initialized" => return None,
+    if method == "tools/call" && server.ask_to_confirm(&id, &request.params) {
+        return None;
+    }
+
+    let result = match method {
+        "initialize" => handle_initialize(server, &request.params),
         "tools/list" => handle_list_tools(),
         "tools/call" => handle_call_tool(server, &request.params),
+        "resources/list" => Ok(json!({ "resources": [] })),
+        "resources/templates/list" => Ok(json!({
+            "resourceTemplates": resources::resource_templates()
+        })),
+        "resources/read" => handle_read_resource(server, &request.params),
+        "completion/complete" => handle_complete(server, &request.params),
         _ => Err(JsonRpcError {
             code: -32601,
-            message: format!("Method not found: {}", request.method),
+            message: format!("Method not found: {}", method),
             data: None,
         }),
     };
 
-    Some(match result {
+    Some(response_for(id, result))
+}
+
+fn response_for(id: Value, result: Result<Value, JsonRpcError>) -> JsonRpcResponse {
+    match result {
         Ok(value) => JsonRpcResponse {
             jsonrpc: "2.0".to_string(),
             id,
@@ -128,14 +398,216 @@ fn handle_request(server: &MemoryServer, line: &str) -> Option<JsonRpcResponse>
             result: None,
             error: Some(error),
         },
+    }
+}
+
+fn handle_notification(server: &mut MemoryServer, method: &str) {
+    let wants_roots = matches!(
+        method,
+        "initialized" | "notifications/initialized" | "notifications/roots/list_changed"
+    );
+    if wants_roots && server.client_supports_roots {
+        server.request_roots();
+    }
+}
+
+/// Handle the client's answer to a server-initiated request.
+fn handle_client_response(server: &mut MemoryServer, response: &JsonRpcRequest) {
+    let answers_confirmation = |p: &mut PendingConfirmation| response.id.as_ref() == Some(&p.elicitation_id);
+    if let Some(pending) = server.pending_confirmation.take_if(answers_confirmation) {
+        finish_confirmation(server, pending, response);
+        return;
+    }
+
+    if response.id.is_none() || response.id != server.pending_roots_request {
+        return;
+    }
+    server.pending_roots_request = None;
+
+    // An error result leaves the previous roots in place
+    let roots = match response.result.as_ref().and_then(|r| r.get("roots")).and_then(|r| r.as_array()) {
+        Some(r) => r,
+        None => return,
+    };
+
+    let mut projects: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        let path = match root.get("uri").and_then(|u| u.as_str()).and_then(file_uri_to_path) {
+            Some(p) => p,
+            None => continue,
+        };
+        if projects.is_empty() {
+            server.workspace = path.clone();
+        }
+        let project = config::find_project_root(&path);
+        if !projects.contains(&project) {
+            projects.push(project);
+        }
+    }
+    server.roots = projects;
+}
+
+/// Complete a held-back `forget` call with the user's answer. Anything but an
+/// explicit accept (decline, cancel, or an error) deletes nothing.
+fn finish_confirmation(server: &mut MemoryServer, pending: PendingConfirmation, response: &JsonRpcRequest) {
+    let action = response
+        .result
+        .as_ref()
+        .and_then(|r| r.get("action"))
+        .and_then(|a| a.as_str())
+        .unwrap_or("cancel");
+
+    let result = if action == "accept" {
+        let mut params = pending.params;
+        params["arguments"]["confirm"] = json!(true);
+        handle_call_tool(server, &Some(params))
+    } else {
+        Ok(json!({
+            "content": [{
+                "type": "text",
+                "text": format!("The user did not approve ({}); nothing was deleted.", action)
+            }]
+        }))
+    };
+
+    let message = serde_json::to_value(response_for(pending.call_id, result)).expect("response serializes");
+    server.outgoing.push(message);
+}
+
+fn handle_read_resource(
+    server: &mut MemoryServer,
+    params: &Option<Value>,
+) -> Result<Value, JsonRpcError> {
+    let uri = params
+        .as_ref()
+        .and_then(|p| p.get("uri"))
+        .and_then(|u| u.as_str())
+        .ok_or_else(|| JsonRpcError {
+            code: -32602,
+            message: "Missing resource uri".to_string(),
+            data: None,
+        })?;
+
+    let project_dir = server.select_project(None)?;
+    let conn = server.connection(&project_dir)?;
+
+    let text = resources::read(uri, conn)
+        .map_err(|e| JsonRpcError {
+            code: -32602,
+            message: e.to_string(),
+            data: None,
+        })?
+        .ok_or_else(|| JsonRpcError {
+            code: -32002,
+            message: format!("Resource not found: {}", uri),
+            data: None,
+        })?;
+
+    Ok(json!({
+        "contents": [{
+            "uri": uri,
+            "mimeType": "text/markdown",
+            "text": text
+        }]
+    }))
+}
+
+fn handle_complete(
+    server: &mut MemoryServer,
+    params: &Option<Value>,
+) -> Result<Value, JsonRpcError> {
+    let argument = params
+        .as_ref()
+        .and_then(|p| p.get("argument"))
+        .ok_or_else(|| JsonRpcError {
+            code: -32602,
+            message: "Missing argument".to_string(),
+            data: None,
+        })?;
+    let name = argument.get("name").and_then(|n| n.as_str()).unwrap_or("");
+    let value = argument.get("value").and_then(|v| v.as_str()).unwrap_or("");
+
+    let project_dir = server.select_project(None)?;
+    let conn = server.connection(&project_dir)?;
+
+    let values = resources::complete(name, value, conn).map_err(|e| JsonRpcError {
+        code: -32603,
+        message: e.to_string(),
+        data: None,
+    })?;
+
+    Ok(resources::completion_result(values))
+}
+
+/// Open another project's memory database without ever writing to it. One
+/// written by an older version is read through a migrated copy.
+fn open_foreign_db(project_dir: &Path, spec: &str) -> Result<crate::db::CurrentSchema, JsonRpcError> {
+    let db_path = config::db_path(project_dir).map_err(|e| JsonRpcError {
+        code: -32603,
+        message: format!("Failed to locate the database: {:#}", e),
+        data: None,
+    })?;
+    if !db_path.is_file() {
+        return Err(JsonRpcError {
+            code: -32602,
+            message: format!("No memory database for project: {}", spec),
+            data: None,
+        });
+    }
+    crate::db::open_current(&db_path).map_err(|e| JsonRpcError {
+        code: -32603,
+        message: format!("Failed to open database: {}", e),
+        data: None,
     })
 }
 
-fn handle_initialize() -> Result<Value, JsonRpcError> {
+/// Identifies a file on disk independently of its path.
+#[cfg(unix)]
+type FileIdentity = (u64, u64);
+#[cfg(not(unix))]
+type FileIdentity = std::time::SystemTime;
+
+#[cfg(unix)]
+fn file_identity(path: &Path) -> Option<FileIdentity> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(path: &Path) -> Option<FileIdentity> {
+    std::fs::metadata(path).and_then(|m| m.created()).ok()
+}
+
+/// Convert a `file://` URI into a local path. Other schemes are ignored.
+fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // Drop an optional authority such as `localhost`
+    let path = &rest[rest.find('/')?..];
+    Some(PathBuf::from(super::http::percent_decode(path)))
+}
+
+fn handle_initialize(
+    server: &mut MemoryServer,
+    params: &Option<Value>,
+) -> Result<Value, JsonRpcError> {
+    server.client_supports_roots = params
+        .as_ref()
+        .and_then(|p| p.get("capabilities"))
+        .and_then(|c| c.get("roots"))
+        .is_some();
+    server.client_supports_elicitation = params
+        .as_ref()
+        .and_then(|p| p.get("capabilities"))
+        .and_then(|c| c.get("elicitation"))
+        .is_some();
+
     Ok(json!({
         "protocolVersion": "2025-11-25",
         "capabilities": {
-            "tools": {}
+            "tools": {},
+            "resources": {},
+            "completions": {}
         },
         "serverInfo": {
             "name": "claude-memory",
@@ -151,7 +623,7 @@ fn handle_list_tools() -> Result<Value, JsonRpcError> {
 }
 
 fn handle_call_tool(
-    server: &MemoryServer,
+    server: &mut MemoryServer,
     params: &Option<Value>,
 ) -> Result<Value, JsonRpcError> {
     let params = params.as_ref().ok_or_else(|| JsonRpcError {
@@ -174,9 +646,41 @@ fn handle_call_tool(
         .cloned()
         .unwrap_or_else(|| json!({}));
 
-    let conn = server.open_db()?;
+    let requested = args.get("project").and_then(|p| p.as_str());
+    let workspace = server.workspace.clone();
+    let global_db = server.global_db.clone();
+    let result = match server.select_project(requested) {
+        Ok(dir) => {
+            let conn = server.connection(&dir)?;
+            let mut result = tools::dispatch(name, &args, conn, &dir, &workspace, global_db.as_deref());
+            // `relocate` moved the database while the call waited for its lock.
+            // A read can simply run again on the new file; a write went to the
+            // old one, and repeating it unasked could apply it twice
+            if server.moved(&dir) {
+                server.discard_connection(&dir);
+                result = if tools::READ_ONLY_TOOLS.contains(&name) {
+                    let conn = server.connection(&dir)?;
+                    tools::dispatch(name, &args, conn, &dir, &workspace, global_db.as_deref())
+                } else {
+                    Err(anyhow::anyhow!("The memory database was relocated during the call; retry it"))
+                };
+            }
+            if result.is_err() {
+                server.discard_connection(&dir);
+            }
+            result
+        }
+        // Read-only tools may look into any discovered project, not just the roots
+        Err(e) if tools::READ_ONLY_TOOLS.contains(&name) => {
+            let spec = requested.unwrap_or_default();
+            let dir = config::resolve_project(spec).ok_or(e)?;
+            let conn = open_foreign_db(&dir, spec)?;
+            tools::dispatch(name, &args, &conn, &dir, &dir, global_db.as_deref())
+        }
+        Err(e) => return Err(e),
+    };
 
-    let result = tools::dispatch(name, &args, &conn).map_err(|e| JsonRpcError {
+    let result = result.map_err(|e| JsonRpcError {
         code: -32603,
         message: e.to_string(),
         data: None,
@@ -189,3 +693,618 @@ fn handle_call_tool(
         }]
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_with_roots(server: &mut MemoryServer) -> Value {
+        let init = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{"roots":{"listChanged":true}}}}"#;
+        assert!(handle_request(server, init).is_some());
+        assert!(handle_request(server, r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).is_none());
+
+        let outgoing = server.drain_outgoing();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0]["method"], "roots/list");
+        outgoing[0]["id"].clone()
+    }
+
+    fn answer_roots(server: &mut MemoryServer, id: &Value, dirs: &[&Path]) {
+        let roots: Vec<Value> = dirs
+            .iter()
+            .map(|d| json!({ "uri": format!("file://{}", d.display()) }))
+            .collect();
+        let response = json!({ "jsonrpc": "2.0", "id": id, "result": { "roots": roots } });
+        assert!(handle_request(server, &response.to_string()).is_none());
+    }
+
+    #[test]
+    fn roots_not_requested_without_client_capability() {
+        let mut server = MemoryServer::for_project(Path::new("/tmp"));
+        handle_request(&mut server, r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#);
+        handle_request(&mut server, r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#);
+        assert!(server.drain_outgoing().is_empty());
+        assert_eq!(server.select_project(None).unwrap(), PathBuf::from("/tmp"));
+    }
+
+    #[test]
+    fn first_root_replaces_startup_project() {
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        let mut server = MemoryServer::for_project(Path::new("/nonexistent"));
+
+        let id = init_with_roots(&mut server);
+        answer_roots(&mut server, &id, &[a.path(), b.path()]);
+
+        assert_eq!(server.select_project(None).unwrap(), a.path());
+        let b_name = b.path().file_name().unwrap().to_str().unwrap();
+        assert_eq!(server.select_project(Some(b_name)).unwrap(), b.path());
+        assert_eq!(server.select_project(b.path().to_str()).unwrap(), b.path());
+        assert!(server.select_project(Some("elsewhere")).is_err());
+    }
+
+    #[test]
+    fn list_changed_requests_roots_again() {
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        let mut server = MemoryServer::for_project(Path::new("/nonexistent"));
+
+        let id = init_with_roots(&mut server);
+        answer_roots(&mut server, &id, &[a.path()]);
+
+        handle_request(&mut server, r#"{"jsonrpc":"2.0","method":"notifications/roots/list_changed"}"#);
+        let outgoing = server.drain_outgoing();
+        assert_eq!(outgoing.len(), 1);
+        assert_ne!(outgoing[0]["id"], id);
+
+        // A stale response to the first request is ignored
+        answer_roots(&mut server, &id, &[b.path()]);
+        assert_eq!(server.select_project(None).unwrap(), a.path());
+
+        answer_roots(&mut server, &outgoing[0]["id"], &[b.path()]);
+        assert_eq!(server.select_project(None).unwrap(), b.path());
+    }
+
+    fn call_tool(server: &mut MemoryServer, name: &str, args: Value) -> JsonRpcResponse {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": { "name": name, "arguments": args }
+        });
+        handle_request(server, &request.to_string()).unwrap()
+    }
+
+    #[test]
+    fn read_tools_can_query_other_projects() {
+        let current = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        {
+            let conn = crate::db::open(&config::db_path(other.path()).unwrap()).unwrap();
+            crate::db::notes::insert_note(&conn, "decided on sqlite", &[], None).unwrap();
+        }
+        let mut server = MemoryServer::for_project(current.path());
+        let project = other.path().to_str().unwrap();
+
+        let response = call_tool(&mut server, "search_notes", json!({ "project": project }));
+        let text = response.result.unwrap()["content"][0]["text"].as_str().unwrap().to_string();
+        assert!(text.contains("decided on sqlite"));
+        assert!(!config::db_path(current.path()).unwrap().exists());
+
+        // Writing tools stay limited to the current roots
+        let response = call_tool(&mut server, "log_note", json!({ "content": "x", "project": project }));
+        assert!(response.error.is_some());
+    }
+
+    #[test]
+    fn other_projects_at_an_old_schema_are_read_through_a_migrated_copy() {
+        let current = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let db_path = config::db_path(other.path()).unwrap();
+        {
+            // A v1 database, as the first versioned release wrote it
+            let conn = crate::db::open(&db_path).unwrap();
+            conn.execute_batch(
+                "DROP TABLE cross_references;
+                 DROP TABLE project_info;
+                 DROP TABLE skipped_sessions;
+                 DROP INDEX idx_sessions_scope;
+                 ALTER TABLE sessions DROP COLUMN scope;
+                 ALTER TABLE sessions DROP COLUMN transcript_path;
+                 ALTER TABLE notes DROP COLUMN pinned;
+                 UPDATE schema_version SET version = 1;
+                 INSERT INTO sessions (id, project_dir, started_at, user_prompts)
+                 VALUES ('old-session', '/p', '2025-01-01', '[\"tune the cache\"]');
+                 INSERT INTO notes (id, content) VALUES ('old-note', 'cache stays in memory');",
+            )
+            .unwrap();
+        }
+        let before = std::fs::read(&db_path).unwrap();
+        let mut server = MemoryServer::for_project(current.path());
+        let project = other.path().to_str().unwrap();
+
+        for (tool, args) in [
+            ("recall", json!({ "query": "cache", "project": project })),
+            ("list_sessions", json!({ "project": project })),
+            ("search_notes", json!({ "query": "cache", "scope": "project", "project": project })),
+        ] {
+            let response = call_tool(&mut server, tool, args);
+            let text = response.result.unwrap()["content"][0]["text"].as_str().unwrap().to_string();
+            assert!(text.contains("old-session") || text.contains("cache stays"), "{}: {}", tool, text);
+        }
+        assert_eq!(std::fs::read(&db_path).unwrap(), before);
+    }
+
+    #[test]
+    fn unknown_foreign_project_is_an_error() {
+        let current = tempfile::tempdir().unwrap();
+        let empty = tempfile::tempdir().unwrap();
+        let mut server = MemoryServer::for_project(current.path());
+
+        let response = call_tool(&mut server, "list_sessions", json!({ "project": empty.path() }));
+        assert!(response.error.unwrap().message.contains("No memory database"));
+        assert!(!config::db_path(empty.path()).unwrap().exists());
+    }
+
+    #[test]
+    fn project_settings_set_default_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = MemoryServer::for_project(dir.path());
+        for i in 0..20 {
+            let content = format!("note {} {}", i, "lorem ipsum ".repeat(20));
+            call_tool(&mut server, "log_note", json!({ "content": content }));
+        }
+        std::fs::write(config::settings_path(dir.path()), "[output]\nmax_tokens = 200\n").unwrap();
+
+        let response = call_tool(&mut server, "search_notes", json!({ "limit": 20 }));
+        let text = response.result.unwrap()["content"][0]["text"].as_str().unwrap().to_string();
+        assert!(text.contains("more result(s) omitted"));
+
+        // An explicit argument overrides the project default
+        let response = call_tool(&mut server, "search_notes", json!({ "limit": 20, "max_tokens": 0 }));
+        let text = response.result.unwrap()["content"][0]["text"].as_str().unwrap().to_string();
+        assert!(!text.contains("trimmed"));
+    }
+
+    #[test]
+    fn cross_references_share_the_token_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let conn = crate::db::open(&config::db_path(dir.path()).unwrap()).unwrap();
+            let files: Vec<String> = (0..20).map(|i| format!("/work/lib/src/module_{}.rs", i)).collect();
+            for i in 0..5 {
+                let xref = crate::db::crossrefs::CrossReference {
+                    session_id: format!("session-{}", i),
+                    source_project: "/work/app".to_string(),
+                    started_at: format!("2026-01-0{}", i + 1),
+                    prompt: Some("rework the parser so that it accepts trailing commas everywhere".repeat(3)),
+                    files: serde_json::to_string(&files).unwrap(),
+                };
+                crate::db::crossrefs::record_cross_reference(&conn, &xref).unwrap();
+            }
+        }
+        let mut server = MemoryServer::for_project(dir.path());
+        let mut list = |args: Value| {
+            let response = call_tool(&mut server, "list_sessions", args);
+            response.result.unwrap()["content"][0]["text"].as_str().unwrap().to_string()
+        };
+
+        let full = list(json!({ "verbosity": "full", "max_tokens": 0 }));
+        assert_eq!(full.matches("from /work/app").count(), 5);
+        assert!(full.contains("module_4.rs"));
+
+        let brief = list(json!({ "verbosity": "brief", "max_tokens": 0 }));
+        assert!(!brief.contains("module_4.rs"));
+
+        let tight = list(json!({ "verbosity": "full", "max_tokens": 200 }));
+        assert!(tight.contains("Edited from other projects"));
+        assert!(tight.matches("from /work/app").count() < 5);
+    }
+
+    fn text_of(response: JsonRpcResponse) -> String {
+        response.result.unwrap()["content"][0]["text"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn global_notes_are_created_on_first_use() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let global_db = home.path().join(".claude").join("memory-global.db");
+        let mut server = MemoryServer::for_project(dir.path());
+        server.global_db = Some(global_db.clone());
+
+        let text = text_of(call_tool(&mut server, "global_notes", json!({})));
+        assert!(text.starts_with("No global notes yet"));
+        assert!(!global_db.exists());
+
+        let text = text_of(call_tool(&mut server, "log_note", json!({ "content": "prefer rg over grep", "scope": "global" })));
+        assert!(text.starts_with("Global note saved"));
+        assert_eq!(note_count(dir.path()), 0);
+
+        let id = text.trim_end_matches(')').rsplit(' ').next().unwrap().to_string();
+        let text = text_of(call_tool(&mut server, "global_notes", json!({})));
+        assert!(text.contains("prefer rg over grep"));
+        assert!(text.contains(&format!("`{}`", id)));
+        let response = call_tool(&mut server, "log_note", json!({ "content": "x", "scope": "all" }));
+        assert!(response.error.is_some());
+    }
+
+    #[test]
+    fn search_notes_merges_project_and_global_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let global_db = home.path().join("memory-global.db");
+        let add = |conn: &rusqlite::Connection, content: &str, created_at: &str| {
+            let id = crate::db::notes::insert_note(conn, content, &[], None).unwrap();
+            conn.execute("UPDATE notes SET created_at = ?2 WHERE id = ?1", [&id, created_at]).unwrap();
+        };
+        {
+            let conn = crate::db::open(&config::db_path(dir.path()).unwrap()).unwrap();
+            add(&conn, "project cache note one", "2026-01-01T00:00:00Z");
+            add(&conn, "project cache note two", "2026-01-03T00:00:00Z");
+            add(&conn, "project cache note three", "2026-01-05T00:00:00Z");
+            let conn = crate::db::open(&global_db).unwrap();
+            add(&conn, "global cache note one", "2026-01-02T00:00:00Z");
+            add(&conn, "global cache note two", "2026-01-04T00:00:00Z");
+        }
+        let mut server = MemoryServer::for_project(dir.path());
+        server.global_db = Some(global_db);
+
+        let text = text_of(call_tool(&mut server, "search_notes", json!({ "limit": 3, "max_tokens": 0 })));
+        assert!(text.starts_with("# 3 Note(s)"));
+        let order: Vec<&str> = text.lines().filter(|l| l.contains("cache note")).collect();
+        assert_eq!(order, ["project cache note three", "global cache note two", "project cache note two"]);
+        assert_eq!(text.matches("(global)").count(), 1);
+
+        let text = text_of(call_tool(&mut server, "search_notes", json!({ "query": "cache", "limit": 3 })));
+        assert!(text.starts_with("# 3 Note(s)"));
+        assert_eq!(text.matches("(global)").count(), 1);
+
+        let text = text_of(call_tool(&mut server, "search_notes", json!({ "scope": "global", "limit": 10 })));
+        assert!(text.starts_with("# 2 Note(s)"));
+        assert!(!text.contains("project cache"));
+    }
+
+    #[test]
+    fn connection_is_reused_until_file_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = MemoryServer::for_project(dir.path());
+
+        call_tool(&mut server, "log_note", json!({ "content": "first" }));
+        let db_path = config::db_path(dir.path()).unwrap();
+        let identity = server.connections[&db_path].identity;
+        call_tool(&mut server, "search_notes", json!({}));
+        assert_eq!(server.connections[&db_path].identity, identity);
+
+        // Replace the database file behind the server's back
+        std::fs::remove_file(&db_path).unwrap();
+        let _ = std::fs::remove_file(db_path.with_extension("db-wal"));
+        let _ = std::fs::remove_file(db_path.with_extension("db-shm"));
+        {
+            let conn = crate::db::open(&db_path).unwrap();
+            crate::db::notes::insert_note(&conn, "restored", &[], None).unwrap();
+        }
+
+        let response = call_tool(&mut server, "search_notes", json!({}));
+        let text = response.result.unwrap()["content"][0]["text"].as_str().unwrap().to_string();
+        assert!(text.contains("restored"));
+        assert!(!text.contains("first"));
+    }
+
+    #[test]
+    fn broken_settings_fail_the_call_instead_of_using_the_default_database() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".claude")).unwrap();
+        std::fs::write(config::settings_path(dir.path()), "[storage\n").unwrap();
+        let mut server = MemoryServer::for_project(dir.path());
+
+        let response = call_tool(&mut server, "log_note", json!({ "content": "lost" }));
+        assert!(response.error.unwrap().message.starts_with("Failed to locate the database"));
+        assert!(!dir.path().join(".claude").join("memory.db").exists());
+    }
+
+    #[test]
+    fn writes_blocked_by_relocate_ask_for_a_retry() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = MemoryServer::for_project(dir.path());
+        call_tool(&mut server, "log_note", json!({ "content": "before the move" }));
+
+        // What `relocate` does: hold the write lock from the copy until the
+        // settings point at the new file and the old one is gone
+        let old = config::db_path(dir.path()).unwrap();
+        let lock = crate::db::open(&old).unwrap();
+        lock.execute_batch("BEGIN IMMEDIATE").unwrap();
+        let call = std::thread::spawn(move || {
+            let response = call_tool(&mut server, "log_note", json!({ "content": "during the move" }));
+            (server, response)
+        });
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        let moved = dir.path().join(".claude").join("moved.db");
+        crate::db::backup_to(&crate::db::open_readonly(&old).unwrap(), &moved).unwrap();
+        std::fs::write(config::settings_path(dir.path()), "[storage]\ndb_path = \".claude/moved.db\"\n").unwrap();
+        std::fs::remove_file(&old).unwrap();
+        let _ = std::fs::remove_file(old.with_extension("db-wal"));
+        let _ = std::fs::remove_file(old.with_extension("db-shm"));
+        drop(lock);
+        let (mut server, response) = call.join().unwrap();
+        assert!(response.error.unwrap().message.contains("relocated"));
+
+        // The retry lands in the new file, once
+        call_tool(&mut server, "log_note", json!({ "content": "during the move" }));
+        let conn = crate::db::open(&moved).unwrap();
+        let notes = crate::db::notes::notes_since(&conn, None).unwrap();
+        let mut contents: Vec<&str> = notes.iter().map(|n| n.content.as_str()).collect();
+        contents.sort();
+        assert_eq!(contents, ["before the move", "during the move"]);
+    }
+
+    #[test]
+    fn settings_edits_do_not_count_as_a_move() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = MemoryServer::for_project(dir.path());
+        call_tool(&mut server, "log_note", json!({ "content": "first" }));
+        assert!(!server.moved(dir.path()));
+
+        std::fs::write(config::settings_path(dir.path()), "[storage]\ndb_path = \".claude/other.db\"\n").unwrap();
+        assert!(!server.moved(dir.path()));
+        // The server keeps the database it resolved and writes only once
+        assert!(call_tool(&mut server, "log_note", json!({ "content": "second" })).error.is_none());
+        assert!(text_of(call_tool(&mut server, "search_notes", json!({}))).starts_with("# 2 Note(s)"));
+    }
+
+    /// Compares tool-call latency with the cached connection against opening the
+    /// database on every call, on a 10k-session database.
+    #[test]
+    #[ignore = "benchmark: cargo test --release -- --ignored --nocapture bench_"]
+    fn bench_tool_calls_on_10k_sessions() {
+        use crate::transcript::metadata::SessionMetadata;
+        use std::time::Instant;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = config::db_path(dir.path()).unwrap();
+        {
+            let mut conn = crate::db::open(&db_path).unwrap();
+            let tx = conn.transaction().unwrap();
+            for i in 0..10_000 {
+                let meta = SessionMetadata {
+                    session_id: format!("session-{:05}", i),
+                    project_dir: dir.path().display().to_string(),
+                    git_branch: Some(format!("feature-{}", i % 50)),
+                    first_timestamp: Some(format!("2026-01-01T00:00:{:02}Z", i % 60)),
+                    user_prompts: vec![format!("fix bug {} in module{} parser", i, i % 100)],
+                    commands_run: vec!["cargo test".to_string()],
+                    ..Default::default()
+                };
+                crate::db::sessions::insert_session(&tx, &meta).unwrap();
+            }
+            tx.commit().unwrap();
+        }
+
+        const CALLS: u32 = 200;
+        let calls = [
+            ("recall", json!({ "query": "parser module42" })),
+            ("list_sessions", json!({ "limit": 10 })),
+            ("get_session", json!({ "session_id": "session-04242" })),
+        ];
+
+        let mut server = MemoryServer::for_project(dir.path());
+        let start = Instant::now();
+        for _ in 0..CALLS {
+            for (name, args) in &calls {
+                assert!(call_tool(&mut server, name, args.clone()).error.is_none());
+            }
+        }
+        let cached = start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..CALLS {
+            for (name, args) in &calls {
+                let conn = crate::db::open(&db_path).unwrap();
+                tools::dispatch(name, args, &conn, dir.path(), dir.path(), None).unwrap();
+            }
+        }
+        let fresh = start.elapsed();
+
+        let per_call = |d: std::time::Duration| d / (CALLS * calls.len() as u32);
+        println!("cached connection: {:?}/call", per_call(cached));
+        println!("open per call:     {:?}/call", per_call(fresh));
+    }
+
+    #[test]
+    fn get_session_accepts_id_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let conn = crate::db::open(&config::db_path(dir.path()).unwrap()).unwrap();
+            conn.execute_batch(
+                "INSERT INTO sessions (id, project_dir, started_at, user_prompts)
+                 VALUES ('1234abcd-full', '/p', '2026-01-01', '[\"first\"]'),
+                        ('1299ffff-full', '/p', '2026-01-02', '[\"second\"]');",
+            )
+            .unwrap();
+        }
+        let mut server = MemoryServer::for_project(dir.path());
+
+        let response = call_tool(&mut server, "get_session", json!({ "session_id": "1234" }));
+        let text = response.result.unwrap()["content"][0]["text"].as_str().unwrap().to_string();
+        assert!(text.contains("1234abcd-full"));
+
+        let response = call_tool(&mut server, "get_session", json!({ "session_id": "12" }));
+        let text = response.result.unwrap()["content"][0]["text"].as_str().unwrap().to_string();
+        assert!(text.contains("Ambiguous"));
+        assert!(text.contains("1299ffff-full"));
+    }
+
+    #[test]
+    fn listings_default_to_the_scope_of_the_first_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let api = dir.path().join("packages").join("api");
+        for sub in [dir.path().join(".git"), dir.path().join(".claude"), api.clone()] {
+            std::fs::create_dir_all(sub).unwrap();
+        }
+        std::fs::write(config::settings_path(dir.path()), "[scopes]\nroots = [\"packages/*\"]\n").unwrap();
+        {
+            let conn = crate::db::open(&config::db_path(dir.path()).unwrap()).unwrap();
+            conn.execute_batch(
+                "INSERT INTO sessions (id, project_dir, started_at, user_prompts, scope) VALUES
+                    ('in-api', '/p', '2026-01-01', '[\"cache\"]', 'packages/api'),
+                    ('in-web', '/p', '2026-01-02', '[\"cache\"]', 'packages/web'),
+                    ('whole-repo', '/p', '2026-01-03', '[\"cache\"]', NULL);",
+            )
+            .unwrap();
+        }
+        let mut server = MemoryServer::for_project(Path::new("/nonexistent"));
+        let id = init_with_roots(&mut server);
+        answer_roots(&mut server, &id, &[&api]);
+
+        let text = |response: JsonRpcResponse| response.result.unwrap()["content"][0]["text"].as_str().unwrap().to_string();
+        let scoped = text(call_tool(&mut server, "recall", json!({ "query": "cache" })));
+        assert!(scoped.contains("in-api") && scoped.contains("whole-repo") && !scoped.contains("in-web"));
+        assert!(scoped.contains("Scope: packages/api"));
+
+        let all = text(call_tool(&mut server, "list_sessions", json!({ "scope": "all" })));
+        assert!(all.contains("in-web") && !all.contains("Scope:"));
+        let web = text(call_tool(&mut server, "list_sessions", json!({ "scope": "packages/web" })));
+        assert!(web.contains("in-web") && !web.contains("in-api"));
+        let unknown = call_tool(&mut server, "list_sessions", json!({ "scope": "docs" }));
+        assert!(unknown.error.unwrap().message.contains("Unknown scope"));
+    }
+
+    #[test]
+    fn completes_session_ids_and_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let conn = crate::db::open(&config::db_path(dir.path()).unwrap()).unwrap();
+            conn.execute_batch(
+                "INSERT INTO sessions (id, project_dir, started_at) VALUES
+                    ('aa11', '/p', '2026-01-01'), ('aa22', '/p', '2026-01-02'), ('bb33', '/p', '2026-01-03');",
+            )
+            .unwrap();
+            let tags = ["decision".to_string(), "design".to_string(), "bug".to_string()];
+            crate::db::notes::insert_note(&conn, "x", &tags, None).unwrap();
+        }
+        let mut server = MemoryServer::for_project(dir.path());
+
+        let complete = |server: &mut MemoryServer, name: &str, value: &str| {
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "completion/complete",
+                "params": {
+                    "ref": { "type": "ref/resource", "uri": "memory://session/{session_id}" },
+                    "argument": { "name": name, "value": value }
+                }
+            });
+            handle_request(server, &request.to_string()).unwrap().result.unwrap()["completion"]["values"].clone()
+        };
+
+        assert_eq!(complete(&mut server, "session_id", "aa"), json!(["aa22", "aa11"]));
+        assert_eq!(complete(&mut server, "tag", "de"), json!(["decision", "design"]));
+        assert_eq!(complete(&mut server, "unknown", ""), json!([]));
+    }
+
+    #[test]
+    fn reads_session_resource_by_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let conn = crate::db::open(&config::db_path(dir.path()).unwrap()).unwrap();
+            conn.execute(
+                "INSERT INTO sessions (id, project_dir, started_at) VALUES ('cafe-1234', '/p', '2026-01-01')",
+                [],
+            )
+            .unwrap();
+        }
+        let mut server = MemoryServer::for_project(dir.path());
+
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"resources/read","params":{"uri":"memory://session/cafe"}}"#;
+        let result = handle_request(&mut server, request).unwrap().result.unwrap();
+        assert!(result["contents"][0]["text"].as_str().unwrap().contains("cafe-1234"));
+
+        let request = r#"{"jsonrpc":"2.0","id":2,"method":"resources/read","params":{"uri":"other://x"}}"#;
+        assert!(handle_request(&mut server, request).unwrap().error.is_some());
+    }
+
+    fn note_count(dir: &Path) -> i64 {
+        let conn = crate::db::open(&config::db_path(dir).unwrap()).unwrap();
+        crate::db::notes::note_count(&conn).unwrap()
+    }
+
+    #[test]
+    fn forget_requires_confirm_without_elicitation() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = MemoryServer::for_project(dir.path());
+        call_tool(&mut server, "log_note", json!({ "content": "temporary api workaround" }));
+
+        let response = call_tool(&mut server, "forget", json!({ "query": "workaround" }));
+        let text = response.result.unwrap()["content"][0]["text"].as_str().unwrap().to_string();
+        assert!(text.contains("confirm: true"));
+        assert_eq!(note_count(dir.path()), 1);
+
+        let response = call_tool(&mut server, "forget", json!({ "query": "workaround", "confirm": true }));
+        let text = response.result.unwrap()["content"][0]["text"].as_str().unwrap().to_string();
+        assert!(text.contains("Forgot 0 session(s) and 1 note(s)"));
+        assert_eq!(note_count(dir.path()), 0);
+    }
+
+    #[test]
+    fn forget_asks_through_elicitation() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = MemoryServer::for_project(dir.path());
+        let init = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{"elicitation":{}}}}"#;
+        handle_request(&mut server, init);
+        call_tool(&mut server, "log_note", json!({ "content": "temporary api workaround" }));
+
+        for (action, remaining) in [("decline", 1), ("accept", 0)] {
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": 9,
+                "method": "tools/call",
+                "params": { "name": "forget", "arguments": { "query": "workaround" } }
+            });
+            assert!(handle_request(&mut server, &request.to_string()).is_none());
+            let outgoing = server.drain_outgoing();
+            assert_eq!(outgoing[0]["method"], "elicitation/create");
+            assert!(outgoing[0]["params"]["message"].as_str().unwrap().contains("temporary api workaround"));
+
+            let answer = json!({ "jsonrpc": "2.0", "id": outgoing[0]["id"], "result": { "action": action } });
+            assert!(handle_request(&mut server, &answer.to_string()).is_none());
+            let outgoing = server.drain_outgoing();
+            assert_eq!(outgoing.len(), 1);
+            assert_eq!(outgoing[0]["id"], 9);
+            assert!(outgoing[0]["result"]["content"][0]["text"].is_string());
+            assert_eq!(note_count(dir.path()), remaining);
+        }
+
+        // Nothing left to match, so there is nothing to ask about
+        let response = call_tool(&mut server, "forget", json!({ "query": "workaround" }));
+        assert!(response.result.is_some());
+        assert!(server.drain_outgoing().is_empty());
+    }
+
+    #[test]
+    fn forget_reaches_global_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let global_db = home.path().join("memory-global.db");
+        let mut server = MemoryServer::for_project(dir.path());
+        server.global_db = Some(global_db.clone());
+        call_tool(&mut server, "log_note", json!({ "content": "old workaround", "scope": "global" }));
+        call_tool(&mut server, "log_note", json!({ "content": "old workaround" }));
+
+        let args = json!({ "query": "workaround", "scope": "global", "confirm": true });
+        let text = text_of(call_tool(&mut server, "forget", args));
+        assert_eq!(text, "Forgot 0 session(s) and 1 note(s).");
+        let global_conn = crate::db::open(&global_db).unwrap();
+        assert_eq!(crate::db::notes::note_count(&global_conn).unwrap(), 0);
+        assert_eq!(note_count(dir.path()), 1);
+
+        let response = call_tool(&mut server, "forget", json!({ "query": "workaround", "scope": "all" }));
+        assert!(response.error.is_some());
+    }
+
+    #[test]
+    fn file_uris_are_decoded() {
+        assert_eq!(file_uri_to_path("file:///home/me/my%20repo"), Some(PathBuf::from("/home/me/my repo")));
+        assert_eq!(file_uri_to_path("file://localhost/srv/x"), Some(PathBuf::from("/srv/x")));
+        assert_eq!(file_uri_to_path("https://example.com/x"), None);
+    }
+}
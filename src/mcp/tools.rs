@@ -1,7 +1,28 @@
+use std::path::Path;
+
 use rusqlite::Connection;
 use serde_json::{json, Value};
 
-use crate::db::{notes, sessions};
+use crate::config::{self, ScopeSettings, SearchSettings};
+use crate::cross_project;
+use crate::db::crossrefs::{self, CrossReference};
+use crate::db::encryption::KeyError;
+use crate::db::forget::{self, Scope, Selection, Selector};
+use crate::db::{self, notes, sessions, LookupError};
+use crate::mcp::budget::{
+    capped, estimate_tokens, fit_items, Budget, Detail, DETAIL_LEVELS, SUMMARY_LEVELS,
+};
+use crate::scopes::Scopes;
+use crate::text::truncate;
+
+/// Appended to trimmed session listings.
+const GET_SESSION_HINT: &str = "Use get_session for a session's complete record.";
+
+/// Most cross-references appended to a listing.
+const CROSS_REFERENCE_LIMIT: usize = 5;
+
+/// Tools that only read, and so may be pointed at any discovered project.
+pub const READ_ONLY_TOOLS: &[&str] = &["recall", "list_sessions", "get_session", "search_notes"];
 
 /// Return all tool definitions for MCP tools/list.
 pub fn tool_definitions() -> Vec<Value> {
@@ -18,7 +39,24 @@ pub fn tool_definitions() -> Vec<Value> {
                     },
                     "limit": {
                         "type": "integer",
-                        "description": "Maximum results (default: 5, max: 20)"
+                        "description": "Maximum results (default: project setting, normally 5, at most 20)"
+                    },
+                    "scope": {
+                        "type": "string",
+                        "description": "Monorepo sub-project to search, e.g. \"packages/api\", or \"all\" for the whole repository (default: the sub-project being worked in, if scopes are configured)"
+                    },
+                    "project": {
+                        "type": "string",
+                        "description": "Project to query: a workspace root or any project from list_projects (name or path; default: first root). Other projects are opened read-only."
+                    },
+                    "verbosity": {
+                        "type": "string",
+                        "enum": ["brief", "normal", "full"],
+                        "description": "Amount of detail (default: project setting, normally \"normal\")"
+                    },
+                    "max_tokens": {
+                        "type": "integer",
+                        "description": "Approximate output budget in tokens; sections are shortened to fit (default: project setting, 0 = unlimited)"
                     }
                 },
                 "required": ["query"]
@@ -32,7 +70,7 @@ pub fn tool_definitions() -> Vec<Value> {
                 "properties": {
                     "limit": {
                         "type": "integer",
-                        "description": "Max sessions to return (default: 10)"
+                        "description": "Max sessions to return (default: project setting, normally 10, at most 50)"
                     },
                     "date_from": {
                         "type": "string",
@@ -41,6 +79,23 @@ pub fn tool_definitions() -> Vec<Value> {
                     "date_to": {
                         "type": "string",
                         "description": "Filter sessions before this date (ISO format, e.g. 2026-02-21)"
+                    },
+                    "scope": {
+                        "type": "string",
+                        "description": "Monorepo sub-project to search, e.g. \"packages/api\", or \"all\" for the whole repository (default: the sub-project being worked in, if scopes are configured)"
+                    },
+                    "project": {
+                        "type": "string",
+                        "description": "Project to query: a workspace root or any project from list_projects (name or path; default: first root). Other projects are opened read-only."
+                    },
+                    "verbosity": {
+                        "type": "string",
+                        "enum": ["brief", "normal", "full"],
+                        "description": "Amount of detail (default: project setting, normally \"normal\")"
+                    },
+                    "max_tokens": {
+                        "type": "integer",
+                        "description": "Approximate output budget in tokens; sections are shortened to fit (default: project setting, 0 = unlimited)"
                     }
                 }
             }
@@ -53,7 +108,20 @@ pub fn tool_definitions() -> Vec<Value> {
                 "properties": {
                     "session_id": {
                         "type": "string",
-                        "description": "Session ID (from recall or list_sessions results)"
+                        "description": "Session ID or unique ID prefix (from recall or list_sessions results)"
+                    },
+                    "project": {
+                        "type": "string",
+                        "description": "Project to query: a workspace root or any project from list_projects (name or path; default: first root). Other projects are opened read-only."
+                    },
+                    "verbosity": {
+                        "type": "string",
+                        "enum": ["brief", "normal", "full"],
+                        "description": "Amount of detail (default: project setting, normally \"normal\")"
+                    },
+                    "max_tokens": {
+                        "type": "integer",
+                        "description": "Approximate output budget in tokens; sections are shortened to fit (default: project setting, 0 = unlimited)"
                     }
                 },
                 "required": ["session_id"]
@@ -73,6 +141,15 @@ pub fn tool_definitions() -> Vec<Value> {
                         "type": "array",
                         "items": { "type": "string" },
                         "description": "Tags for categorization (e.g. [\"decision\", \"architecture\", \"bug\"])"
+                    },
+                    "scope": {
+                        "type": "string",
+                        "enum": ["project", "global"],
+                        "description": "\"global\" for the user's personal preferences and facts that hold in every project (default: project)"
+                    },
+                    "project": {
+                        "type": "string",
+                        "description": "Project to use when several workspace roots are open (root name or path; default: first root)"
                     }
                 },
                 "required": ["content"]
@@ -94,20 +171,130 @@ pub fn tool_definitions() -> Vec<Value> {
                     },
                     "limit": {
                         "type": "integer",
-                        "description": "Max results (default: 10)"
+                        "description": "Max results (default: project setting, normally 10)"
+                    },
+                    "scope": {
+                        "type": "string",
+                        "enum": ["all", "project", "global"],
+                        "description": "Which notes to search: the project's, the user's global ones, or both (default: all; global notes are labeled)"
+                    },
+                    "project": {
+                        "type": "string",
+                        "description": "Project to query: a workspace root or any project from list_projects (name or path; default: first root). Other projects are opened read-only."
+                    },
+                    "verbosity": {
+                        "type": "string",
+                        "enum": ["brief", "normal", "full"],
+                        "description": "Amount of detail (default: project setting, normally \"normal\")"
+                    },
+                    "max_tokens": {
+                        "type": "integer",
+                        "description": "Approximate output budget in tokens; sections are shortened to fit (default: project setting, 0 = unlimited)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "global_notes",
+            "description": "List the user's global notes: personal preferences and facts that hold in every project. Call this at the start of a session to load them.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "tag": {
+                        "type": "string",
+                        "description": "Filter notes by tag"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Max results (default: project setting, normally 10)"
+                    },
+                    "verbosity": {
+                        "type": "string",
+                        "enum": ["brief", "normal", "full"],
+                        "description": "Amount of detail (default: project setting, normally \"normal\")"
+                    },
+                    "max_tokens": {
+                        "type": "integer",
+                        "description": "Approximate output budget in tokens; sections are shortened to fit (default: project setting, 0 = unlimited)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "forget",
+            "description": "Permanently delete sessions or notes from memory, by ID, by search query, or by date range. Use when the user asks to forget something. Without confirm, only previews what would be deleted; the user is asked to approve when the client supports it.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session_ids": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Sessions to forget (full IDs or unique prefixes)"
+                    },
+                    "note_ids": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Notes to forget (full IDs or unique prefixes)"
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "Forget sessions and notes matching this FTS5 query"
+                    },
+                    "date_from": {
+                        "type": "string",
+                        "description": "Only those on or after this date (ISO format: YYYY-MM-DD)"
+                    },
+                    "date_to": {
+                        "type": "string",
+                        "description": "Only those on or before this date (ISO format: YYYY-MM-DD)"
+                    },
+                    "kind": {
+                        "type": "string",
+                        "enum": ["all", "sessions", "notes"],
+                        "description": "What query and dates select (default: all)"
+                    },
+                    "with_notes": {
+                        "type": "boolean",
+                        "description": "Also delete notes attached to forgotten sessions (default: keep them, unlinked)"
+                    },
+                    "scope": {
+                        "type": "string",
+                        "enum": ["project", "global"],
+                        "description": "Forget from the project's memory or from the user's global notes (default: project)"
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "Only list what would be forgotten"
+                    },
+                    "confirm": {
+                        "type": "boolean",
+                        "description": "Delete without a further prompt. Only set this after the user has approved the preview."
+                    },
+                    "project": {
+                        "type": "string",
+                        "description": "Project to use when several workspace roots are open (root name or path; default: first root)"
                     }
                 }
             }
         }),
         json!({
             "name": "list_projects",
-            "description": "List all projects on this machine that have claude-memory databases. Shows session counts, date ranges, and recent branches for each project. Use this to discover past work across projects.",
+            "description": "List all projects on this machine that have claude-memory databases. Shows session counts, date ranges, and recent branches for each project. Use this to discover past work across projects, then pass a project name to recall, list_sessions, get_session or search_notes to dig into it.",
             "inputSchema": {
                 "type": "object",
                 "properties": {
                     "limit": {
                         "type": "integer",
                         "description": "Maximum projects to return (default: 20)"
+                    },
+                    "verbosity": {
+                        "type": "string",
+                        "enum": ["brief", "normal", "full"],
+                        "description": "Amount of detail (default: project setting, normally \"normal\")"
+                    },
+                    "max_tokens": {
+                        "type": "integer",
+                        "description": "Approximate output budget in tokens; sections are shortened to fit (default: project setting, 0 = unlimited)"
                     }
                 }
             }
@@ -116,19 +303,80 @@ pub fn tool_definitions() -> Vec<Value> {
 }
 
 /// Dispatch a tool call to the appropriate handler.
-pub fn dispatch(name: &str, args: &Value, conn: &Connection) -> anyhow::Result<String> {
+///
+/// `location` is the directory the agent works in; inside a monorepo
+/// sub-project it limits `recall` and `list_sessions` to that scope.
+/// `global_db` is the user's global memory, `None` when it has no home.
+pub fn dispatch(
+    name: &str,
+    args: &Value,
+    conn: &Connection,
+    current_project: &Path,
+    location: &Path,
+    global_db: Option<&Path>,
+) -> anyhow::Result<String> {
+    let settings = config::load_settings(current_project)?;
+    let budget = Budget::from_args(args, &settings.output)?;
+    let scope = || requested_scope(args, &settings.scopes, current_project, location);
+
     match name {
-        "recall" => handle_recall(args, conn),
-        "list_sessions" => handle_list_sessions(args, conn),
-        "get_session" => handle_get_session(args, conn),
-        "log_note" => handle_log_note(args, conn),
-        "search_notes" => handle_search_notes(args, conn),
-        "list_projects" => handle_list_projects(args),
+        "recall" => handle_recall(args, conn, &settings.search, &budget, scope()?.as_deref()),
+        "list_sessions" => handle_list_sessions(args, conn, &settings.search, &budget, scope()?.as_deref()),
+        "get_session" => handle_get_session(args, conn, &budget),
+        "log_note" => handle_log_note(args, conn, global_db),
+        "search_notes" => handle_search_notes(args, conn, &settings.search, &budget, global_db),
+        "global_notes" => handle_global_notes(args, &settings.search, &budget, global_db),
+        "list_projects" => handle_list_projects(args, current_project, &budget),
+        "forget" => handle_forget(args, conn, current_project, global_db),
         _ => Ok(format!("Unknown tool: {}", name)),
     }
 }
 
-fn handle_recall(args: &Value, conn: &Connection) -> anyhow::Result<String> {
+/// The scope a listing is limited to: the `scope` argument (`all` for none),
+/// else the scope of `location`.
+fn requested_scope(
+    args: &Value,
+    settings: &ScopeSettings,
+    project_dir: &Path,
+    location: &Path,
+) -> anyhow::Result<Option<String>> {
+    let requested = args.get("scope").and_then(|s| s.as_str());
+    if requested == Some("all") {
+        return Ok(None);
+    }
+    let scopes = Scopes::load(project_dir, settings)?;
+    match requested {
+        Some(scope) => {
+            anyhow::ensure!(
+                scopes.scope_of(project_dir, Path::new(scope)).as_deref() == Some(scope.trim_matches('/')),
+                "Unknown scope: {} (scope roots: {})",
+                scope,
+                if scopes.is_empty() { "none configured".to_string() } else { scopes.patterns().join(", ") }
+            );
+            Ok(Some(scope.trim_matches('/').to_string()))
+        }
+        None => Ok(scopes.scope_of(project_dir, location)),
+    }
+}
+
+/// Says which scope a listing covers and how to widen it.
+fn scope_note(scope: Option<&str>) -> String {
+    match scope {
+        Some(scope) => format!(
+            "_Scope: {} and repository-wide sessions; pass scope: \"all\" for the whole repository._\n\n",
+            scope
+        ),
+        None => String::new(),
+    }
+}
+
+fn handle_recall(
+    args: &Value,
+    conn: &Connection,
+    search: &SearchSettings,
+    budget: &Budget,
+    scope: Option<&str>,
+) -> anyhow::Result<String> {
     let query = args
         .get("query")
         .and_then(|q| q.as_str())
@@ -137,16 +385,20 @@ fn handle_recall(args: &Value, conn: &Connection) -> anyhow::Result<String> {
     let limit = args
         .get("limit")
         .and_then(|l| l.as_u64())
-        .unwrap_or(5)
-        .min(20) as usize;
+        .map_or(search.recall_limit, |l| l as usize)
+        .min(search.max_recall_limit);
+
+    let (results, is_fallback) = sessions::search_sessions_in(conn, query, limit, scope)?;
+    let elsewhere = crossrefs::search_cross_references(conn, query, CROSS_REFERENCE_LIMIT)?;
 
-    let (results, is_fallback) = sessions::search_sessions(conn, query, limit)?;
+    let (elsewhere, budget) = cross_reference_section(budget, &elsewhere);
 
     if results.is_empty() {
-        return Ok(format!("No sessions found matching: \"{}\"", query));
+        let none = format!("No sessions found matching: \"{}\"\n{}", query, scope_note(scope));
+        return Ok(format!("{}{}", none, elsewhere).trim_end().to_string());
     }
 
-    let mut output = if is_fallback {
+    let mut header = if is_fallback {
         format!(
             "# Found {} session(s) with partial matches for: \"{}\"\n\
              _(No exact match — showing sessions matching some of these terms)_\n\n",
@@ -160,56 +412,75 @@ fn handle_recall(args: &Value, conn: &Connection) -> anyhow::Result<String> {
             query
         )
     };
+    header.push_str(&scope_note(scope));
 
-    for session in &results {
-        output.push_str(&format_session_summary(session));
-        output.push('\n');
-    }
-
-    Ok(output)
+    let listing = fit_items(&budget, &SUMMARY_LEVELS, &header, &results, GET_SESSION_HINT, |session, detail| {
+        format!("{}\n", format_session_summary(session, detail))
+    });
+    Ok(format!("{}{}", listing, elsewhere))
 }
 
-fn handle_list_sessions(args: &Value, conn: &Connection) -> anyhow::Result<String> {
+fn handle_list_sessions(
+    args: &Value,
+    conn: &Connection,
+    search: &SearchSettings,
+    budget: &Budget,
+    scope: Option<&str>,
+) -> anyhow::Result<String> {
     let limit = args
         .get("limit")
         .and_then(|l| l.as_u64())
-        .unwrap_or(10)
-        .min(50) as usize;
+        .map_or(search.list_limit, |l| l as usize)
+        .min(search.max_list_limit);
 
     let date_from = args.get("date_from").and_then(|d| d.as_str());
     let date_to = args.get("date_to").and_then(|d| d.as_str());
 
-    let results = sessions::list_sessions(conn, limit, date_from, date_to)?;
+    let results = sessions::list_sessions(conn, limit, date_from, date_to, None, scope)?;
+    let elsewhere = crossrefs::recent_cross_references(conn, CROSS_REFERENCE_LIMIT, date_from, date_to)?;
+
+    let (elsewhere, budget) = cross_reference_section(budget, &elsewhere);
 
     if results.is_empty() {
-        return Ok("No sessions found.".to_string());
+        let none = format!("No sessions found.\n{}", scope_note(scope));
+        return Ok(format!("{}{}", none, elsewhere).trim_end().to_string());
     }
 
-    let mut output = format!("# {} Recent Session(s)\n\n", results.len());
-
-    for session in &results {
-        output.push_str(&format_session_summary(session));
-        output.push('\n');
-    }
+    let header = format!("# {} Recent Session(s)\n\n{}", results.len(), scope_note(scope));
 
-    Ok(output)
+    let listing = fit_items(&budget, &SUMMARY_LEVELS, &header, &results, GET_SESSION_HINT, |session, detail| {
+        format!("{}\n", format_session_summary(session, detail))
+    });
+    Ok(format!("{}{}", listing, elsewhere))
 }
 
-fn handle_get_session(args: &Value, conn: &Connection) -> anyhow::Result<String> {
+fn handle_get_session(
+    args: &Value,
+    conn: &Connection,
+    budget: &Budget,
+) -> anyhow::Result<String> {
     let session_id = args
         .get("session_id")
         .and_then(|s| s.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing required parameter: session_id"))?;
 
-    let session = sessions::get_session(conn, session_id)?;
+    let session_id = match sessions::resolve_session_id(conn, session_id) {
+        Ok(id) => id,
+        Err(e) => match e.downcast_ref::<LookupError>() {
+            Some(lookup) => return Ok(lookup.to_string()),
+            None => return Err(e),
+        },
+    };
+
+    let session = sessions::get_session(conn, &session_id)?;
 
     match session {
-        Some(s) => Ok(format_session_detail(&s)),
+        Some(s) => Ok(fit_items(budget, &DETAIL_LEVELS, "", &[s], "", format_session_detail)),
         None => Ok(format!("Session not found: {}", session_id)),
     }
 }
 
-fn handle_log_note(args: &Value, conn: &Connection) -> anyhow::Result<String> {
+fn handle_log_note(args: &Value, conn: &Connection, global_db: Option<&Path>) -> anyhow::Result<String> {
     let content = args
         .get("content")
         .and_then(|c| c.as_str())
@@ -225,58 +496,205 @@ fn handle_log_note(args: &Value, conn: &Connection) -> anyhow::Result<String> {
         })
         .unwrap_or_default();
 
-    let id = notes::insert_note(conn, content, &tags, None)?;
+    let global = match note_scope(args, NoteScope::Project)? {
+        NoteScope::Project => false,
+        NoteScope::Global => true,
+        NoteScope::All => anyhow::bail!("A note is saved to one scope: project or global"),
+    };
+    let id = if global {
+        let global_conn = db::open(&global_path(global_db)?)?;
+        notes::insert_note(&global_conn, content, &tags, None)?
+    } else {
+        notes::insert_note(conn, content, &tags, None)?
+    };
 
     let tag_display = if tags.is_empty() {
         String::new()
     } else {
         format!(" [{}]", tags.join(", "))
     };
+    let what = if global { "Global note" } else { "Note" };
+
+    Ok(format!("{} saved{} (id: {})", what, tag_display, &id[..8]))
+}
 
-    Ok(format!("Note saved{} (id: {})", tag_display, &id[..8]))
+/// The global memory's path, or why there is none.
+fn global_path(global_db: Option<&Path>) -> anyhow::Result<std::path::PathBuf> {
+    global_db.map_or_else(config::global_db_path, |p| Ok(p.to_path_buf()))
 }
 
-fn handle_search_notes(args: &Value, conn: &Connection) -> anyhow::Result<String> {
+/// Which notes a call works on: the project's, the user's global ones, or both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NoteScope {
+    Project,
+    Global,
+    All,
+}
+
+fn note_scope(args: &Value, default: NoteScope) -> anyhow::Result<NoteScope> {
+    match args.get("scope").and_then(|s| s.as_str()) {
+        None => Ok(default),
+        Some("project") => Ok(NoteScope::Project),
+        Some("global") => Ok(NoteScope::Global),
+        Some("all") => Ok(NoteScope::All),
+        Some(other) => anyhow::bail!("Invalid scope: {} (expected project, global or all)", other),
+    }
+}
+
+fn handle_search_notes(
+    args: &Value,
+    conn: &Connection,
+    search: &SearchSettings,
+    budget: &Budget,
+    global_db: Option<&Path>,
+) -> anyhow::Result<String> {
     let query = args.get("query").and_then(|q| q.as_str());
     let tag = args.get("tag").and_then(|t| t.as_str());
     let limit = args
         .get("limit")
         .and_then(|l| l.as_u64())
-        .unwrap_or(10) as usize;
+        .map_or(search.notes_limit, |l| l as usize);
 
-    let results = notes::search_notes(conn, query, tag, limit)?;
+    let scope = note_scope(args, NoteScope::All)?;
+    let mut project = Vec::new();
+    let mut global = Vec::new();
+    if scope != NoteScope::Global {
+        project = notes::search_notes(conn, query, tag, limit)?;
+    }
+    if scope != NoteScope::Project {
+        if let Some(global_conn) = db::open_global(&global_path(global_db)?)? {
+            global = notes::search_notes(&global_conn, query, tag, limit)?;
+        }
+    }
+    let results = notes::merge_scopes(project, global, query.is_some(), limit);
 
     if results.is_empty() {
         return Ok("No notes found.".to_string());
     }
 
-    let mut output = format!("# {} Note(s)\n\n", results.len());
+    let header = format!("# {} Note(s)\n\n", results.len());
+
+    Ok(fit_items(budget, &SUMMARY_LEVELS, &header, &results, "", |(global, note), detail| {
+        format_labeled_note(note, global.then_some("global"), detail)
+    }))
+}
 
-    for note in &results {
-        let date = &note.created_at[..10.min(note.created_at.len())];
-        let tags: Vec<String> = serde_json::from_str(&note.tags).unwrap_or_default();
-        let tag_display = if tags.is_empty() {
-            String::new()
-        } else {
-            format!(" [{}]", tags.join(", "))
+fn handle_global_notes(
+    args: &Value,
+    search: &SearchSettings,
+    budget: &Budget,
+    global_db: Option<&Path>,
+) -> anyhow::Result<String> {
+    let tag = args.get("tag").and_then(|t| t.as_str());
+    let limit = args
+        .get("limit")
+        .and_then(|l| l.as_u64())
+        .map_or(search.notes_limit, |l| l as usize);
+
+    let results = match db::open_global(&global_path(global_db)?)? {
+        Some(global_conn) => notes::search_notes(&global_conn, None, tag, limit)?,
+        None => Vec::new(),
+    };
+    if results.is_empty() {
+        return Ok("No global notes yet. Save one with log_note and scope: \"global\".".to_string());
+    }
+
+    let header = format!("# {} Global Note(s)\n\n", results.len());
+
+    Ok(fit_items(budget, &SUMMARY_LEVELS, &header, &results, "", format_note))
+}
+
+/// Resolve the `forget` arguments to the rows they select, in the project's
+/// memory or with `scope: "global"` in the global one.
+pub fn forget_selection(args: &Value, conn: &Connection, global_db: Option<&Path>) -> anyhow::Result<Selection> {
+    let strings = |key: &str| -> Vec<String> {
+        args.get(key)
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    };
+    let string = |key: &str| args.get(key).and_then(|v| v.as_str()).map(String::from);
+    let scope = match args.get("kind").and_then(|k| k.as_str()) {
+        None | Some("all") => Scope::All,
+        Some("sessions") => Scope::Sessions,
+        Some("notes") => Scope::Notes,
+        Some(other) => anyhow::bail!("Invalid kind: {} (expected all, sessions or notes)", other),
+    };
+    let selector = Selector {
+        session_ids: strings("session_ids"),
+        note_ids: strings("note_ids"),
+        query: string("query"),
+        date_from: string("date_from"),
+        date_to: string("date_to"),
+        scope,
+        with_notes: args.get("with_notes").and_then(|w| w.as_bool()).unwrap_or(false),
+    };
+    if forgets_global(args)? {
+        return match db::open_global(&global_path(global_db)?)? {
+            Some(global_conn) => forget::select(&global_conn, &selector),
+            None => Ok(Selection::default()),
         };
+    }
+    forget::select(conn, &selector)
+}
 
-        output.push_str(&format!("## {}{}\n", date, tag_display));
-        output.push_str(&note.content);
-        output.push_str("\n\n");
+/// Whether `forget` works on the global memory instead of the project's.
+pub fn forgets_global(args: &Value) -> anyhow::Result<bool> {
+    match note_scope(args, NoteScope::Project)? {
+        NoteScope::Project => Ok(false),
+        NoteScope::Global => Ok(true),
+        NoteScope::All => anyhow::bail!("forget works on one scope at a time: project or global"),
     }
+}
 
-    Ok(output)
+fn handle_forget(
+    args: &Value,
+    conn: &Connection,
+    current_project: &Path,
+    global_db: Option<&Path>,
+) -> anyhow::Result<String> {
+    let flag = |key: &str| args.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let selection = match forget_selection(args, conn, global_db) {
+        Ok(s) => s,
+        Err(e) => match e.downcast_ref::<LookupError>() {
+            Some(lookup) => return Ok(lookup.to_string()),
+            None => return Err(e),
+        },
+    };
+    if selection.is_empty() {
+        return Ok("Nothing matches; nothing was deleted.".to_string());
+    }
+    if flag("dry_run") {
+        return Ok(format!("Would forget:\n{}", selection.describe()));
+    }
+    if !flag("confirm") {
+        return Ok(format!(
+            "Would forget:\n{}\nNothing was deleted. Show this to the user and, once they approve, call forget again with the same arguments and confirm: true.",
+            selection.describe()
+        ));
+    }
+
+    if forgets_global(args)? {
+        let global_conn = db::open(&global_path(global_db)?)?;
+        return Ok(forget::forget(&global_conn, &selection)?.to_string());
+    }
+    let forgotten = forget::forget(conn, &selection)?;
+    cross_project::forget(&selection.sessions, current_project);
+    Ok(forgotten.to_string())
 }
 
-fn handle_list_projects(args: &Value) -> anyhow::Result<String> {
+fn handle_list_projects(
+    args: &Value,
+    current_project: &Path,
+    budget: &Budget,
+) -> anyhow::Result<String> {
     let limit = args
         .get("limit")
         .and_then(|l| l.as_u64())
         .unwrap_or(20)
         .min(50) as usize;
 
-    let current_project = crate::config::detect_project_dir().ok();
     let projects = crate::config::discover_project_dbs();
 
     if projects.is_empty() {
@@ -284,10 +702,22 @@ fn handle_list_projects(args: &Value) -> anyhow::Result<String> {
     }
 
     let mut entries: Vec<ProjectEntry> = Vec::new();
+    // Encrypted databases this process has no (or the wrong) key for
+    let mut locked: Vec<String> = Vec::new();
 
     for project in &projects {
+        let name = project
+            .project_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| project.project_dir.display().to_string());
+
         let conn = match crate::db::open_readonly(&project.db_path) {
             Ok(c) => c,
+            Err(e) if e.downcast_ref::<KeyError>().is_some() => {
+                locked.push(name);
+                continue;
+            }
             Err(_) => continue,
         };
 
@@ -296,15 +726,7 @@ fn handle_list_projects(args: &Value) -> anyhow::Result<String> {
             Err(_) => continue,
         };
 
-        let is_current = current_project
-            .as_ref()
-            .is_some_and(|cp| cp == &project.project_dir);
-
-        let name = project
-            .project_dir
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_else(|| project.project_dir.display().to_string());
+        let is_current = project.project_dir == current_project;
 
         entries.push(ProjectEntry {
             name,
@@ -324,11 +746,11 @@ fn handle_list_projects(args: &Value) -> anyhow::Result<String> {
 
     entries.truncate(limit);
 
-    let mut output = format!("# {} Project(s) with Memory\n\n", entries.len());
-    output.push_str("| Project | Sessions | Notes | Last Active | Branch |\n");
-    output.push_str("|---------|----------|-------|-------------|--------|\n");
+    let mut header = format!("# {} Project(s) with Memory\n\n", entries.len());
+    header.push_str("| Project | Sessions | Notes | Last Active | Branch |\n");
+    header.push_str("|---------|----------|-------|-------------|--------|\n");
 
-    for entry in &entries {
+    let mut output = fit_items(budget, &SUMMARY_LEVELS, &header, &entries, "", |entry, _| {
         let marker = if entry.is_current { " **(current)**" } else { "" };
         let last_active = entry
             .summary
@@ -338,11 +760,11 @@ fn handle_list_projects(args: &Value) -> anyhow::Result<String> {
             .unwrap_or("-");
         let branch = entry.summary.last_branch.as_deref().unwrap_or("-");
 
-        output.push_str(&format!(
+        format!(
             "| {}{} | {} | {} | {} | {} |\n",
             entry.name, marker, entry.summary.session_count, entry.summary.note_count, last_active, branch,
-        ));
-    }
+        )
+    });
 
     let total_sessions: i64 = entries.iter().map(|e| e.summary.session_count).sum();
     let total_notes: i64 = entries.iter().map(|e| e.summary.note_count).sum();
@@ -350,6 +772,9 @@ fn handle_list_projects(args: &Value) -> anyhow::Result<String> {
         "\n_Total: {} sessions, {} notes across {} projects_\n",
         total_sessions, total_notes, entries.len()
     ));
+    if !locked.is_empty() {
+        output.push_str(&format!("_Encrypted, no usable key: {}_\n", locked.join(", ")));
+    }
 
     Ok(output)
 }
@@ -362,7 +787,47 @@ struct ProjectEntry {
 
 // --- Formatting helpers ---
 
-fn format_session_summary(session: &sessions::SessionRow) -> String {
+/// The "Edited from other projects" section for sessions of other projects
+/// that edited files of this one, and the budget left for the rest of the
+/// output. The section gets at most a quarter of the budget.
+fn cross_reference_section(budget: &Budget, elsewhere: &[CrossReference]) -> (String, Budget) {
+    if elsewhere.is_empty() {
+        return (String::new(), *budget);
+    }
+    let share = Budget {
+        max_tokens: budget.max_tokens.map(|max| (max / 4).max(1)),
+        ..*budget
+    };
+    let header = "\n## Edited from other projects\n_Pass the project to get_session for one of these sessions._\n";
+    let section = fit_items(&share, &SUMMARY_LEVELS, header, elsewhere, "", format_cross_reference);
+    let rest = Budget {
+        max_tokens: budget.max_tokens.map(|max| max.saturating_sub(estimate_tokens(&section)).max(1)),
+        ..*budget
+    };
+    (section, rest)
+}
+
+/// One line per session; its full record is in the other project's memory.
+fn format_cross_reference(xref: &CrossReference, detail: &Detail) -> String {
+    let date = &xref.started_at[..10.min(xref.started_at.len())];
+    let files: Vec<String> = serde_json::from_str(&xref.files).unwrap_or_default();
+    let names: Vec<&str> = files.iter().map(|f| f.rsplit('/').next().unwrap_or(f)).collect();
+    let (shown, more) = capped(&names, detail.files.min(5));
+    let mut out = format!("- {} `{}` from {}", date, xref.session_id, xref.source_project);
+    if !shown.is_empty() {
+        out.push_str(&format!(": {}", shown.join(", ")));
+    }
+    if more > 0 {
+        out.push_str(&format!(" (+{})", more));
+    }
+    if let Some(prompt) = &xref.prompt {
+        out.push_str(&format!(" — \"{}\"", truncate(prompt, detail.prompt_len.min(80))));
+    }
+    out.push('\n');
+    out
+}
+
+fn format_session_summary(session: &sessions::SessionRow, detail: &Detail) -> String {
     let date = &session.started_at[..10.min(session.started_at.len())];
     let duration = session
         .duration_seconds
@@ -377,72 +842,84 @@ fn format_session_summary(session: &sessions::SessionRow) -> String {
         out.push_str(&format!("**Model:** {}\n", model));
     }
 
-    // User prompts (show first 2, truncated)
+    // User prompts (first few, truncated)
     if let Ok(prompts) = serde_json::from_str::<Vec<String>>(&session.user_prompts) {
         if !prompts.is_empty() {
+            let (shown, more) = capped(&prompts, detail.prompts);
             out.push_str("**Prompts:**\n");
-            for prompt in prompts.iter().take(2) {
-                let display = if prompt.len() > 150 {
-                    format!("{}...", &prompt[..150])
-                } else {
-                    prompt.clone()
-                };
-                out.push_str(&format!("- {}\n", display));
+            for prompt in shown {
+                out.push_str(&format!("- {}\n", truncate(prompt, detail.prompt_len)));
             }
-            if prompts.len() > 2 {
-                out.push_str(&format!("- _(+{} more)_\n", prompts.len() - 2));
+            if more > 0 {
+                out.push_str(&format!("- _(+{} more)_\n", more));
             }
         }
     }
 
     // Files modified (show filenames only)
     if let Ok(files) = serde_json::from_str::<Vec<String>>(&session.files_modified) {
-        if !files.is_empty() {
-            let names: Vec<&str> = files
+        let (shown, more) = capped(&files, detail.files);
+        if !shown.is_empty() {
+            let names: Vec<&str> = shown
                 .iter()
                 .map(|f| f.rsplit('/').next().unwrap_or(f))
-                .take(8)
                 .collect();
             out.push_str(&format!("**Files modified:** {}", names.join(", ")));
-            if files.len() > 8 {
-                out.push_str(&format!(" (+{})", files.len() - 8));
+            if more > 0 {
+                out.push_str(&format!(" (+{})", more));
             }
             out.push('\n');
+        } else if more > 0 {
+            out.push_str(&format!("**Files modified:** {}\n", more));
         }
     }
 
     // Git commits
     if let Ok(commits) = serde_json::from_str::<Vec<String>>(&session.git_commits) {
-        if !commits.is_empty() {
+        let (shown, more) = capped(&commits, detail.commits);
+        if !shown.is_empty() {
             out.push_str("**Commits:**\n");
-            for commit in &commits {
+            for commit in shown {
                 out.push_str(&format!("- {}\n", commit));
             }
+            if more > 0 {
+                out.push_str(&format!("- _(+{} more)_\n", more));
+            }
+        } else if more > 0 {
+            out.push_str(&format!("**Commits:** {}\n", more));
         }
     }
 
     out
 }
 
-fn format_session_detail(session: &sessions::SessionRow) -> String {
-    let mut out = format_session_summary(session);
+pub fn format_session_detail(session: &sessions::SessionRow, detail: &Detail) -> String {
+    let mut out = format_session_summary(session, detail);
 
     // Full file lists
     if let Ok(files) = serde_json::from_str::<Vec<String>>(&session.files_read) {
         if !files.is_empty() {
+            let (shown, more) = capped(&files, detail.files_read);
             out.push_str(&format!("\n**Files read ({}):**\n", files.len()));
-            for f in files.iter().take(20) {
+            for f in shown {
                 out.push_str(&format!("- {}\n", f));
             }
+            if more > 0 {
+                out.push_str(&format!("- _(+{} more)_\n", more));
+            }
         }
     }
 
     if let Ok(cmds) = serde_json::from_str::<Vec<String>>(&session.commands_run) {
         if !cmds.is_empty() {
+            let (shown, more) = capped(&cmds, detail.commands);
             out.push_str(&format!("\n**Commands ({}):**\n", cmds.len()));
-            for cmd in &cmds {
+            for cmd in shown {
                 out.push_str(&format!("- `{}`\n", cmd));
             }
+            if more > 0 {
+                out.push_str(&format!("- _(+{} more)_\n", more));
+            }
         }
     }
 
@@ -463,9 +940,48 @@ fn format_session_detail(session: &sessions::SessionRow) -> String {
         session.input_tokens, session.output_tokens
     ));
 
+    let shortened = json_len(&session.user_prompts) > detail.prompts
+        || json_len(&session.files_modified) > detail.files
+        || json_len(&session.git_commits) > detail.commits
+        || json_len(&session.files_read) > detail.files_read
+        || json_len(&session.commands_run) > detail.commands;
+    if shortened {
+        out.push_str("\n_Some sections are shortened; call get_session with `verbosity: \"full\"` for everything._\n");
+    }
+
     out
 }
 
+pub fn format_note(note: &notes::NoteRow, detail: &Detail) -> String {
+    format_labeled_note(note, None, detail)
+}
+
+/// A note with its short ID (for `forget` and ID prefixes) and a label after
+/// its date, like `(global)`.
+fn format_labeled_note(note: &notes::NoteRow, label: Option<&str>, detail: &Detail) -> String {
+    let date = &note.created_at[..10.min(note.created_at.len())];
+    let tags: Vec<String> = serde_json::from_str(&note.tags).unwrap_or_default();
+    let tag_display = if tags.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", tags.join(", "))
+    };
+
+    format!(
+        "## {} `{}`{}{}\n{}\n\n",
+        date,
+        note.id.get(..8).unwrap_or(&note.id),
+        label.map(|l| format!(" ({})", l)).unwrap_or_default(),
+        tag_display,
+        truncate(&note.content, detail.note_len)
+    )
+}
+
+/// Number of entries in a JSON array column.
+fn json_len(json: &str) -> usize {
+    serde_json::from_str::<Vec<Value>>(json).map_or(0, |v| v.len())
+}
+
 fn format_duration(seconds: i64) -> String {
     if seconds < 60 {
         format!("{}s", seconds)
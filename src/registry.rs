@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// One known project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    pub db_path: PathBuf,
+    /// When the project was last ingested into, added or found by a scan.
+    pub last_seen: String,
+    /// See [`crate::identity::project_identity`]. Databases in the data
+    /// directory are found by it, since nothing inside the project points
+    /// to them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+}
+
+/// Every project with a memory database this user has, by project
+/// directory: `~/.claude/memory-projects.json`. Ingest adds projects as they
+/// are used; `claude-memory projects` edits it by hand or by scanning.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Registry {
+    pub projects: BTreeMap<PathBuf, RegistryEntry>,
+}
+
+/// `~/.claude/memory-projects.json`
+pub fn registry_path() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".claude").join("memory-projects.json"))
+}
+
+impl Registry {
+    /// Read the registry. `None` when it was never written. A file that
+    /// doesn't parse is an error, so it is not saved over.
+    pub fn load() -> anyhow::Result<Option<Self>> {
+        match registry_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(None),
+        }
+    }
+
+    fn load_from(path: &Path) -> anyhow::Result<Option<Self>> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => anyhow::bail!("Cannot read {}: {}", path.display(), e),
+        };
+        let registry = serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Cannot parse {}: {}; fix or delete it", path.display(), e))?;
+        Ok(Some(registry))
+    }
+
+    /// Write the registry, replacing the file atomically.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = registry_path().ok_or_else(|| anyhow::anyhow!("HOME is not set"))?;
+        self.save_to(&path)
+    }
+
+    fn save_to(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension(format!("json.{}", std::process::id()));
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Add or refresh a project, keeping its identity while the database
+    /// stays the same. Returns whether it was new.
+    pub fn insert(&mut self, project_dir: &Path, db_path: &Path) -> bool {
+        let identity = self
+            .projects
+            .get(project_dir)
+            .filter(|entry| entry.db_path == db_path)
+            .and_then(|entry| entry.identity.clone());
+        let entry = RegistryEntry {
+            db_path: db_path.to_path_buf(),
+            last_seen: now(),
+            identity,
+        };
+        self.projects.insert(project_dir.to_path_buf(), entry).is_none()
+    }
+
+    /// The project with this identity whose database is under `dir`.
+    pub fn find_in(&self, dir: &Path, identity: &str) -> Option<(&PathBuf, &RegistryEntry)> {
+        self.projects
+            .iter()
+            .find(|(_, entry)| entry.identity.as_deref() == Some(identity) && entry.db_path.starts_with(dir))
+    }
+
+    /// Drop projects whose database is gone. Returns their directories.
+    pub fn prune(&mut self) -> Vec<PathBuf> {
+        let dead: Vec<PathBuf> = self
+            .projects
+            .iter()
+            .filter(|(_, entry)| !entry.db_path.is_file())
+            .map(|(dir, _)| dir.clone())
+            .collect();
+        for dir in &dead {
+            self.projects.remove(dir);
+        }
+        dead
+    }
+}
+
+/// Record that a project's database is in use. Rewrites the file at most
+/// once a day per project, since ingest calls this every session. A project
+/// seen at a new path replaces the entry with the same database.
+pub fn touch(project_dir: &Path, db_path: &Path, identity: &str) -> anyhow::Result<()> {
+    let path = registry_path().ok_or_else(|| anyhow::anyhow!("HOME is not set"))?;
+    touch_in(&path, project_dir, db_path, identity)
+}
+
+fn touch_in(path: &Path, project_dir: &Path, db_path: &Path, identity: &str) -> anyhow::Result<()> {
+    let mut registry = Registry::load_from(path)?.unwrap_or_default();
+    let today = &now()[..10];
+    if let Some(entry) = registry.projects.get(project_dir) {
+        if entry.db_path == db_path && entry.identity.as_deref() == Some(identity) && entry.last_seen.starts_with(today) {
+            return Ok(());
+        }
+    }
+    registry.projects.retain(|dir, entry| dir == project_dir || entry.db_path != db_path);
+    let entry = RegistryEntry {
+        db_path: db_path.to_path_buf(),
+        last_seen: now(),
+        identity: Some(identity.to_string()),
+    };
+    registry.projects.insert(project_dir.to_path_buf(), entry);
+    registry.save_to(path)
+}
+
+fn now() -> String {
+    chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn touch_registers_and_prune_drops_dead_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memory-projects.json");
+        assert!(Registry::load_from(&path).unwrap().is_none());
+
+        let live = dir.path().join("live.db");
+        std::fs::write(&live, "").unwrap();
+        touch_in(&path, Path::new("/p/live"), &live, "path:/p/live").unwrap();
+        touch_in(&path, Path::new("/p/dead"), &dir.path().join("dead.db"), "path:/p/dead").unwrap();
+
+        // Once a day is enough
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        touch_in(&path, Path::new("/p/live"), &live, "path:/p/live").unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().modified().unwrap(), modified);
+
+        let mut registry = Registry::load_from(&path).unwrap().unwrap();
+        assert_eq!(registry.projects.len(), 2);
+        assert_eq!(registry.prune(), [PathBuf::from("/p/dead")]);
+        assert_eq!(registry.projects.keys().collect::<Vec<_>>(), [Path::new("/p/live")]);
+
+        // A damaged file is reported, not replaced by a fresh registry
+        std::fs::write(&path, "{\"projects\": {").unwrap();
+        assert!(touch_in(&path, Path::new("/p/new"), &live, "path:/p/new").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"projects\": {");
+    }
+
+    #[test]
+    fn databases_are_found_by_identity_after_a_move() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memory-projects.json");
+        let data = dir.path().join("data");
+        let db = data.join("app-1234").join("memory.db");
+        let remote = "remote:github.com/owner/app";
+
+        touch_in(&path, Path::new("/old/app"), &db, remote).unwrap();
+        touch_in(&path, Path::new("/new/app"), &db, remote).unwrap();
+        let registry = Registry::load_from(&path).unwrap().unwrap();
+        assert_eq!(registry.projects.keys().collect::<Vec<_>>(), [Path::new("/new/app")]);
+        assert_eq!(registry.find_in(&data, remote).unwrap().1.db_path, db);
+
+        // Back in the project, the database is no longer looked up by identity
+        touch_in(&path, Path::new("/new/app"), Path::new("/new/app/.claude/memory.db"), remote).unwrap();
+        assert!(Registry::load_from(&path).unwrap().unwrap().find_in(&data, remote).is_none());
+    }
+}
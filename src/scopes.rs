@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::path::{Component, Path};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::config::ScopeSettings;
+
+/// The sub-projects of a monorepo: directories matching `scopes.roots`, plus
+/// the workspace members of Cargo.toml and package.json when
+/// `scopes.detect` is on. A scope is named by its path from the project root,
+/// e.g. `packages/api`.
+pub struct Scopes {
+    patterns: Vec<String>,
+    set: GlobSet,
+}
+
+impl Scopes {
+    pub fn load(project_dir: &Path, settings: &ScopeSettings) -> anyhow::Result<Self> {
+        let mut patterns: Vec<String> = settings.roots.clone();
+        if settings.detect {
+            patterns.extend(workspace_members(project_dir));
+        }
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &patterns {
+            let glob = GlobBuilder::new(pattern.trim_start_matches("./").trim_matches('/'))
+                .literal_separator(true)
+                .build()
+                .map_err(|e| anyhow::anyhow!("Invalid scope root {:?}: {}", pattern, e))?;
+            builder.add(glob);
+        }
+        Ok(Self { patterns, set: builder.build()? })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// The configured and detected root patterns.
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// The scope containing a directory: the deepest scope root it is in.
+    /// `None` for the rest of the repository and for paths outside it.
+    pub fn scope_of(&self, project_dir: &Path, dir: &Path) -> Option<String> {
+        let relative = if dir.is_absolute() { dir.strip_prefix(project_dir).ok()? } else { dir };
+        let parts: Vec<String> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+        (1..=parts.len())
+            .rev()
+            .map(|n| parts[..n].join("/"))
+            .find(|prefix| self.set.is_match(prefix))
+    }
+
+    /// The scope a session worked in: the one of its working directory, else
+    /// the one most of its modified files (or, without any, read files) are in.
+    pub fn session_scope<'a>(
+        &self,
+        project_dir: &Path,
+        cwd: &str,
+        files_modified: impl IntoIterator<Item = &'a String>,
+        files_read: impl IntoIterator<Item = &'a String>,
+    ) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        if let Some(scope) = self.scope_of(project_dir, Path::new(cwd)) {
+            return Some(scope);
+        }
+        let modified: Vec<&String> = files_modified.into_iter().collect();
+        let files = if modified.is_empty() { files_read.into_iter().collect() } else { modified };
+        self.majority_scope(project_dir, &files)
+    }
+
+    fn majority_scope(&self, project_dir: &Path, files: &[&String]) -> Option<String> {
+        let mut votes: HashMap<Option<String>, usize> = HashMap::new();
+        for file in files {
+            let dir = Path::new(file.as_str()).parent().unwrap_or(Path::new(""));
+            *votes.entry(self.scope_of(project_dir, dir)).or_default() += 1;
+        }
+        let best = votes.values().copied().max()?;
+        let mut leaders = votes.into_iter().filter(|(_, count)| *count == best);
+        match (leaders.next(), leaders.next()) {
+            (Some((scope, _)), None) => scope,
+            _ => None, // a tie is repository-wide work
+        }
+    }
+}
+
+/// Member patterns of a Cargo workspace and of npm/yarn workspaces.
+fn workspace_members(project_dir: &Path) -> Vec<String> {
+    let mut members = Vec::new();
+    if let Some(cargo) = std::fs::read_to_string(project_dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok())
+    {
+        let list = cargo.get("workspace").and_then(|w| w.get("members")).and_then(|m| m.as_array());
+        members.extend(list.into_iter().flatten().filter_map(|m| m.as_str()).map(String::from));
+    }
+    if let Some(package) = std::fs::read_to_string(project_dir.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+    {
+        // Either a list, or `{ "packages": [...] }` (yarn)
+        let workspaces = package.get("workspaces");
+        let list = workspaces
+            .and_then(|w| w.as_array())
+            .or_else(|| workspaces?.get("packages")?.as_array());
+        members.extend(list.into_iter().flatten().filter_map(|m| m.as_str()).map(String::from));
+    }
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scopes(roots: &[&str]) -> Scopes {
+        let settings = ScopeSettings { roots: roots.iter().map(|r| r.to_string()).collect(), detect: false };
+        Scopes::load(Path::new("/repo"), &settings).unwrap()
+    }
+
+    #[test]
+    fn deepest_matching_root_wins() {
+        let scopes = scopes(&["packages/*", "packages/api/plugins/*", "tools"]);
+        let root = Path::new("/repo");
+        assert_eq!(scopes.scope_of(root, Path::new("/repo/packages/web/src")).as_deref(), Some("packages/web"));
+        assert_eq!(
+            scopes.scope_of(root, Path::new("/repo/packages/api/plugins/auth/lib")).as_deref(),
+            Some("packages/api/plugins/auth")
+        );
+        assert_eq!(scopes.scope_of(root, Path::new("tools/bin")).as_deref(), Some("tools"));
+        assert_eq!(scopes.scope_of(root, Path::new("/repo/docs")), None);
+        assert_eq!(scopes.scope_of(root, Path::new("/repo")), None);
+        assert_eq!(scopes.scope_of(root, Path::new("/elsewhere/packages/web")), None);
+    }
+
+    #[test]
+    fn session_scope_falls_back_to_files() {
+        let scopes = scopes(&["packages/*"]);
+        let root = Path::new("/repo");
+        let files = |paths: &[&str]| paths.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        let web = files(&["/repo/packages/web/a.ts", "/repo/packages/web/b.ts", "/repo/README.md"]);
+        let none = files(&[]);
+
+        assert_eq!(scopes.session_scope(root, "/repo/packages/api", &web, &none).as_deref(), Some("packages/api"));
+        assert_eq!(scopes.session_scope(root, "/repo", &web, &none).as_deref(), Some("packages/web"));
+        assert_eq!(scopes.session_scope(root, "/repo", &none, &web).as_deref(), Some("packages/web"));
+        let split = files(&["/repo/packages/web/a.ts", "/repo/packages/api/b.ts"]);
+        assert_eq!(scopes.session_scope(root, "/repo", &split, &none), None);
+    }
+
+    #[test]
+    fn detects_cargo_and_npm_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[workspace]\nmembers = [\"crates/*\"]\n").unwrap();
+        std::fs::write(dir.path().join("package.json"), r#"{"workspaces": {"packages": ["apps/*"]}}"#).unwrap();
+        let settings = ScopeSettings { roots: Vec::new(), detect: true };
+        let scopes = Scopes::load(dir.path(), &settings).unwrap();
+        assert_eq!(scopes.patterns(), ["crates/*", "apps/*"]);
+    }
+}
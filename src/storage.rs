@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use crate::identity;
+use crate::registry::Registry;
+
+/// `$XDG_DATA_HOME/claude-memory`, or `~/.local/share/claude-memory`.
+pub fn data_dir() -> Option<PathBuf> {
+    let base = match std::env::var("XDG_DATA_HOME") {
+        // The spec says relative paths are invalid and must be ignored
+        Ok(dir) if Path::new(&dir).is_absolute() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var("HOME").ok()?).join(".local").join("share"),
+    };
+    Some(base.join("claude-memory"))
+}
+
+/// Name of a project's directory in the data directory: a readable name plus
+/// a hash of the project's identity, e.g. `my-app-3f2a1b9c8d7e6f5a`. Clones
+/// and renamed checkouts of a repository get the same ID.
+fn id_for(identity: &str, project_dir: &Path) -> String {
+    let name: String = identity::display_name(identity, project_dir)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') { c } else { '-' })
+        .collect();
+    let name = name.trim_start_matches('.');
+    let hash = format!("{:016x}", fnv1a(identity.as_bytes()));
+    if name.is_empty() {
+        hash
+    } else {
+        format!("{}-{}", name, hash)
+    }
+}
+
+/// FNV-1a: a hash that, unlike `DefaultHasher`, never changes between builds.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// `<data dir>/<project id>/memory.db`, or wherever the project registry
+/// has the database of a project with the same identity.
+pub fn data_db_path(project_dir: &Path) -> Option<PathBuf> {
+    let dir = data_dir()?;
+    let identity = identity::project_identity(project_dir);
+    // Unreadable, the registry has nothing to add to the default location
+    let registry = Registry::load().ok().flatten().unwrap_or_default();
+    match registry.find_in(&dir, &identity) {
+        Some((_, entry)) => Some(entry.db_path.clone()),
+        None => Some(dir.join(id_for(&identity, project_dir)).join("memory.db")),
+    }
+}
+
+/// Hand the database registered for `old_dir` to the project at `new_dir`,
+/// for projects whose identity changed with the move (those outside git).
+/// Returns whether there was one to hand over.
+pub fn relink(old_dir: &Path, new_dir: &Path) -> anyhow::Result<bool> {
+    let dir = data_dir().ok_or_else(|| anyhow::anyhow!("HOME is not set"))?;
+    let mut registry = Registry::load()?.unwrap_or_default();
+    let identity = identity::project_identity(new_dir);
+    if registry.find_in(&dir, &identity).is_some() {
+        return Ok(false);
+    }
+    let Some(mut entry) = registry.projects.remove(old_dir).filter(|entry| entry.db_path.starts_with(&dir)) else {
+        return Ok(false);
+    };
+    entry.identity = Some(identity);
+    registry.projects.insert(new_dir.to_path_buf(), entry);
+    registry.save()?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_ids_are_readable_and_follow_identity() {
+        let here = Path::new("/work/My App");
+        let id = id_for("path:/work/My App", here);
+        assert!(id.starts_with("My-App-"), "{}", id);
+        assert_ne!(id, id_for("path:/other/My App", here));
+
+        // A remote names the project, wherever it is checked out
+        let remote = "remote:github.com/owner/repo";
+        assert_eq!(id_for(remote, here), id_for(remote, Path::new("/elsewhere/clone")));
+        assert!(id_for(remote, here).starts_with("repo-"));
+        assert_eq!(id_for("path:/", Path::new("/")).len(), 16);
+    }
+}
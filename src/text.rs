@@ -0,0 +1,28 @@
+//! Shortening text for display.
+
+/// Truncate `s` to at most `max_len` bytes (on a char boundary), adding `...`.
+pub fn truncate(s: &str, max_len: usize) -> String {
+    if s.len() <= max_len {
+        s.to_string()
+    } else {
+        format!("{}...", &s[..s.floor_char_boundary(max_len)])
+    }
+}
+
+/// [`truncate`] after collapsing whitespace and newlines, for list rows.
+pub fn one_line(s: &str, max_len: usize) -> String {
+    truncate(&s.split_whitespace().collect::<Vec<_>>().join(" "), max_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_on_char_boundaries() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("héllo wörld", 2), "h...");
+        assert_eq!(one_line("fix\n  the   parser", 20), "fix the parser");
+        assert_eq!(one_line("fix\n  the   parser", 7), "fix the...");
+    }
+}
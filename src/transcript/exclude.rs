@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use super::metadata::SessionMetadata;
+
+/// Matches paths against gitignore-style exclusion globs: a pattern without
+/// `/` (like `.env`) matches that name at any depth, one with `/` (like
+/// `secrets/**`) is anchored at the project root. A matching directory
+/// excludes everything under it.
+pub struct PathFilter {
+    globs: GlobSet,
+    root: PathBuf,
+}
+
+impl PathFilter {
+    pub fn new(patterns: &[String], project_dir: &Path) -> anyhow::Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let anchored = match pattern.trim_end_matches('/') {
+                p if p.contains('/') => p.trim_start_matches('/').to_string(),
+                p => format!("**/{}", p),
+            };
+            for glob in [anchored.clone(), format!("{}/**", anchored)] {
+                builder.add(
+                    GlobBuilder::new(&glob)
+                        .literal_separator(true)
+                        .build()
+                        .map_err(|e| anyhow::anyhow!("Invalid exclude pattern `{}`: {}", pattern, e))?,
+                );
+            }
+        }
+        Ok(Self {
+            globs: builder.build()?,
+            root: project_dir.to_path_buf(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.globs.is_empty()
+    }
+
+    /// Whether `path` (absolute, or relative to the project root) is excluded.
+    pub fn is_excluded(&self, path: &str) -> bool {
+        let path = Path::new(path);
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        self.globs.is_match(relative.strip_prefix("/").unwrap_or(relative))
+    }
+
+    /// Whether any word of a shell command is an excluded path.
+    pub fn mentions_excluded(&self, command: &str) -> bool {
+        command
+            .split_whitespace()
+            .map(|word| word.trim_matches(|c| matches!(c, '\'' | '"' | '<' | '>' | '(' | ')' | ';')))
+            .filter(|word| !word.is_empty() && !word.starts_with('-'))
+            .any(|word| self.is_excluded(word))
+    }
+
+    /// Drop excluded files and the commands that mention them. Returns how many
+    /// entries were dropped.
+    pub fn filter_metadata(&self, meta: &mut SessionMetadata) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let before = meta.files_read.len() + meta.files_modified.len() + meta.commands_run.len();
+        meta.files_read.retain(|f| !self.is_excluded(f));
+        meta.files_modified.retain(|f| !self.is_excluded(f));
+        meta.commands_run.retain(|c| !self.mentions_excluded(c));
+        before - (meta.files_read.len() + meta.files_modified.len() + meta.commands_run.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(patterns: &[&str]) -> PathFilter {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        PathFilter::new(&patterns, Path::new("/work/app")).unwrap()
+    }
+
+    #[test]
+    fn matches_like_gitignore() {
+        let f = filter(&[".env", "secrets/**", "*.pem", "/config/prod.yaml"]);
+        assert!(f.is_excluded("/work/app/.env"));
+        assert!(f.is_excluded("/work/app/services/api/.env"));
+        assert!(f.is_excluded("/work/app/secrets/db/password.txt"));
+        assert!(f.is_excluded("/home/me/certs/server.pem"));
+        assert!(f.is_excluded("config/prod.yaml"));
+        assert!(!f.is_excluded("/work/app/.env.example"));
+        assert!(!f.is_excluded("/work/app/src/secrets/mod.rs"));
+        assert!(!f.is_excluded("/work/app/services/config/prod.yaml"));
+    }
+
+    #[test]
+    fn drops_files_and_commands_that_mention_them() {
+        let f = filter(&[".env", "secrets"]);
+        let mut meta = SessionMetadata::default();
+        meta.files_read.extend(["/work/app/.env".to_string(), "/work/app/src/main.rs".to_string()]);
+        meta.files_modified.insert("/work/app/secrets/token".to_string());
+        meta.commands_run = vec![
+            "cat .env".to_string(),
+            "grep -r KEY \"secrets/prod\"".to_string(),
+            "cargo test".to_string(),
+        ];
+
+        assert_eq!(f.filter_metadata(&mut meta), 4);
+        assert_eq!(meta.files_read.into_iter().collect::<Vec<_>>(), ["/work/app/src/main.rs"]);
+        assert!(meta.files_modified.is_empty());
+        assert_eq!(meta.commands_run, ["cargo test"]);
+    }
+}
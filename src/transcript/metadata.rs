@@ -9,6 +9,8 @@ pub struct SessionMetadata {
     pub project_dir: String,
     pub git_branch: Option<String>,
     pub model: Option<String>,
+    /// Where the transcript was read from, so it can be shown again later.
+    pub transcript_path: Option<String>,
 
     pub first_timestamp: Option<String>,
     pub last_timestamp: Option<String>,
@@ -23,6 +25,9 @@ pub struct SessionMetadata {
 
     pub total_input_tokens: u64,
     pub total_output_tokens: u64,
+
+    /// Monorepo sub-project the session worked in; set at ingest.
+    pub scope: Option<String>,
 }
 
 impl SessionMetadata {
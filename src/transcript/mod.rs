@@ -1,2 +1,5 @@
+pub mod exclude;
 pub mod metadata;
 pub mod parser;
+pub mod redact;
+pub mod render;
@@ -4,13 +4,12 @@ use std::path::Path;
 use serde_json::Value;
 
 use super::metadata::SessionMetadata;
-
-const MAX_COMMANDS: usize = 50;
-const MAX_COMMAND_LEN: usize = 200;
+use crate::config::ParserSettings;
+use crate::text::truncate;
 
 /// Parse a Claude Code transcript JSONL file, extracting session metadata.
 /// Streams line-by-line to handle large files efficiently.
-pub fn parse_transcript(path: &Path) -> anyhow::Result<SessionMetadata> {
+pub fn parse_transcript(path: &Path, limits: &ParserSettings) -> anyhow::Result<SessionMetadata> {
     let file = std::fs::File::open(path)?;
     let reader = BufReader::new(file);
 
@@ -54,9 +53,9 @@ pub fn parse_transcript(path: &Path) -> anyhow::Result<SessionMetadata> {
         }
 
         match value.get("type").and_then(|t| t.as_str()) {
-            Some("user") => extract_user_message(&value, &mut meta),
+            Some("user") => extract_user_message(&value, &mut meta, limits),
             Some("assistant") => {
-                extract_assistant_message(&value, &mut meta, &mut seen_commands);
+                extract_assistant_message(&value, &mut meta, &mut seen_commands, limits);
             }
             _ => {} // Skip progress, file-history-snapshot, system, etc.
         }
@@ -67,7 +66,7 @@ pub fn parse_transcript(path: &Path) -> anyhow::Result<SessionMetadata> {
 }
 
 /// Extract data from a user message.
-fn extract_user_message(value: &Value, meta: &mut SessionMetadata) {
+fn extract_user_message(value: &Value, meta: &mut SessionMetadata, limits: &ParserSettings) {
     let content = match value.get("message").and_then(|m| m.get("content")) {
         Some(c) => c,
         None => return,
@@ -77,7 +76,7 @@ fn extract_user_message(value: &Value, meta: &mut SessionMetadata) {
     if let Some(text) = content.as_str() {
         // Skip meta/system messages (commands, local-command-stdout, etc.)
         if !text.starts_with('<') && !text.is_empty() {
-            meta.user_prompts.push(truncate(text, 2000));
+            meta.user_prompts.push(truncate(text, limits.max_prompt_len));
         }
     }
 
@@ -91,7 +90,7 @@ fn extract_user_message(value: &Value, meta: &mut SessionMetadata) {
             }
             if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
                 if !text.starts_with('<') && !text.is_empty() {
-                    meta.user_prompts.push(truncate(text, 2000));
+                    meta.user_prompts.push(truncate(text, limits.max_prompt_len));
                 }
             }
         }
@@ -103,6 +102,7 @@ fn extract_assistant_message(
     value: &Value,
     meta: &mut SessionMetadata,
     seen_commands: &mut std::collections::HashSet<String>,
+    limits: &ParserSettings,
 ) {
     let message = match value.get("message") {
         Some(m) => m,
@@ -173,8 +173,8 @@ fn extract_assistant_message(
             }
             "Bash" => {
                 if let Some(cmd) = input.and_then(|i| i.get("command")).and_then(|c| c.as_str()) {
-                    let truncated = truncate(cmd, MAX_COMMAND_LEN);
-                    if meta.commands_run.len() < MAX_COMMANDS
+                    let truncated = truncate(cmd, limits.max_command_len);
+                    if meta.commands_run.len() < limits.max_commands
                         && seen_commands.insert(truncated.clone())
                     {
                         meta.commands_run.push(truncated.clone());
@@ -210,14 +210,6 @@ fn extract_commit_message(cmd: &str) -> Option<String> {
     None
 }
 
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..s.floor_char_boundary(max_len)])
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -238,7 +230,7 @@ mod tests {
             r#"{"type":"user","sessionId":"test-123","cwd":"/home/test","gitBranch":"main","message":{"role":"user","content":"Hello, let's fix the bug"},"timestamp":"2026-02-21T10:00:00Z"}"#,
         ]);
 
-        let meta = parse_transcript(fixture.path()).unwrap();
+        let meta = parse_transcript(fixture.path(), &ParserSettings::default()).unwrap();
         assert_eq!(meta.session_id, "test-123");
         assert_eq!(meta.project_dir, "/home/test");
         assert_eq!(meta.git_branch, Some("main".to_string()));
@@ -251,7 +243,7 @@ mod tests {
             r#"{"type":"assistant","sessionId":"test-123","cwd":"/home/test","message":{"model":"claude-opus-4-6","role":"assistant","content":[{"type":"tool_use","name":"Write","input":{"file_path":"/home/test/foo.rs"}},{"type":"tool_use","name":"Bash","input":{"command":"cargo test"}}],"usage":{"input_tokens":100,"output_tokens":50}},"timestamp":"2026-02-21T10:01:00Z"}"#,
         ]);
 
-        let meta = parse_transcript(fixture.path()).unwrap();
+        let meta = parse_transcript(fixture.path(), &ParserSettings::default()).unwrap();
         assert_eq!(meta.model, Some("claude-opus-4-6".to_string()));
         assert!(meta.files_modified.contains("/home/test/foo.rs"));
         assert_eq!(meta.commands_run, vec!["cargo test"]);
@@ -267,7 +259,7 @@ mod tests {
             r#"{"type":"user","sessionId":"test-123","cwd":"/home/test","message":{"role":"user","content":"<local-command-caveat>skip this</local-command-caveat>"},"timestamp":"2026-02-21T10:00:00Z"}"#,
         ]);
 
-        let meta = parse_transcript(fixture.path()).unwrap();
+        let meta = parse_transcript(fixture.path(), &ParserSettings::default()).unwrap();
         assert!(meta.user_prompts.is_empty());
     }
 
@@ -278,7 +270,7 @@ mod tests {
             r#"{"type":"user","sessionId":"test-123","cwd":"/home/test","message":{"role":"user","content":"valid message"},"timestamp":"2026-02-21T10:00:00Z"}"#,
         ]);
 
-        let meta = parse_transcript(fixture.path()).unwrap();
+        let meta = parse_transcript(fixture.path(), &ParserSettings::default()).unwrap();
         assert_eq!(meta.user_prompts, vec!["valid message"]);
     }
 
@@ -295,7 +287,7 @@ mod tests {
             r#"{"type":"user","sessionId":"test-123","cwd":"/home/test","message":{"role":"user","content":"end"},"timestamp":"2026-02-21T10:30:00Z"}"#,
         ]);
 
-        let meta = parse_transcript(fixture.path()).unwrap();
+        let meta = parse_transcript(fixture.path(), &ParserSettings::default()).unwrap();
         assert_eq!(meta.duration_seconds, Some(1800));
     }
 }
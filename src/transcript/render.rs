@@ -0,0 +1,148 @@
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::text::one_line;
+
+/// Longest tool input summary shown per tool call.
+const MAX_TOOL_SUMMARY: usize = 120;
+
+/// Render a Claude Code transcript as a readable conversation.
+///
+/// User prompts and assistant text are printed in full; tool calls are
+/// condensed to one line each. Tool results and meta messages are skipped.
+pub fn render_transcript(path: &Path, out: &mut impl Write) -> anyhow::Result<()> {
+    let file = std::fs::File::open(path)?;
+    let reader = BufReader::new(file);
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let value: Value = match serde_json::from_str(&line) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+
+        let time = value
+            .get("timestamp")
+            .and_then(|t| t.as_str())
+            .map(|t| t.get(..16).unwrap_or(t).replace('T', " "))
+            .unwrap_or_default();
+
+        match value.get("type").and_then(|t| t.as_str()) {
+            Some("user") => {
+                let texts = user_texts(&value);
+                if !texts.is_empty() {
+                    writeln!(out, "── {} user ──", time)?;
+                    for text in texts {
+                        writeln!(out, "{}\n", text)?;
+                    }
+                }
+            }
+            Some("assistant") => {
+                let blocks = assistant_blocks(&value);
+                if !blocks.is_empty() {
+                    writeln!(out, "── {} assistant ──", time)?;
+                    for block in blocks {
+                        writeln!(out, "{}", block)?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Prompt text from a user message, skipping tool results and meta messages.
+fn user_texts(value: &Value) -> Vec<String> {
+    let content = match value.get("message").and_then(|m| m.get("content")) {
+        Some(c) => c,
+        None => return Vec::new(),
+    };
+
+    let is_prompt = |text: &str| !text.starts_with('<') && !text.is_empty();
+
+    if let Some(text) = content.as_str() {
+        return if is_prompt(text) { vec![text.to_string()] } else { Vec::new() };
+    }
+
+    content
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .filter(|item| item.get("type").and_then(|t| t.as_str()) != Some("tool_result"))
+                .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+                .filter(|text| is_prompt(text))
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Text blocks and one-line tool call summaries from an assistant message.
+fn assistant_blocks(value: &Value) -> Vec<String> {
+    let content = match value
+        .get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())
+    {
+        Some(c) => c,
+        None => return Vec::new(),
+    };
+
+    let mut blocks = Vec::new();
+    for item in content {
+        match item.get("type").and_then(|t| t.as_str()) {
+            Some("text") => {
+                if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
+                    blocks.push(text.to_string());
+                }
+            }
+            Some("tool_use") => {
+                let name = item.get("name").and_then(|n| n.as_str()).unwrap_or("?");
+                let input = item.get("input");
+                let summary = ["command", "file_path", "pattern", "url", "description"]
+                    .iter()
+                    .find_map(|key| input.and_then(|i| i.get(*key)).and_then(|v| v.as_str()))
+                    .unwrap_or("");
+                blocks.push(format!("  → {}: {}", name, one_line(summary, MAX_TOOL_SUMMARY)));
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn renders_prompts_text_and_tool_calls() {
+        let mut file = NamedTempFile::new().unwrap();
+        for line in [
+            r#"{"type":"user","message":{"role":"user","content":"Fix the parser"},"timestamp":"2026-02-21T10:00:00Z"}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Running tests."},{"type":"tool_use","name":"Bash","input":{"command":"cargo test\n  --all"}}]},"timestamp":"2026-02-21T10:01:00Z"}"#,
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"ok"}]},"timestamp":"2026-02-21T10:02:00Z"}"#,
+        ] {
+            writeln!(file, "{}", line).unwrap();
+        }
+
+        let mut out = Vec::new();
+        render_transcript(file.path(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("── 2026-02-21 10:00 user ──\nFix the parser"));
+        assert!(out.contains("Running tests."));
+        assert!(out.contains("  → Bash: cargo test --all"));
+        assert!(!out.contains("10:02"));
+    }
+}
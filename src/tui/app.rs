@@ -0,0 +1,373 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::ListState;
+use rusqlite::Connection;
+
+use crate::db::notes::{self, NoteRow};
+use crate::db::sessions::{self, SessionRow};
+use crate::mcp::budget::DETAIL_LEVELS;
+use crate::mcp::tools;
+
+/// Most rows loaded into either list.
+const LIST_LIMIT: usize = 500;
+
+/// Rows moved by PageUp / PageDown.
+const PAGE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Sessions,
+    Notes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Browse,
+    /// Typing into the search box; results update on every keystroke.
+    Search,
+    /// Waiting for `y` to confirm deleting the selected note.
+    ConfirmDelete,
+}
+
+pub struct App {
+    conn: Connection,
+    pub tab: Tab,
+    pub mode: Mode,
+    pub query: String,
+    pub sessions: Vec<SessionRow>,
+    pub notes: Vec<NoteRow>,
+    pub session_state: ListState,
+    pub note_state: ListState,
+    pub detail_scroll: u16,
+    pub status: String,
+    pub should_quit: bool,
+    /// Text the event loop should copy to the clipboard after the next draw.
+    copy_request: Option<String>,
+}
+
+impl App {
+    pub fn new(conn: Connection) -> anyhow::Result<Self> {
+        let mut app = App {
+            conn,
+            tab: Tab::Sessions,
+            mode: Mode::Browse,
+            query: String::new(),
+            sessions: Vec::new(),
+            notes: Vec::new(),
+            session_state: ListState::default(),
+            note_state: ListState::default(),
+            detail_scroll: 0,
+            status: String::new(),
+            should_quit: false,
+            copy_request: None,
+        };
+        app.sessions = sessions::list_sessions(&app.conn, LIST_LIMIT, None, None, None, None)?;
+        app.notes = notes::search_notes(&app.conn, None, None, LIST_LIMIT)?;
+        app.session_state.select((!app.sessions.is_empty()).then_some(0));
+        app.note_state.select((!app.notes.is_empty()).then_some(0));
+        Ok(app)
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.should_quit = true;
+            return;
+        }
+
+        match self.mode {
+            Mode::Browse => self.browse_key(key),
+            Mode::Search => self.search_key(key),
+            Mode::ConfirmDelete => self.confirm_delete_key(key),
+        }
+    }
+
+    /// Text to copy, if a copy was requested since the last call.
+    pub fn take_copy_request(&mut self) -> Option<String> {
+        self.copy_request.take()
+    }
+
+    fn browse_key(&mut self, key: KeyEvent) {
+        self.status.clear();
+        match key.code {
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Esc if self.query.is_empty() => self.should_quit = true,
+            KeyCode::Esc => {
+                self.query.clear();
+                self.refresh();
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.tab = match self.tab {
+                    Tab::Sessions => Tab::Notes,
+                    Tab::Notes => Tab::Sessions,
+                };
+                self.detail_scroll = 0;
+                self.refresh();
+            }
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Char('j') | KeyCode::Down => self.move_selection(1),
+            KeyCode::Char('k') | KeyCode::Up => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(PAGE as isize),
+            KeyCode::PageUp => self.move_selection(-(PAGE as isize)),
+            KeyCode::Char('g') | KeyCode::Home => self.move_selection(isize::MIN),
+            KeyCode::Char('G') | KeyCode::End => self.move_selection(isize::MAX),
+            KeyCode::Char('J') => self.detail_scroll = self.detail_scroll.saturating_add(1),
+            KeyCode::Char('K') => self.detail_scroll = self.detail_scroll.saturating_sub(1),
+            KeyCode::Char('y') => self.copy_selected_id(),
+            KeyCode::Char('p') if self.tab == Tab::Notes => self.toggle_pin(),
+            KeyCode::Char('d') if self.tab == Tab::Notes => {
+                if let Some(note) = self.selected_note() {
+                    self.status = format!("Delete note {}? (y/n)", short_id(&note.id));
+                    self.mode = Mode::ConfirmDelete;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.mode = Mode::Browse,
+            KeyCode::Esc => {
+                self.mode = Mode::Browse;
+                self.query.clear();
+                self.refresh();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.refresh();
+            }
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.refresh();
+            }
+            _ => {}
+        }
+    }
+
+    fn confirm_delete_key(&mut self, key: KeyEvent) {
+        self.mode = Mode::Browse;
+        if key.code != KeyCode::Char('y') {
+            self.status = "Delete cancelled.".to_string();
+            return;
+        }
+
+        let id = match self.selected_note() {
+            Some(note) => note.id.clone(),
+            None => return,
+        };
+        self.status = match notes::delete_note(&self.conn, &id) {
+            Ok(_) => format!("Deleted note {}.", short_id(&id)),
+            Err(e) => format!("Delete failed: {}", e),
+        };
+        self.refresh();
+    }
+
+    /// Reload the current tab for the current query, keeping the previous
+    /// results (and showing the error) if the query isn't valid FTS syntax yet.
+    fn refresh(&mut self) {
+        let query = self.query.trim();
+        let query = (!query.is_empty()).then_some(query);
+
+        let result = match self.tab {
+            Tab::Sessions => {
+                let rows = match query {
+                    Some(q) => sessions::search_sessions(&self.conn, q, LIST_LIMIT).map(|(rows, _)| rows),
+                    None => sessions::list_sessions(&self.conn, LIST_LIMIT, None, None, None, None),
+                };
+                rows.map(|rows| self.sessions = rows)
+            }
+            Tab::Notes => notes::search_notes(&self.conn, query, None, LIST_LIMIT)
+                .map(|rows| self.notes = rows),
+        };
+
+        match result {
+            Ok(()) => {
+                if self.mode == Mode::Search {
+                    self.status.clear();
+                }
+            }
+            Err(e) => self.status = format!("Search error: {}", e),
+        }
+
+        let len = self.current_len();
+        let state = self.current_state();
+        let selected = state.selected().map(|i| i.min(len.saturating_sub(1)));
+        state.select(if len == 0 { None } else { selected.or(Some(0)) });
+        self.detail_scroll = 0;
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let len = self.current_len();
+        if len == 0 {
+            return;
+        }
+        let state = self.current_state();
+        let current = state.selected().unwrap_or(0) as isize;
+        let next = current.saturating_add(delta).clamp(0, len as isize - 1);
+        state.select(Some(next as usize));
+        self.detail_scroll = 0;
+    }
+
+    fn copy_selected_id(&mut self) {
+        let id = match self.tab {
+            Tab::Sessions => self.selected_session().map(|s| s.id.clone()),
+            Tab::Notes => self.selected_note().map(|n| n.id.clone()),
+        };
+        if let Some(id) = id {
+            self.status = format!("Copied {}", id);
+            self.copy_request = Some(id);
+        }
+    }
+
+    fn toggle_pin(&mut self) {
+        let (id, pinned) = match self.selected_note() {
+            Some(note) => (note.id.clone(), !note.pinned),
+            None => return,
+        };
+        self.status = match notes::set_note_pinned(&self.conn, &id, pinned) {
+            Ok(_) if pinned => format!("Pinned note {}.", short_id(&id)),
+            Ok(_) => format!("Unpinned note {}.", short_id(&id)),
+            Err(e) => format!("Pin failed: {}", e),
+        };
+        self.refresh();
+        // Pinning reorders the list; keep the same note selected
+        if let Some(i) = self.notes.iter().position(|n| n.id == id) {
+            self.note_state.select(Some(i));
+        }
+    }
+
+    pub fn selected_session(&self) -> Option<&SessionRow> {
+        self.session_state.selected().and_then(|i| self.sessions.get(i))
+    }
+
+    pub fn selected_note(&self) -> Option<&NoteRow> {
+        self.note_state.selected().and_then(|i| self.notes.get(i))
+    }
+
+    /// Detail pane text for the current selection.
+    pub fn detail(&self) -> String {
+        match self.tab {
+            Tab::Sessions => self
+                .selected_session()
+                .map(|s| tools::format_session_detail(s, &DETAIL_LEVELS[0]))
+                .unwrap_or_default(),
+            Tab::Notes => self
+                .selected_note()
+                .map(|n| {
+                    let mut out = tools::format_note(n, &DETAIL_LEVELS[0]);
+                    out.push_str(&format!("id: {}\n", n.id));
+                    if let Some(session_id) = &n.session_id {
+                        out.push_str(&format!("session: {}\n", session_id));
+                    }
+                    out
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    fn current_len(&self) -> usize {
+        match self.tab {
+            Tab::Sessions => self.sessions.len(),
+            Tab::Notes => self.notes.len(),
+        }
+    }
+
+    fn current_state(&mut self) -> &mut ListState {
+        match self.tab {
+            Tab::Sessions => &mut self.session_state,
+            Tab::Notes => &mut self.note_state,
+        }
+    }
+}
+
+pub fn short_id(id: &str) -> &str {
+    &id[..8.min(id.len())]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_str(app: &mut App, s: &str) {
+        for c in s.chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+    }
+
+    fn test_app() -> (tempfile::TempDir, App) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = db::open(&dir.path().join("memory.db")).unwrap();
+        conn.execute_batch(
+            "INSERT INTO sessions (id, project_dir, started_at, user_prompts) VALUES
+                ('s-auth', '/p', '2026-01-02', '[\"fix authentication\"]'),
+                ('s-docs', '/p', '2026-01-01', '[\"update docs\"]');
+             INSERT INTO notes (id, content, created_at) VALUES
+                ('n-old', 'old note', '2026-01-01'),
+                ('n-new', 'new note', '2026-01-02');",
+        ).unwrap();
+        let app = App::new(conn).unwrap();
+        (dir, app)
+    }
+
+    #[test]
+    fn search_updates_as_you_type() {
+        let (_dir, mut app) = test_app();
+        assert_eq!(app.sessions.len(), 2);
+
+        app.handle_key(key(KeyCode::Char('/')));
+        type_str(&mut app, "authentication");
+        assert_eq!(app.mode, Mode::Search);
+        assert_eq!(app.sessions.len(), 1);
+        assert!(app.detail().contains("fix authentication"));
+
+        // Esc clears the search rather than quitting
+        app.handle_key(key(KeyCode::Esc));
+        assert!(!app.should_quit);
+        assert_eq!(app.sessions.len(), 2);
+    }
+
+    #[test]
+    fn invalid_query_keeps_previous_results() {
+        let (_dir, mut app) = test_app();
+        app.handle_key(key(KeyCode::Char('/')));
+        type_str(&mut app, "docs \"");
+        assert_eq!(app.sessions.len(), 1);
+        assert!(app.status.starts_with("Search error"));
+    }
+
+    #[test]
+    fn pin_and_delete_notes() {
+        let (_dir, mut app) = test_app();
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.selected_note().unwrap().id, "n-new");
+
+        // Pinning the older note moves it to the top and keeps it selected
+        app.handle_key(key(KeyCode::Char('j')));
+        app.handle_key(key(KeyCode::Char('p')));
+        assert_eq!(app.notes[0].id, "n-old");
+        assert!(app.selected_note().unwrap().pinned);
+
+        app.handle_key(key(KeyCode::Char('d')));
+        app.handle_key(key(KeyCode::Char('n')));
+        assert_eq!(app.notes.len(), 2);
+
+        app.handle_key(key(KeyCode::Char('d')));
+        app.handle_key(key(KeyCode::Char('y')));
+        assert_eq!(app.notes.len(), 1);
+        assert_eq!(app.selected_note().unwrap().id, "n-new");
+    }
+
+    #[test]
+    fn copy_requests_full_session_id() {
+        let (_dir, mut app) = test_app();
+        app.handle_key(key(KeyCode::Char('y')));
+        assert_eq!(app.take_copy_request().as_deref(), Some("s-auth"));
+        assert_eq!(app.take_copy_request(), None);
+    }
+}
@@ -0,0 +1,122 @@
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Tabs, Wrap};
+use ratatui::Frame;
+
+use super::app::{short_id, App, Mode, Tab};
+use crate::text::one_line;
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    draw_header(frame, app, header);
+
+    let [list_area, detail_area] =
+        Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(body);
+
+    let highlight = Style::default().add_modifier(Modifier::REVERSED);
+    match app.tab {
+        Tab::Sessions => {
+            let items: Vec<ListItem> = app
+                .sessions
+                .iter()
+                .map(|s| {
+                    let started = s.started_at.get(..16).unwrap_or(&s.started_at).replace('T', " ");
+                    let first_prompt = serde_json::from_str::<Vec<String>>(&s.user_prompts)
+                        .ok()
+                        .and_then(|p| p.into_iter().next())
+                        .map(|p| one_line(&p, 80))
+                        .unwrap_or_default();
+                    ListItem::new(Line::from(vec![
+                        Span::styled(short_id(&s.id).to_string(), Style::default().fg(Color::Yellow)),
+                        Span::raw(format!(" {} ", started)),
+                        Span::styled(
+                            s.git_branch.clone().unwrap_or_default(),
+                            Style::default().fg(Color::Cyan),
+                        ),
+                        Span::raw(format!(" {}", first_prompt)),
+                    ]))
+                })
+                .collect();
+            let title = format!(" Sessions ({}) ", app.sessions.len());
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .highlight_style(highlight);
+            frame.render_stateful_widget(list, list_area, &mut app.session_state);
+        }
+        Tab::Notes => {
+            let items: Vec<ListItem> = app
+                .notes
+                .iter()
+                .map(|n| {
+                    let tags: Vec<String> = serde_json::from_str(&n.tags).unwrap_or_default();
+                    ListItem::new(Line::from(vec![
+                        Span::raw(if n.pinned { "* " } else { "  " }),
+                        Span::styled(short_id(&n.id).to_string(), Style::default().fg(Color::Yellow)),
+                        Span::raw(format!(" {} ", n.created_at.get(..10).unwrap_or(&n.created_at))),
+                        Span::styled(
+                            if tags.is_empty() { String::new() } else { format!("[{}] ", tags.join(", ")) },
+                            Style::default().fg(Color::Cyan),
+                        ),
+                        Span::raw(one_line(&n.content, 80)),
+                    ]))
+                })
+                .collect();
+            let title = format!(" Notes ({}) ", app.notes.len());
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .highlight_style(highlight);
+            frame.render_stateful_widget(list, list_area, &mut app.note_state);
+        }
+    }
+
+    let detail = Paragraph::new(app.detail())
+        .block(Block::default().borders(Borders::ALL).title(" Detail "))
+        .wrap(Wrap { trim: false })
+        .scroll((app.detail_scroll, 0));
+    frame.render_widget(detail, detail_area);
+
+    frame.render_widget(Paragraph::new(footer_line(app)), footer);
+}
+
+fn draw_header(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let [tabs_area, search_area] =
+        Layout::horizontal([Constraint::Length(24), Constraint::Min(10)]).areas(area);
+
+    let selected = match app.tab {
+        Tab::Sessions => 0,
+        Tab::Notes => 1,
+    };
+    let tabs = Tabs::new(vec!["Sessions", "Notes"])
+        .select(selected)
+        .highlight_style(Style::default().bold().fg(Color::Yellow));
+    frame.render_widget(tabs, tabs_area);
+
+    let search = if app.mode == Mode::Search {
+        Line::from(vec![Span::raw("/ "), Span::raw(app.query.as_str()), Span::raw("█")])
+    } else if app.query.is_empty() {
+        Line::from(Span::styled("/ to search", Style::default().dim()))
+    } else {
+        Line::from(vec![Span::raw("/ "), Span::styled(app.query.as_str(), Style::default().bold())])
+    };
+    frame.render_widget(Paragraph::new(search), search_area);
+}
+
+fn footer_line(app: &App) -> Line<'_> {
+    if !app.status.is_empty() {
+        return Line::from(app.status.as_str());
+    }
+
+    let keys = match (app.mode, app.tab) {
+        (Mode::Search, _) => "type to search  enter done  esc clear  ↑↓ move",
+        (_, Tab::Sessions) => "j/k move  J/K scroll  / search  y copy id  tab notes  q quit",
+        (_, Tab::Notes) => "j/k move  J/K scroll  / search  p pin  d delete  y copy id  tab sessions  q quit",
+    };
+    Line::from(Span::styled(keys, Style::default().dim()))
+}